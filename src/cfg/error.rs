@@ -1,6 +1,7 @@
 use std::path::PathBuf;
 
 use crate::cfg::VarName;
+use crate::env_file::EnvLocation;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -15,20 +16,28 @@ pub enum CfgError {
     PrivateEnvDirNotFound(String),
     #[error("private env dir must be an absolute path `{0:?}` for `{1}`")]
     PrivateEnvDirRelativePath(PathBuf, String),
+    #[error("private env source not found for `{0}`")]
+    PrivateEnvSourceNotFound(String),
+    #[error("private env source already unset for `{0}`")]
+    PrivateEnvSourceAlreadyUnset(String),
     #[error("public env dir not found for `{0}`")]
     PublicEnvDirNotFound(String),
     #[error("public env dir already unset for `{0}`")]
     PublicEnvAlreadyUnset(String),
     #[error("private env dir already unset for `{0}`")]
     PrivateEnvAlreadyUnset(String),
-    #[error("there is two `{0}` env `{1:?}` and `{2:?}`, please remove once")]
-    EnvExistTwice(String, PathBuf, PathBuf),
+    #[error("there is two `{0}` env `{1:?}` and `{2}`, please remove once")]
+    EnvExistTwice(String, PathBuf, EnvLocation),
     #[error("env {0} not found")]
     EnvNotFound(String),
     #[error("format not found for array_vars `{0}`")]
     FormatNotFound(VarName),
     #[error("delimiter not found for array_vars `{0}`")]
     DelimiterNotFound(VarName),
+    #[error("command `{0}` not found in setup `{1}`")]
+    CommandNotFound(String, String),
+    #[error("command `{0}` of setup `{1}` must have either a `file` or a `command`")]
+    CommandWithoutScript(String, String),
     #[error("unknown cfg error")]
     Unknown,
 }
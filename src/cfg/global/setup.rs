@@ -2,10 +2,12 @@ use anyhow::Result;
 use serde::de::{Unexpected, Visitor};
 use serde::export::Formatter;
 use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::result::Result as stdResult;
 
 use crate::cfg::{CfgError, LocalSetupCfg, SetupCfg};
+use crate::vault::VaultSource;
 
 pub type SetupName = String;
 
@@ -18,6 +20,9 @@ impl AsRef<PathBuf> for PrivateEnvDir {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+struct PrivateEnvSource(#[serde(deserialize_with = "deserialize_private_env_source")] String);
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GlobalProjectSetupCfg {
     #[serde(skip)]
@@ -25,6 +30,24 @@ pub struct GlobalProjectSetupCfg {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     private_env_dir: Option<PrivateEnvDir>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    private_env_source: Option<PrivateEnvSource>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    allowed_run_file: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    allowed_commands: Option<BTreeMap<String, String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    allowed_env_run_files: Option<BTreeMap<String, String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    allowed_env_commands: Option<BTreeMap<String, BTreeMap<String, String>>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    allowed_hooks: Option<String>,
 }
 
 impl GlobalProjectSetupCfg {
@@ -32,6 +55,12 @@ impl GlobalProjectSetupCfg {
         Self {
             name,
             private_env_dir: None,
+            private_env_source: None,
+            allowed_run_file: None,
+            allowed_commands: None,
+            allowed_env_run_files: None,
+            allowed_env_commands: None,
+            allowed_hooks: None,
         }
     }
 
@@ -59,6 +88,91 @@ impl GlobalProjectSetupCfg {
         }
     }
 
+    pub fn private_env_source(&self) -> Result<VaultSource> {
+        let source = self
+            .private_env_source
+            .as_ref()
+            .ok_or(CfgError::PrivateEnvSourceNotFound(self.name.clone()))?;
+        Ok(VaultSource::parse(&source.0)?)
+    }
+
+    pub fn set_private_env_source(&mut self, source: String) -> Result<()> {
+        VaultSource::parse(&source)?;
+        self.private_env_source = Some(PrivateEnvSource(source));
+        Ok(())
+    }
+
+    pub fn unset_private_env_source(&mut self) -> Result<()> {
+        if self.private_env_source.is_none() {
+            bail!(CfgError::PrivateEnvSourceAlreadyUnset(self.name.clone()))
+        } else {
+            self.private_env_source = None;
+            Ok(())
+        }
+    }
+
+    /// Hash of the last run file content approved with `allow`.
+    pub fn allowed_run_file(&self) -> Option<&String> {
+        self.allowed_run_file.as_ref()
+    }
+
+    pub fn set_allowed_run_file(&mut self, hash: String) {
+        self.allowed_run_file = Some(hash);
+    }
+
+    /// Hash of the last run file approved with `allow` for the env `env_name`, when it has an
+    /// `envs` override.
+    pub fn allowed_env_run_file(&self, env_name: &str) -> Option<&String> {
+        self.allowed_env_run_files
+            .as_ref()
+            .and_then(|allowed_env_run_files| allowed_env_run_files.get(env_name))
+    }
+
+    pub fn set_allowed_env_run_file(&mut self, env_name: String, hash: String) {
+        self.allowed_env_run_files
+            .get_or_insert_with(BTreeMap::new)
+            .insert(env_name, hash);
+    }
+
+    /// Hash of the last content of the command `name` approved with `allow`.
+    pub fn allowed_command(&self, name: &str) -> Option<&String> {
+        self.allowed_commands
+            .as_ref()
+            .and_then(|allowed_commands| allowed_commands.get(name))
+    }
+
+    pub fn set_allowed_command(&mut self, name: String, hash: String) {
+        self.allowed_commands
+            .get_or_insert_with(BTreeMap::new)
+            .insert(name, hash);
+    }
+
+    /// Hash of the last content of the command `name` approved with `allow` for the env
+    /// `env_name`, when it has an `envs` override.
+    pub fn allowed_env_command(&self, env_name: &str, name: &str) -> Option<&String> {
+        self.allowed_env_commands
+            .as_ref()
+            .and_then(|allowed_env_commands| allowed_env_commands.get(env_name))
+            .and_then(|allowed_commands| allowed_commands.get(name))
+    }
+
+    pub fn set_allowed_env_command(&mut self, env_name: String, name: String, hash: String) {
+        self.allowed_env_commands
+            .get_or_insert_with(BTreeMap::new)
+            .entry(env_name)
+            .or_default()
+            .insert(name, hash);
+    }
+
+    /// Hash of the last `hooks` approved with `allow`.
+    pub fn allowed_hooks(&self) -> Option<&String> {
+        self.allowed_hooks.as_ref()
+    }
+
+    pub fn set_allowed_hooks(&mut self, hash: String) {
+        self.allowed_hooks = Some(hash);
+    }
+
     pub fn name(&self) -> &SetupName {
         &self.name
     }
@@ -73,6 +187,12 @@ impl From<&LocalSetupCfg> for GlobalProjectSetupCfg {
         Self {
             name: local_setup.name().clone(),
             private_env_dir: None,
+            private_env_source: None,
+            allowed_run_file: None,
+            allowed_commands: None,
+            allowed_env_run_files: None,
+            allowed_env_commands: None,
+            allowed_hooks: None,
         }
     }
 }
@@ -119,6 +239,21 @@ where
     deserializer.deserialize_str(InnerVisitor)
 }
 
+fn deserialize_private_env_source<'de, D>(deserializer: D) -> stdResult<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let source = String::deserialize(deserializer)?;
+    if let Err(err) = VaultSource::parse(&source) {
+        Err(serde::de::Error::invalid_value(
+            Unexpected::Str(&source),
+            &err.to_string().as_str(),
+        ))
+    } else {
+        Ok(source)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
@@ -154,4 +289,22 @@ private_env_dir: /rel_path
             setup_cfg.private_env_dir().unwrap()
         );
     }
+
+    #[test]
+    fn deserialize_private_env_source() {
+        let content = r#"
+private_env_source: "vault://secret/myapp/{env}"
+        "#;
+        let error = serde_yaml::from_str::<GlobalProjectSetupCfg>(content).unwrap_err();
+        assert!(contains("incorrect vault source").eval(&error.to_string()));
+
+        let content = r#"
+private_env_source: "vault://secret/data/myapp/{env}"
+        "#;
+        let setup_cfg = serde_yaml::from_str::<GlobalProjectSetupCfg>(content).unwrap();
+        assert_eq!(
+            setup_cfg.private_env_source().unwrap().url(),
+            "vault://secret/data/myapp/{env}"
+        );
+    }
 }
@@ -1,4 +1,5 @@
 use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::rc::Rc;
 
 use serde::de::{MapAccess, Visitor};
@@ -7,29 +8,57 @@ use serde::ser::SerializeMap;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
 
+pub use process_groups::{Process, ProcessGroup, ProcessGroups};
 pub use setup::LocalSetupCfg;
+pub use setup_arguments::{ArgumentType, SetupArgument, SetupArguments};
 pub use setup_array_vars::{ArrayVar, ArrayVars, VarCase};
+pub use setup_commands::{CommandName, SetupCommand, SetupCommands};
+pub use setup_cwd::SetupCwd;
+pub use setup_envs::{SetupEnvCfg, SetupEnvs};
+pub use setup_hooks::{Hook, SetupHooks};
+pub use setup_requires::SetupRequirement;
+pub use setup_ssm::SsmCfg;
 pub use setup_vars::{VarName, Vars};
 
 use crate::cfg::local::setup::SetupName;
 use crate::cfg::setup::SetupsCfg;
 use crate::cfg::SetupCfg;
 
+mod process_groups;
 mod setup;
+mod setup_arguments;
 mod setup_array_vars;
+mod setup_commands;
+mod setup_cwd;
+mod setup_envs;
+mod setup_hooks;
+mod setup_requires;
+mod setup_ssm;
 mod setup_vars;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LocalCfg {
     setups: LocalSetupsCfg,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    groups: ProcessGroups,
 }
 
 impl LocalCfg {
     pub fn new() -> Self {
         Self {
             setups: LocalSetupsCfg::new(),
+            groups: ProcessGroups::new(),
         }
     }
+
+    pub fn groups(&self) -> &ProcessGroups {
+        &self.groups
+    }
+
+    pub fn set_group(&mut self, name: String, group: ProcessGroup) {
+        self.groups.insert(name, group);
+    }
 }
 
 impl SetupsCfg for LocalCfg {
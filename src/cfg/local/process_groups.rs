@@ -0,0 +1,84 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+pub type ProcessName = String;
+pub type ProcessGroups = BTreeMap<String, ProcessGroup>;
+
+/// Processes started together by `sht up <group>`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ProcessGroup {
+    processes: BTreeMap<ProcessName, Process>,
+
+    /// Stop all the processes when one of them exits, true by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop_on_exit: Option<bool>,
+}
+
+impl ProcessGroup {
+    pub fn processes(&self) -> &BTreeMap<ProcessName, Process> {
+        &self.processes
+    }
+
+    pub fn add_process(&mut self, name: ProcessName, process: Process) {
+        self.processes.insert(name, process);
+    }
+
+    pub fn stop_on_exit(&self) -> bool {
+        self.stop_on_exit.unwrap_or(true)
+    }
+}
+
+/// A setup, `<setup>`, or one of its commands, `<setup>:<command>`, run with the vars of `env`,
+/// the env of `sht up` by default.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Process {
+    run: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    env: Option<String>,
+}
+
+impl Process {
+    pub fn new(run: String) -> Self {
+        Self { run, env: None }
+    }
+
+    pub fn run(&self) -> &String {
+        &self.run
+    }
+
+    pub fn env(&self) -> Option<&String> {
+        self.env.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cfg::local::ProcessGroups;
+
+    #[test]
+    fn process_groups_yaml() {
+        let groups: ProcessGroups = serde_yaml::from_str(
+            r#"
+dev:
+  processes:
+    web:
+      run: app:serve
+    worker:
+      run: worker
+      env: staging
+  stop_on_exit: false
+"#,
+        )
+        .unwrap();
+
+        let dev = groups.get("dev").unwrap();
+        assert!(!dev.stop_on_exit());
+        let web = dev.processes().get("web").unwrap();
+        assert_eq!(web.run(), "app:serve");
+        assert_eq!(web.env(), None);
+        let worker = dev.processes().get("worker").unwrap();
+        assert_eq!(worker.env().unwrap(), "staging");
+    }
+}
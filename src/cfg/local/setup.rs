@@ -2,12 +2,16 @@ use std::borrow::Cow;
 use std::cell::RefCell;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::time::Duration;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 
 use crate::cfg::local::setup_vars::Vars;
-use crate::cfg::local::ArrayVars;
+use crate::cfg::local::{
+    ArrayVars, CommandName, SetupArguments, SetupCommand, SetupCommands, SetupCwd, SetupEnvCfg,
+    SetupEnvs, SetupHooks, SetupRequirement, SsmCfg,
+};
 use crate::cfg::setup::SetupCfg;
 use crate::cfg::CfgError;
 
@@ -28,6 +32,63 @@ pub struct LocalSetupCfg {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     vars: Option<Rc<RefCell<Vars>>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    secrets: Option<Vars>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ssm: Option<SsmCfg>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    protected: Option<Vec<String>>,
+
+    /// Working directory of the run file and the commands.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cwd: Option<SetupCwd>,
+
+    /// Program that runs the run file, e.g. `python3`, the run file doesn't need to be executable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    interpreter: Option<String>,
+
+    /// Passed to the run file before the args of `sht run`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    args: Option<Vec<String>>,
+
+    /// Positional and flag arguments of the run file, validated before the run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    arguments: Option<SetupArguments>,
+
+    /// Tools, vars and files checked before the run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    requires: Option<Vec<SetupRequirement>>,
+
+    /// Commands run before or after `run`, `sync`, `edit` and `use`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hooks: Option<SetupHooks>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    commands: Option<SetupCommands>,
+
+    /// Steps run before the run file : a command of the setup, another setup or
+    /// `<setup>:<command>`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    depends_on: Option<Vec<String>>,
+
+    /// Maximum duration of a run, e.g. `10m`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timeout: Option<String>,
+
+    /// Number of runs again after a non-zero exit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retries: Option<u32>,
+
+    /// Delay before the first retry, doubled at each retry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retry_backoff: Option<String>,
+
+    /// Replace `file`, `array_vars`, `vars`, `cwd` or `args` for some envs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    envs: Option<SetupEnvs>,
 }
 
 impl Clone for LocalSetupCfg {
@@ -50,6 +111,21 @@ impl Clone for LocalSetupCfg {
             file: self.file.clone(),
             array_vars,
             vars,
+            secrets: self.secrets.clone(),
+            ssm: self.ssm.clone(),
+            protected: self.protected.clone(),
+            cwd: self.cwd.clone(),
+            interpreter: self.interpreter.clone(),
+            args: self.args.clone(),
+            arguments: self.arguments.clone(),
+            requires: self.requires.clone(),
+            hooks: self.hooks.clone(),
+            commands: self.commands.clone(),
+            depends_on: self.depends_on.clone(),
+            timeout: self.timeout.clone(),
+            retries: self.retries,
+            retry_backoff: self.retry_backoff.clone(),
+            envs: self.envs.clone(),
         }
     }
 }
@@ -62,6 +138,21 @@ impl LocalSetupCfg {
             file,
             array_vars: None,
             vars: None,
+            secrets: None,
+            ssm: None,
+            protected: None,
+            cwd: None,
+            interpreter: None,
+            args: None,
+            arguments: None,
+            requires: None,
+            hooks: None,
+            commands: None,
+            depends_on: None,
+            timeout: None,
+            retries: None,
+            retry_backoff: None,
+            envs: None,
         };
 
         local_setup.new_array_vars();
@@ -92,6 +183,116 @@ impl LocalSetupCfg {
         self.vars.as_ref().map(|r| Rc::clone(r))
     }
 
+    /// Secret vars are declared in the `secrets` list, case insensitive.
+    pub fn is_secret(&self, var_name: &str) -> bool {
+        match &self.secrets {
+            Some(secrets) => secrets
+                .as_ref()
+                .iter()
+                .any(|secret| secret.to_env_var() == var_name.to_uppercase()),
+            None => false,
+        }
+    }
+
+    pub fn is_protected_env(&self, env_name: &str) -> bool {
+        match &self.protected {
+            Some(protected) => protected.iter().any(|name| name == env_name),
+            None => false,
+        }
+    }
+
+    pub fn cwd(&self) -> Option<&SetupCwd> {
+        self.cwd.as_ref()
+    }
+
+    pub fn interpreter(&self) -> Option<&String> {
+        self.interpreter.as_ref()
+    }
+
+    pub fn args(&self) -> Vec<String> {
+        self.args.clone().unwrap_or_default()
+    }
+
+    pub fn arguments(&self) -> SetupArguments {
+        self.arguments.clone().unwrap_or_default()
+    }
+
+    pub fn requires(&self) -> Vec<SetupRequirement> {
+        self.requires.clone().unwrap_or_default()
+    }
+
+    pub fn hooks(&self) -> SetupHooks {
+        self.hooks.clone().unwrap_or_default()
+    }
+
+    pub fn commands(&self) -> SetupCommands {
+        self.commands.clone().unwrap_or_default()
+    }
+
+    pub fn command(&self, name: &str) -> Option<&SetupCommand> {
+        self.commands
+            .as_ref()
+            .and_then(|commands| commands.get(name))
+    }
+
+    pub fn set_command(&mut self, name: CommandName, command: SetupCommand) {
+        self.commands
+            .get_or_insert_with(SetupCommands::new)
+            .insert(name, command);
+    }
+
+    pub fn depends_on(&self) -> Vec<String> {
+        self.depends_on.clone().unwrap_or_default()
+    }
+
+    pub fn timeout(&self) -> Result<Option<Duration>> {
+        parse_duration(&self.timeout).context(format!("invalid timeout for setup `{}`", self.name))
+    }
+
+    pub fn retries(&self) -> u32 {
+        self.retries.unwrap_or(0)
+    }
+
+    pub fn retry_backoff(&self) -> Result<Option<Duration>> {
+        parse_duration(&self.retry_backoff)
+            .context(format!("invalid retry_backoff for setup `{}`", self.name))
+    }
+
+    pub fn env_cfg(&self, env_name: &str) -> Option<&SetupEnvCfg> {
+        self.envs.as_ref().and_then(|envs| envs.get(env_name))
+    }
+
+    pub fn env_cfgs_mut(&mut self) -> impl Iterator<Item = &mut SetupEnvCfg> {
+        self.envs.iter_mut().flat_map(|envs| envs.values_mut())
+    }
+
+    /// Copy of the setup with the overrides of `env_name` applied.
+    pub fn for_env(&self, env_name: &str) -> Self {
+        let mut local_setup = self.clone();
+        if let Some(env_cfg) = self.env_cfg(env_name).cloned() {
+            if let Some(file) = env_cfg.file {
+                local_setup.file = file;
+            }
+            if let Some(array_vars) = env_cfg.array_vars {
+                local_setup.array_vars = Some(Rc::new(RefCell::new(array_vars)));
+            }
+            if let Some(vars) = env_cfg.vars {
+                local_setup.vars = Some(Rc::new(RefCell::new(vars)));
+            }
+            if let Some(cwd) = env_cfg.cwd {
+                local_setup.cwd = Some(cwd);
+            }
+            if let Some(args) = env_cfg.args {
+                local_setup.args = Some(args);
+            }
+        }
+        local_setup
+    }
+
+    pub fn ssm(&self) -> SsmCfg {
+        self.ssm.clone().unwrap_or_default()
+    }
+
     pub fn public_env_dir(&self) -> Cow<Path> {
         match &self.public_env_dir {
             Some(dir) => Cow::Borrowed(dir),
@@ -113,6 +314,13 @@ impl LocalSetupCfg {
     }
 }
 
+fn parse_duration(duration: &Option<String>) -> Result<Option<Duration>> {
+    match duration {
+        Some(duration) => Ok(Some(humantime::parse_duration(duration)?)),
+        None => Ok(None),
+    }
+}
+
 impl SetupCfg for LocalSetupCfg {
     fn name(&self) -> &String {
         &self.name
@@ -125,6 +333,9 @@ impl SetupCfg for LocalSetupCfg {
 
 #[cfg(test)]
 mod tests {
+    use std::path::PathBuf;
+    use std::time::Duration;
+
     use crate::cfg::{ArrayVar, LocalSetupCfg};
 
     #[test]
@@ -152,4 +363,55 @@ array_vars:
         let content = serde_yaml::to_string(&setup_cfg).unwrap();
         assert_eq!(expect, content.as_str());
     }
+
+    #[test]
+    fn local_cfg_timeout_retries() {
+        let setup_cfg: LocalSetupCfg = serde_yaml::from_str(
+            r#"
+file: run.sh
+timeout: 10m
+retries: 3
+retry_backoff: 500ms
+"#,
+        )
+        .unwrap();
+        assert_eq!(setup_cfg.timeout().unwrap(), Some(Duration::from_secs(600)));
+        assert_eq!(setup_cfg.retries(), 3);
+        assert_eq!(
+            setup_cfg.retry_backoff().unwrap(),
+            Some(Duration::from_millis(500))
+        );
+
+        let setup_cfg: LocalSetupCfg =
+            serde_yaml::from_str("file: run.sh\ntimeout: soon\n").unwrap();
+        assert!(setup_cfg.timeout().is_err());
+        assert_eq!(setup_cfg.retries(), 0);
+    }
+
+    #[test]
+    fn local_cfg_for_env() {
+        let setup_cfg: LocalSetupCfg = serde_yaml::from_str(
+            r#"
+file: run.sh
+vars: [ VAR1 ]
+args: [ --verbose ]
+envs:
+  prod:
+    file: deploy_prod.sh
+    array_vars:
+      all: ".*"
+"#,
+        )
+        .unwrap();
+
+        let prod = setup_cfg.for_env("prod");
+        assert_eq!(prod.file(), &PathBuf::from("deploy_prod.sh"));
+        assert_eq!(prod.array_vars().unwrap().borrow().as_ref().len(), 1);
+        assert_eq!(prod.vars().unwrap().borrow().as_ref().len(), 1);
+        assert_eq!(prod.args(), vec!["--verbose".to_string()]);
+
+        let dev = setup_cfg.for_env("dev");
+        assert_eq!(dev.file(), &PathBuf::from("run.sh"));
+        assert!(dev.array_vars().is_none());
+    }
 }
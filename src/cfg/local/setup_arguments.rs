@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+use strum_macros::AsRefStr;
+
+pub type SetupArguments = Vec<SetupArgument>;
+
+/// Argument of the run file declared in `arguments`, checked before the run.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SetupArgument {
+    name: String,
+
+    /// `--<name>`, positional otherwise.
+    #[serde(default, skip_serializing_if = "is_false")]
+    flag: bool,
+
+    #[serde(
+        rename = "type",
+        default,
+        skip_serializing_if = "ArgumentType::is_string"
+    )]
+    kind: ArgumentType,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    choices: Option<Vec<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    help: Option<String>,
+}
+
+impl SetupArgument {
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn is_flag(&self) -> bool {
+        self.flag
+    }
+
+    pub fn kind(&self) -> ArgumentType {
+        self.kind
+    }
+
+    pub fn choices(&self) -> Option<&Vec<String>> {
+        self.choices.as_ref()
+    }
+
+    pub fn default(&self) -> Option<&String> {
+        self.default.as_ref()
+    }
+
+    pub fn help(&self) -> Option<&String> {
+        self.help.as_ref()
+    }
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, AsRefStr, Default)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum ArgumentType {
+    #[default]
+    String,
+    Int,
+    Float,
+    /// A flag without value, `true` when it's given.
+    Bool,
+}
+
+impl ArgumentType {
+    pub fn is_string(&self) -> bool {
+        matches!(self, ArgumentType::String)
+    }
+
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        let valid = match self {
+            ArgumentType::String => true,
+            ArgumentType::Int => value.parse::<i64>().is_ok(),
+            ArgumentType::Float => value.parse::<f64>().is_ok(),
+            ArgumentType::Bool => value == "true" || value == "false",
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(format!("`{}` is not a valid {}", value, self.as_ref()))
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+pub type CommandName = String;
+pub type SetupCommands = BTreeMap<CommandName, SetupCommand>;
+
+/// A named command of a setup, run with `sht run <name>` with the vars of the setup.
+/// It is either a script `file` or an inline `command` run by `sh`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SetupCommand {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<PathBuf>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    command: Option<String>,
+
+    /// Passed before the args of `sht run <name>`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    args: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+
+    /// Steps run before, see `LocalSetupCfg::depends_on`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    depends_on: Vec<String>,
+}
+
+impl SetupCommand {
+    /// Inline command line, without args nor dependencies.
+    pub fn inline(command: String) -> Self {
+        Self {
+            file: None,
+            command: Some(command),
+            args: vec![],
+            description: None,
+            depends_on: vec![],
+        }
+    }
+
+    pub fn file(&self) -> Option<&PathBuf> {
+        self.file.as_ref()
+    }
+
+    pub fn command(&self) -> Option<&String> {
+        self.command.as_ref()
+    }
+
+    pub fn args(&self) -> &Vec<String> {
+        &self.args
+    }
+
+    pub fn description(&self) -> Option<&String> {
+        self.description.as_ref()
+    }
+
+    pub fn depends_on(&self) -> &Vec<String> {
+        &self.depends_on
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::cfg::local::SetupCommands;
+
+    #[test]
+    fn setup_commands_yaml() {
+        let commands: SetupCommands = serde_yaml::from_str(
+            r#"
+build:
+  file: build.sh
+  description: Build the app
+migrate:
+  command: diesel migration run
+  args:
+    - --locked
+  depends_on:
+    - build
+"#,
+        )
+        .unwrap();
+
+        let build = commands.get("build").unwrap();
+        assert_eq!(build.file(), Some(&PathBuf::from("build.sh")));
+        assert_eq!(build.command(), None);
+        assert!(build.args().is_empty());
+        assert_eq!(build.description().unwrap(), "Build the app");
+
+        let migrate = commands.get("migrate").unwrap();
+        assert_eq!(migrate.command().unwrap(), "diesel migration run");
+        assert_eq!(migrate.args(), &vec!["--locked".to_string()]);
+        assert_eq!(migrate.depends_on(), &vec!["build".to_string()]);
+        assert!(build.depends_on().is_empty());
+    }
+}
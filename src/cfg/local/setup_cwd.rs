@@ -0,0 +1,59 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Working directory of the run file and the commands of a setup.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum SetupCwd {
+    /// Relative to the project directory, where `short.yaml` is.
+    Project(PathBuf),
+    /// `script: <path>`, relative to the directory of the run file.
+    Script { script: PathBuf },
+}
+
+impl SetupCwd {
+    pub fn resolve(&self, project_dir: &Path, script_dir: &Path) -> PathBuf {
+        match self {
+            SetupCwd::Project(dir) => project_dir.join(dir),
+            SetupCwd::Script { script } => script_dir.join(script),
+        }
+    }
+}
+
+impl fmt::Display for SetupCwd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SetupCwd::Project(dir) => write!(f, "{}", dir.to_string_lossy()),
+            SetupCwd::Script { script } => write!(f, "script: {}", script.to_string_lossy()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use crate::cfg::local::SetupCwd;
+
+    #[test]
+    fn setup_cwd_resolve() {
+        let project = Path::new("/project");
+        let script_dir = Path::new("/project/scripts");
+
+        let cwd: SetupCwd = serde_yaml::from_str("build").unwrap();
+        assert_eq!(cwd, SetupCwd::Project(PathBuf::from("build")));
+        assert_eq!(
+            cwd.resolve(project, script_dir),
+            PathBuf::from("/project/build")
+        );
+
+        let cwd: SetupCwd = serde_yaml::from_str("script: ../app").unwrap();
+        assert_eq!(
+            cwd.resolve(project, script_dir),
+            PathBuf::from("/project/scripts/../app")
+        );
+        assert_eq!(cwd.to_string(), "script: ../app");
+    }
+}
@@ -0,0 +1,27 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cfg::local::{ArrayVars, SetupCwd, Vars};
+
+pub type SetupEnvs = BTreeMap<String, SetupEnvCfg>;
+
+/// Replace the setup configuration for one env, the missing keys keep the setup values.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SetupEnvCfg {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub array_vars: Option<ArrayVars>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vars: Option<Vars>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<SetupCwd>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub args: Option<Vec<String>>,
+}
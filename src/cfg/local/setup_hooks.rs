@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use strum_macros::AsRefStr;
+
+/// Commands run by `sh` around the operations of short, with the vars of the env.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SetupHooks {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pre_run: Option<String>,
+
+    /// Gets the exit status of the run in `SHORT_EXIT_STATUS`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_run: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pre_sync: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_sync: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_edit: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_use: Option<String>,
+}
+
+impl SetupHooks {
+    pub fn is_empty(&self) -> bool {
+        self.pre_run.is_none()
+            && self.post_run.is_none()
+            && self.pre_sync.is_none()
+            && self.post_sync.is_none()
+            && self.post_edit.is_none()
+            && self.post_use.is_none()
+    }
+
+    pub fn get(&self, hook: Hook) -> Option<&String> {
+        match hook {
+            Hook::PreRun => self.pre_run.as_ref(),
+            Hook::PostRun => self.post_run.as_ref(),
+            Hook::PreSync => self.pre_sync.as_ref(),
+            Hook::PostSync => self.post_sync.as_ref(),
+            Hook::PostEdit => self.post_edit.as_ref(),
+            Hook::PostUse => self.post_use.as_ref(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, AsRefStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum Hook {
+    PreRun,
+    PostRun,
+    PreSync,
+    PostSync,
+    PostEdit,
+    PostUse,
+}
+
+impl Hook {
+    pub const ALL: [Hook; 6] = [
+        Hook::PreRun,
+        Hook::PostRun,
+        Hook::PreSync,
+        Hook::PostSync,
+        Hook::PostEdit,
+        Hook::PostUse,
+    ];
+
+    /// Run before the operation, which is aborted when it fails.
+    pub fn is_pre(&self) -> bool {
+        matches!(self, Hook::PreRun | Hook::PreSync)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cfg::local::{Hook, SetupHooks};
+
+    #[test]
+    fn setup_hooks_yaml() {
+        let hooks: SetupHooks = serde_yaml::from_str(
+            r#"
+pre_run: aws sso login
+post_edit: docker restart api
+"#,
+        )
+        .unwrap();
+        assert_eq!(hooks.get(Hook::PreRun).unwrap(), "aws sso login");
+        assert_eq!(hooks.get(Hook::PostEdit).unwrap(), "docker restart api");
+        assert!(hooks.get(Hook::PostRun).is_none());
+        assert!(!hooks.is_empty());
+        assert!(SetupHooks::default().is_empty());
+        assert_eq!(Hook::PostEdit.as_ref(), "post_edit");
+        assert!(Hook::PreSync.is_pre());
+        assert!(!Hook::PostUse.is_pre());
+
+        let content = serde_yaml::to_string(&hooks).unwrap();
+        assert_eq!(
+            content,
+            "---\npre_run: aws sso login\npost_edit: docker restart api"
+        );
+    }
+}
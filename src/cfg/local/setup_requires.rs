@@ -0,0 +1,60 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// Checked before the run of a setup, listed in `requires`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum SetupRequirement {
+    /// `<tool>` found in the `PATH`, or `<tool> <op><version>`, e.g. `node >=14`.
+    Tool(String),
+    /// `var: <name>`, a var of the env with a value.
+    Var { var: String },
+    /// `file: <path>`, relative to the project directory.
+    File { file: PathBuf },
+}
+
+impl fmt::Display for SetupRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SetupRequirement::Tool(tool) => write!(f, "{}", tool),
+            SetupRequirement::Var { var } => write!(f, "var {}", var),
+            SetupRequirement::File { file } => write!(f, "file {}", file.to_string_lossy()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::cfg::local::SetupRequirement;
+
+    #[test]
+    fn setup_requires_yaml() {
+        let requires: Vec<SetupRequirement> = serde_yaml::from_str(
+            r#"
+- node >=14
+- aws
+- var: DATABASE_URL
+- file: .env.local
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            requires,
+            vec![
+                SetupRequirement::Tool("node >=14".into()),
+                SetupRequirement::Tool("aws".into()),
+                SetupRequirement::Var {
+                    var: "DATABASE_URL".into()
+                },
+                SetupRequirement::File {
+                    file: PathBuf::from(".env.local")
+                },
+            ]
+        );
+        assert_eq!(requires[2].to_string(), "var DATABASE_URL");
+    }
+}
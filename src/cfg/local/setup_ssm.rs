@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+const SETUP_PLACEHOLDER: &str = "{setup}";
+const ENV_PLACEHOLDER: &str = "{env}";
+const DEFAULT_SSM_PATH: &str = "/{setup}/{env}";
+
+/// AWS SSM Parameter Store settings, each env var is stored under `path`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SsmCfg {
+    path: String,
+}
+
+impl SsmCfg {
+    pub fn new(path: String) -> Self {
+        Self { path }
+    }
+
+    /// Path prefix, `{setup}` and `{env}` are replaced by the setup and env names.
+    pub fn path(&self, setup_name: &str, env_name: &str) -> String {
+        let path = self
+            .path
+            .replace(SETUP_PLACEHOLDER, setup_name)
+            .replace(ENV_PLACEHOLDER, env_name);
+        let path = path.trim_end_matches('/');
+        if path.starts_with('/') {
+            path.to_string()
+        } else {
+            format!("/{}", path)
+        }
+    }
+}
+
+impl Default for SsmCfg {
+    fn default() -> Self {
+        Self::new(DEFAULT_SSM_PATH.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cfg::local::SsmCfg;
+
+    #[test]
+    fn ssm_path() {
+        let ssm = SsmCfg::default();
+        assert_eq!(ssm.path("myapp", "prod"), "/myapp/prod");
+
+        let ssm = SsmCfg::new("deploy/{env}/{setup}/".into());
+        assert_eq!(ssm.path("myapp", "prod"), "/deploy/prod/myapp");
+    }
+}
@@ -11,7 +11,11 @@ pub use file::FileCfg;
 pub use global::GlobalCfg;
 pub use local::LocalCfg;
 pub use local::LocalSetupCfg;
-pub use local::{ArrayVar, ArrayVars, VarCase, VarName, Vars};
+pub use local::{
+    ArgumentType, ArrayVar, ArrayVars, CommandName, Hook, Process, ProcessGroup, ProcessGroups,
+    SetupArgument, SetupCommand, SetupCommands, SetupCwd, SetupHooks, SetupRequirement, SsmCfg,
+    VarCase, VarName, Vars,
+};
 pub use setup::Setup;
 pub use setup::SetupCfg;
 pub use setup::SetupsCfg;
@@ -58,11 +62,25 @@ impl Cfg {
         Ok(())
     }
 
+    /// Save only the global cfg, keep the local cfg file untouched.
+    pub fn save_global(&self) -> Result<()> {
+        self.global_cfg.save()?;
+        Ok(())
+    }
+
     pub fn add_local_setup_cfg(&mut self, setup: LocalSetupCfg) {
         let local_cfg = self.local_cfg.borrow_mut();
         local_cfg.add_setup(setup);
     }
 
+    pub fn process_groups(&self) -> &ProcessGroups {
+        self.local_cfg.borrow().groups()
+    }
+
+    pub fn set_process_group(&mut self, name: String, group: ProcessGroup) {
+        self.local_cfg.borrow_mut().set_group(name, group);
+    }
+
     pub fn sync_local_to_global(&mut self) -> Result<()> {
         let global_cfg = self.global_cfg.borrow_mut();
         global_cfg
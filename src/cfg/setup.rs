@@ -9,9 +9,34 @@ use serde::export::Formatter;
 
 use crate::cfg::error::CfgError;
 use crate::cfg::global::GlobalProjectSetupCfg;
-use crate::cfg::LocalSetupCfg;
+use crate::cfg::{LocalSetupCfg, SetupCommand};
 use crate::env_file;
-use crate::env_file::{path_from_env_name, Env};
+use crate::env_file::{path_from_env_name, Env, EnvLocation};
+use crate::vault;
+use crate::vault::VaultSource;
+
+/// Private envs are stored in a directory or in a vault.
+#[derive(Debug, Clone)]
+pub enum PrivateEnvsDir {
+    Dir(PathBuf),
+    Vault(VaultSource),
+}
+
+impl PrivateEnvsDir {
+    pub fn env_location(&self, env_name: &String) -> EnvLocation {
+        match self {
+            PrivateEnvsDir::Dir(dir) => EnvLocation::File(path_from_env_name(dir, env_name)),
+            PrivateEnvsDir::Vault(source) => EnvLocation::Vault(source.clone(), env_name.clone()),
+        }
+    }
+
+    pub fn read_envs(&self) -> Vec<Result<Env>> {
+        match self {
+            PrivateEnvsDir::Dir(dir) => env_file::read_dir(dir),
+            PrivateEnvsDir::Vault(source) => vault::read_envs(source),
+        }
+    }
+}
 
 pub trait SetupsCfg {
     type Setup: SetupCfg;
@@ -53,6 +78,8 @@ pub struct Setup {
     local_cfg_file: Option<PathBuf>,
     local_setup: Weak<RefCell<LocalSetupCfg>>,
     global_setup: Weak<RefCell<GlobalProjectSetupCfg>>,
+    /// Env and local setup with the overrides of the env, see `for_env`.
+    resolved: Option<(String, Rc<RefCell<LocalSetupCfg>>)>,
 }
 
 impl Setup {
@@ -61,6 +88,7 @@ impl Setup {
             local_cfg_file: None,
             local_setup: Weak::default(),
             global_setup: Weak::default(),
+            resolved: None,
         }
     }
 
@@ -84,44 +112,64 @@ impl Setup {
         Ok(run_file)
     }
 
+    pub fn local_cfg_command(&self, name: &str) -> Result<SetupCommand> {
+        let local_setup = self.local_setup().context("local_setup not found")?;
+        let local_setup = local_setup.borrow();
+        let command = local_setup.command(name).cloned().ok_or_else(|| {
+            CfgError::CommandNotFound(name.to_string(), local_setup.name().clone())
+        })?;
+        Ok(command)
+    }
+
     pub fn env(&self, env_name: &String) -> Result<Env> {
-        let env_file = self.env_file(env_name)?;
-        let env = Env::from_file_reader(env_file)?;
+        let env_location = self.env_location(env_name)?;
+        let env = Env::read(&env_location)?;
         Ok(env)
     }
 
-    pub fn env_file(&self, env_name: &String) -> Result<PathBuf> {
-        match (self.envs_private_dir(), self.envs_public_dir()) {
-            (Ok(private_dir), Ok(public_dir)) => {
-                let public_env = path_from_env_name(&public_dir, env_name);
-                let private_env = path_from_env_name(&private_dir, env_name);
-                if private_env.exists() && public_env.exists() {
-                    Err(CfgError::EnvExistTwice(env_name.clone(), public_env, private_env).into())
-                } else if private_env.exists() {
-                    Ok(private_env)
-                } else if public_env.exists() {
-                    Ok(public_env)
-                } else {
-                    Err(CfgError::EnvNotFound(env_name.clone()).into())
-                }
-            }
-            (Ok(private_dir), Err(_)) => {
-                let private_env = path_from_env_name(&private_dir, env_name);
-                if private_env.exists() {
-                    Ok(private_env)
-                } else {
-                    Err(CfgError::EnvNotFound(env_name.clone()).into())
+    /// An env is protected if it's listed in the setup `protected` envs or
+    /// if its file header contains the `# sht:protected` annotation.
+    pub fn is_protected(&self, env: &Env) -> bool {
+        let listed = match (self.local_setup(), env.name()) {
+            (Some(local_setup), Ok(env_name)) => local_setup.borrow().is_protected_env(&env_name),
+            _ => false,
+        };
+        listed || env.is_protected()
+    }
+
+    /// An env is private when it's not read from the public env directory of the setup.
+    pub fn is_private(&self, env: &Env) -> bool {
+        match (self.envs_public_dir(), env.file()) {
+            (Ok(public_dir), Some(file)) => !file.starts_with(public_dir),
+            _ => true,
+        }
+    }
+
+    /// A vault is listed once per command, when it can't be reached a public env is still found.
+    pub fn env_location(&self, env_name: &String) -> Result<EnvLocation> {
+        let public_env = self
+            .envs_public_dir()
+            .map(|public_dir| path_from_env_name(public_dir, env_name))
+            .ok()
+            .filter(|public_env| public_env.exists());
+        let private_env = match self.envs_private_dir() {
+            Ok(private_dir) => {
+                let private_env = private_dir.env_location(env_name);
+                match private_env.exists() {
+                    Ok(exists) => Some(private_env).filter(|_| exists),
+                    Err(_) if public_env.is_some() => None,
+                    Err(err) => return Err(err.into()),
                 }
             }
-            (Err(_), Ok(public_env)) => {
-                let public_env = path_from_env_name(&public_env, env_name);
-                if public_env.exists() {
-                    Ok(public_env)
-                } else {
-                    Err(CfgError::EnvNotFound(env_name.clone()).into())
-                }
+            Err(_) => None,
+        };
+        match (public_env, private_env) {
+            (Some(public_env), Some(private_env)) => {
+                Err(CfgError::EnvExistTwice(env_name.clone(), public_env, private_env).into())
             }
-            (_, Err(err)) => Err(err),
+            (Some(public_env), None) => Ok(EnvLocation::File(public_env)),
+            (None, Some(private_env)) => Ok(private_env),
+            (None, None) => Err(CfgError::EnvNotFound(env_name.clone()).into()),
         }
     }
 
@@ -153,18 +201,23 @@ impl Setup {
         vec![]
     }
 
-    pub fn envs_private_dir(&self) -> Result<PathBuf> {
+    /// Private envs can be sourced from a vault (e.g. `vault://secret/data/myapp/{env}`).
+    pub fn envs_private_dir(&self) -> Result<PrivateEnvsDir> {
         if let Some(global_setup) = self.global_setup() {
-            if let Ok(dir) = global_setup.borrow().private_env_dir() {
-                return Ok(dir.clone());
+            let global_setup = global_setup.borrow();
+            if let Ok(source) = global_setup.private_env_source() {
+                return Ok(PrivateEnvsDir::Vault(source));
+            }
+            if let Ok(dir) = global_setup.private_env_dir() {
+                return Ok(PrivateEnvsDir::Dir(dir.clone()));
             }
         }
         bail!(CfgError::PrivateEnvDirNotFound(self.name()?))
     }
 
     pub fn envs_private(&self) -> Vec<Result<Env>> {
-        if let Ok(global_setup) = self.envs_private_dir() {
-            let env = env_file::read_dir(&global_setup);
+        if let Ok(private_dir) = self.envs_private_dir() {
+            let env = private_dir.read_envs();
             return env
                 .into_iter()
                 .map(|env| env.context("fail to parse private env"))
@@ -213,6 +266,7 @@ impl Setup {
                 local_cfg_file: Some(local_file.to_owned()),
                 local_setup: Rc::downgrade(local_setup),
                 global_setup: Rc::downgrade(global_setup),
+                resolved: None,
             })
         } else {
             Err(anyhow!(
@@ -221,8 +275,29 @@ impl Setup {
         }
     }
 
+    /// The setup with the `envs` overrides of `env_name` applied to its local setup.
+    pub fn for_env(&self, env_name: &str) -> Result<Self> {
+        let local_setup = self
+            .local_setup
+            .upgrade()
+            .context("local_setup not found")?;
+        let local_setup = local_setup.borrow().for_env(env_name);
+        Ok(Self {
+            resolved: Some((env_name.to_string(), Rc::new(RefCell::new(local_setup)))),
+            ..self.clone()
+        })
+    }
+
+    /// Env of the overrides applied by `for_env`.
+    pub fn resolved_env(&self) -> Option<&String> {
+        self.resolved.as_ref().map(|(env_name, _)| env_name)
+    }
+
     pub fn local_setup(&self) -> Option<Rc<RefCell<LocalSetupCfg>>> {
-        self.local_setup.upgrade()
+        match &self.resolved {
+            Some((_, local_setup)) => Some(Rc::clone(local_setup)),
+            None => self.local_setup.upgrade(),
+        }
     }
 
     pub fn global_setup(&self) -> Option<Rc<RefCell<GlobalProjectSetupCfg>>> {
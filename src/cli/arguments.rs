@@ -0,0 +1,166 @@
+use anyhow::Result;
+use clap::{App, AppSettings, Arg};
+
+use crate::cfg::{ArgumentType, SetupArgument};
+use crate::cli::error::CliError;
+use crate::run_file::{argument_var_name, EnvVar};
+use crate::BIN_NAME;
+
+/// Help of the argument, with its type and the env var of its value.
+fn argument_help(argument: &SetupArgument) -> String {
+    let mut help = argument.help().cloned().unwrap_or_default();
+    if !argument.kind().is_string() {
+        help = format!("{} [type: {}]", help, argument.kind().as_ref());
+    }
+    format!(
+        "{} [env: {}]",
+        help,
+        argument_var_name(argument.name()).to_uppercase()
+    )
+    .trim()
+    .to_string()
+}
+
+/// A bool flag, without value.
+fn is_switch(argument: &SetupArgument) -> bool {
+    argument.is_flag() && argument.kind() == ArgumentType::Bool
+}
+
+/// Parser of the `arguments` declared by the setup.
+fn arguments_app<'a>(
+    setup_name: &str,
+    arguments: &'a [SetupArgument],
+    helps: &'a [String],
+) -> App<'a, 'a> {
+    let mut app = App::new(setup_name)
+        .bin_name(format!("{} run -s {} --", BIN_NAME, setup_name))
+        .about("Arguments of the setup, their values are injected as env vars.")
+        .template("{about}\n\nUSAGE:\n    {usage}\n\n{all-args}")
+        .setting(AppSettings::NoBinaryName)
+        .setting(AppSettings::DisableHelpFlags)
+        .setting(AppSettings::DisableVersion)
+        .setting(AppSettings::DeriveDisplayOrder)
+        .setting(AppSettings::ColorNever);
+
+    let mut index = 0;
+    for (argument, help) in arguments.iter().zip(helps.iter()) {
+        let kind = argument.kind();
+        let mut arg = Arg::with_name(argument.name()).help(help);
+        if argument.is_flag() {
+            arg = arg.long(argument.name());
+        } else {
+            index += 1;
+            arg = arg.index(index).required(argument.default().is_none());
+        }
+        if !is_switch(argument) {
+            arg = arg
+                .takes_value(true)
+                .validator(move |value: String| kind.validate(&value));
+            if let Some(default) = argument.default() {
+                arg = arg.default_value(default);
+            }
+            if let Some(choices) = argument.choices() {
+                let choices: Vec<&str> = choices.iter().map(|choice| choice.as_str()).collect();
+                arg = arg.possible_values(&choices);
+            }
+        }
+        app = app.arg(arg);
+    }
+    app
+}
+
+/// Check `args` against the `arguments` of the setup, return the env vars of their values.
+/// A bool flag is `true` when it's given, `false` otherwise.
+pub fn parse_arguments(
+    setup_name: &str,
+    arguments: &[SetupArgument],
+    args: &[String],
+) -> Result<Vec<EnvVar>> {
+    let helps: Vec<String> = arguments.iter().map(argument_help).collect();
+    let matches = arguments_app(setup_name, arguments, &helps)
+        .get_matches_from_safe(args)
+        .map_err(|err| {
+            let message = err.message.split("\n\nUSAGE").next().unwrap_or_default();
+            let message = message.trim_start_matches("error: ").trim();
+            CliError::InvalidArguments(setup_name.to_string(), message.to_string())
+        })?;
+
+    let env_vars = arguments
+        .iter()
+        .filter_map(|argument| {
+            let name = argument.name();
+            if is_switch(argument) {
+                Some(EnvVar::from_argument(
+                    name,
+                    matches.is_present(name).to_string(),
+                ))
+            } else {
+                matches
+                    .value_of(name)
+                    .map(|value| EnvVar::from_argument(name, value.to_string()))
+            }
+        })
+        .collect();
+    Ok(env_vars)
+}
+
+/// Print the usage of the `arguments` of the setup.
+pub fn print_arguments_help(setup_name: &str, arguments: &[SetupArgument]) -> Result<()> {
+    let helps: Vec<String> = arguments.iter().map(argument_help).collect();
+    arguments_app(setup_name, arguments, &helps).print_help()?;
+    println!();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cfg::SetupArgument;
+    use crate::cli::arguments::parse_arguments;
+
+    fn vars(arguments: &[SetupArgument], args: &[&str]) -> anyhow::Result<Vec<(String, String)>> {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        Ok(parse_arguments("deploy", arguments, &args)?
+            .iter()
+            .map(|env_var| (env_var.var().to_env_var(), env_var.env_value().to_string()))
+            .collect())
+    }
+
+    #[test]
+    fn arguments() {
+        let arguments: Vec<SetupArgument> = serde_yaml::from_str(
+            r#"
+- name: target
+  choices: [web, worker]
+- name: replicas
+  flag: true
+  type: int
+  default: "2"
+- name: dry-run
+  flag: true
+  type: bool
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            vars(&arguments, &["web", "--dry-run"]).unwrap(),
+            vec![
+                ("SHORT_ARG_TARGET".to_string(), "web".to_string()),
+                ("SHORT_ARG_REPLICAS".to_string(), "2".to_string()),
+                ("SHORT_ARG_DRY_RUN".to_string(), "true".to_string()),
+            ]
+        );
+        assert_eq!(
+            vars(&arguments, &["worker", "--replicas", "4"]).unwrap()[1..],
+            [
+                ("SHORT_ARG_REPLICAS".to_string(), "4".to_string()),
+                ("SHORT_ARG_DRY_RUN".to_string(), "false".to_string()),
+            ]
+        );
+
+        assert!(vars(&arguments, &[]).is_err());
+        assert!(vars(&arguments, &["api"]).is_err());
+        assert!(vars(&arguments, &["web", "--replicas", "many"]).is_err());
+        assert!(vars(&arguments, &["web", "--unknown"]).is_err());
+    }
+}
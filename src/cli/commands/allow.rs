@@ -0,0 +1,333 @@
+use colored::*;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::ArgMatches;
+
+use crate::cfg::{global_cfg_directory, Cfg, Hook, Setup};
+use crate::cli::cfg::{get_cfg, reach_directories};
+use crate::cli::error::CliError;
+use crate::cli::settings::get_settings;
+use crate::cli::terminal::message::{bad_info, message, success};
+use crate::utils::hash::{hex, sha256};
+use crate::utils::line_diff::{line_diff, LineDiff};
+use crate::utils::write_all::write_all_dir;
+
+use super::run::{setup_cwd, setup_script, Script};
+
+const ALLOWED_DIR: &str = "allowed";
+
+/// Approved run files are kept by hash, to show what changed since.
+fn allowed_file(hash: &str) -> Result<PathBuf> {
+    let (_, global_dir) = reach_directories()?;
+    Ok(global_cfg_directory(&global_dir)
+        .join(ALLOWED_DIR)
+        .join(hash))
+}
+
+fn read_run_file(run_file: &Path) -> Result<String> {
+    read_to_string(run_file).context(format!("fail to read run file {:?}", run_file))
+}
+
+/// Settings of the setup that change what the script runs, approved with it. One line each,
+/// appended to the script.
+fn run_settings(setup: &Setup, command_name: Option<&str>, script: &Script) -> Result<Vec<String>> {
+    let local_setup = setup.local_setup().unwrap();
+    let local_setup = local_setup.borrow();
+    let mut settings = vec![];
+    let (interpreter, args, depends_on) = match command_name {
+        Some(command_name) => {
+            let command = setup.local_cfg_command(command_name)?;
+            (None, command.args().clone(), command.depends_on().clone())
+        }
+        None => (
+            local_setup.interpreter().cloned(),
+            local_setup.args(),
+            local_setup.depends_on(),
+        ),
+    };
+    if let Some(interpreter) = interpreter {
+        settings.push(format!("interpreter: {}", interpreter));
+    }
+    if let Some(cwd) = setup_cwd(setup, script)? {
+        settings.push(format!("cwd: {}", cwd.to_string_lossy()));
+    }
+    if !args.is_empty() {
+        settings.push(format!("args: {}", serde_json::to_string(&args)?));
+    }
+    if let Some(vars) = local_setup.vars() {
+        settings.push(format!("vars: {}", serde_json::to_string(&*vars.borrow())?));
+    }
+    if let Some(array_vars) = local_setup.array_vars() {
+        settings.push(format!(
+            "array_vars: {}",
+            serde_json::to_string(&*array_vars.borrow())?
+        ));
+    }
+    if !depends_on.is_empty() {
+        settings.push(format!(
+            "depends_on: {}",
+            serde_json::to_string(&depends_on)?
+        ));
+    }
+    Ok(settings
+        .into_iter()
+        .map(|setting| format!("# sht {}", setting))
+        .collect())
+}
+
+/// Content trusted with `allow` : the run file of the setup or one of its `commands`, with the
+/// settings that change what it runs.
+struct Allowable {
+    /// Run file or command name, displayed to the user.
+    label: String,
+    command_name: Option<String>,
+    /// Env with an `envs` override, approved apart from the others.
+    env_name: Option<String>,
+    content: String,
+    hash: String,
+}
+
+impl Allowable {
+    fn read(setup: &Setup, command_name: Option<&str>) -> Result<Self> {
+        let script = setup_script(setup, command_name)?;
+        let (label, mut content) = match &script {
+            Script::File(file) => (file.to_string_lossy().into_owned(), read_run_file(file)?),
+            Script::Inline(line) => (command_name.unwrap_or_default().to_string(), line.clone()),
+        };
+        for setting in run_settings(setup, command_name, &script)? {
+            if !content.is_empty() && !content.ends_with('\n') {
+                content.push('\n');
+            }
+            content.push_str(&setting);
+        }
+        let hash = hex(&sha256(content.as_bytes()));
+        let env_name = setup.resolved_env().cloned().filter(|env_name| {
+            let local_setup = setup.local_setup().unwrap();
+            let local_setup = local_setup.borrow();
+            local_setup.env_cfg(env_name).is_some()
+        });
+        Ok(Self {
+            label,
+            command_name: command_name.map(|name| name.to_string()),
+            env_name,
+            content,
+            hash,
+        })
+    }
+
+    fn allowed_hash(&self, setup: &Setup) -> Option<String> {
+        let global_setup = setup.global_setup().unwrap();
+        let global_setup = global_setup.borrow();
+        match (&self.command_name, &self.env_name) {
+            (Some(command_name), Some(env_name)) => global_setup
+                .allowed_env_command(env_name, command_name)
+                .cloned(),
+            (Some(command_name), None) => global_setup.allowed_command(command_name).cloned(),
+            (None, Some(env_name)) => global_setup.allowed_env_run_file(env_name).cloned(),
+            (None, None) => global_setup.allowed_run_file().cloned(),
+        }
+    }
+
+    fn approve(&self, setup: &Setup) -> Result<()> {
+        write_all_dir(allowed_file(&self.hash)?, &self.content)?;
+        let global_setup = setup.global_setup().unwrap();
+        let mut global_setup = global_setup.borrow_mut();
+        match (&self.command_name, &self.env_name) {
+            (Some(command_name), Some(env_name)) => global_setup.set_allowed_env_command(
+                env_name.clone(),
+                command_name.clone(),
+                self.hash.clone(),
+            ),
+            (Some(command_name), None) => {
+                global_setup.set_allowed_command(command_name.clone(), self.hash.clone())
+            }
+            (None, Some(env_name)) => {
+                global_setup.set_allowed_env_run_file(env_name.clone(), self.hash.clone())
+            }
+            (None, None) => global_setup.set_allowed_run_file(self.hash.clone()),
+        }
+        Ok(())
+    }
+
+    fn not_allowed(self, setup: &Setup) -> Result<anyhow::Error> {
+        let setup_name = setup.name()?;
+        let error = match (self.command_name, self.env_name) {
+            (command_name, Some(env_name)) => {
+                let mut allow_args = format!("-e {}", env_name);
+                if let Some(command_name) = command_name {
+                    allow_args.push_str(&format!(" {}", command_name));
+                }
+                CliError::EnvOverrideNotAllowed(setup_name, env_name, allow_args)
+            }
+            (Some(command_name), None) => CliError::CommandNotAllowed(command_name, setup_name),
+            (None, None) => CliError::RunFileNotAllowed(PathBuf::from(self.label), setup_name),
+        };
+        Ok(error.into())
+    }
+}
+
+pub fn allow(app: &ArgMatches) -> Result<()> {
+    let mut cfg = get_cfg()?;
+    cfg.sync_local_to_global()?;
+    let cfg = cfg;
+
+    let settings = get_settings(app, &cfg);
+    let setup_name = settings.setup()?;
+    let mut setup = cfg.current_setup(setup_name)?;
+    if let Ok(env) = settings.env() {
+        setup = setup.for_env(env)?;
+    }
+
+    let allowable = Allowable::read(&setup, app.value_of("command"))?;
+    allowable.approve(&setup)?;
+    let hooks_allowed = allowable.command_name.is_none() && approve_hooks(&setup)?;
+    cfg.save_global()?;
+
+    success(
+        format!(
+            "`{}` allowed for `{}`",
+            allowable.label.bold(),
+            setup_name.bold()
+        )
+        .as_str(),
+    );
+    if hooks_allowed {
+        success(format!("hooks allowed for `{}`", setup_name.bold()).as_str());
+    }
+    Ok(())
+}
+
+/// Trust on first use : the run file, or the command `command_name`, is approved the first
+/// time it runs, after that any change must be approved with `allow`. The `envs` overrides are
+/// not trusted on first use, a new override must be approved with `allow -e <env>`.
+pub fn check_allowed(cfg: &Cfg, setup: &Setup, command_name: Option<&str>) -> Result<()> {
+    let allowable = Allowable::read(setup, command_name)?;
+
+    match allowable.allowed_hash(setup) {
+        None if allowable.env_name.is_some() => {
+            let env_name = allowable.env_name.as_deref().unwrap_or_default();
+            bad_info(
+                format!(
+                    "`{}` is overridden for env `{}`",
+                    allowable.label.bold(),
+                    env_name
+                )
+                .as_str(),
+            );
+            for line in allowable.content.lines() {
+                message(format!("+ {}", line).green().to_string().as_str());
+            }
+            Err(allowable.not_allowed(setup)?)
+        }
+        None => {
+            allowable.approve(setup)?;
+            cfg.save_global()
+        }
+        Some(allowed_hash) if allowed_hash == allowable.hash => Ok(()),
+        Some(allowed_hash) => {
+            bad_info(format!("`{}` changed since it was allowed", allowable.label.bold()).as_str());
+            print_changes(&allowed_hash, &allowable.content)?;
+            Err(allowable.not_allowed(setup)?)
+        }
+    }
+}
+
+/// A dependency step runs without being named by the user, it is not trusted on first use and
+/// must have been approved with `allow`. Its changes are refused by `check_allowed`.
+pub fn check_dependency_allowed(setup: &Setup, command_name: Option<&str>) -> Result<()> {
+    let allowable = Allowable::read(setup, command_name)?;
+    if allowable.allowed_hash(setup).is_some() {
+        return Ok(());
+    }
+
+    let setup_name = setup.name()?;
+    let mut step = setup_name.clone();
+    let mut allow_args = format!("-s {}", setup_name);
+    if let Some(env_name) = &allowable.env_name {
+        allow_args.push_str(&format!(" -e {}", env_name));
+    }
+    if let Some(command_name) = &allowable.command_name {
+        step.push_str(&format!(":{}", command_name));
+        allow_args.push_str(&format!(" {}", command_name));
+    }
+    bad_info(format!("dependency `{}` has never been allowed", step.bold()).as_str());
+    for line in allowable.content.lines() {
+        message(format!("+ {}", line).green().to_string().as_str());
+    }
+    Err(CliError::DependencyNotAllowed(step, allow_args).into())
+}
+
+/// The `hooks` of the setup, if any, must have been approved with `allow`. They are not trusted
+/// on first use, a hook can run on `use` or `sync` before any run.
+pub fn check_hooks_allowed(setup: &Setup) -> Result<()> {
+    let (content, hash) = match read_hooks(setup)? {
+        Some(hooks) => hooks,
+        None => return Ok(()),
+    };
+    let global_setup = setup.global_setup().unwrap();
+    let allowed_hash = global_setup.borrow().allowed_hooks().cloned();
+    match allowed_hash {
+        Some(allowed_hash) if allowed_hash == hash => Ok(()),
+        Some(allowed_hash) => {
+            bad_info("hooks changed since they were allowed");
+            print_changes(&allowed_hash, &content)?;
+            Err(CliError::HooksNotAllowed(setup.name()?).into())
+        }
+        None => {
+            for line in content.lines() {
+                message(format!("+ {}", line).green().to_string().as_str());
+            }
+            Err(CliError::HooksNotAllowed(setup.name()?).into())
+        }
+    }
+}
+
+/// Content and hash of the `hooks` of the setup, `None` without hooks.
+fn read_hooks(setup: &Setup) -> Result<Option<(String, String)>> {
+    let local_setup = setup.local_setup().unwrap();
+    let hooks = local_setup.borrow().hooks();
+    if hooks.is_empty() {
+        return Ok(None);
+    }
+    let content: String = Hook::ALL
+        .iter()
+        .filter_map(|hook| {
+            hooks
+                .get(*hook)
+                .map(|line| format!("{}: {}\n", hook.as_ref(), line))
+        })
+        .collect();
+    let hash = hex(&sha256(content.as_bytes()));
+    Ok(Some((content, hash)))
+}
+
+fn approve_hooks(setup: &Setup) -> Result<bool> {
+    let (content, hash) = match read_hooks(setup)? {
+        Some(hooks) => hooks,
+        None => return Ok(false),
+    };
+    write_all_dir(allowed_file(&hash)?, &content)?;
+    let global_setup = setup.global_setup().unwrap();
+    global_setup.borrow_mut().set_allowed_hooks(hash);
+    Ok(true)
+}
+
+/// Lines changed since the content approved as `allowed_hash`.
+fn print_changes(allowed_hash: &str, content: &str) -> Result<()> {
+    if let Ok(allowed_content) = read_to_string(allowed_file(allowed_hash)?) {
+        for line in line_diff(&allowed_content, content) {
+            match line {
+                LineDiff::Removed(line) => {
+                    message(format!("- {}", line).red().to_string().as_str())
+                }
+                LineDiff::Added(line) => {
+                    message(format!("+ {}", line).green().to_string().as_str())
+                }
+                LineDiff::Same(_) => {}
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use anyhow::Result;
+use clap::ArgMatches;
+use colored::*;
+
+use crate::cli::cfg::get_cfg;
+use crate::cli::error::CliError;
+use crate::cli::settings::get_settings;
+use crate::cli::terminal::message::{info, success};
+
+use super::run::{setup_env_vars, setup_requirements};
+
+/// Check and list the `requires` of the setup, without running it.
+pub fn check_requirements(app: &ArgMatches) -> Result<()> {
+    let mut cfg = get_cfg()?;
+    cfg.sync_local_to_global()?;
+    let cfg = cfg;
+
+    let settings = get_settings(app, &cfg);
+    let setup_name = settings.setup()?;
+    let env_name = settings.env()?;
+    let setup = cfg.current_setup(setup_name)?.for_env(env_name)?;
+    let env = setup.env(env_name)?;
+    let env_vars = setup_env_vars(&setup, &env)?;
+
+    let checks = setup_requirements(&setup, &env, &env_vars)?;
+    if checks.is_empty() {
+        info(format!("no requirements declared for `{}`", setup_name.bold()).as_str());
+        return Ok(());
+    }
+    for check in checks.iter() {
+        check.print();
+    }
+
+    let missing = checks.iter().filter(|check| !check.is_met()).count();
+    if missing > 0 {
+        return Err(CliError::MissingRequirements(setup_name.clone(), missing).into());
+    }
+    success(format!("all the requirements of `{}` are met", setup_name.bold()).as_str());
+    Ok(())
+}
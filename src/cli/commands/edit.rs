@@ -4,12 +4,17 @@ use std::process::Command;
 
 use anyhow::{Context, Result};
 use clap::ArgMatches;
+use tempdir::TempDir;
 
+use crate::cfg::Hook;
 use crate::cli::cfg::get_cfg;
 use crate::cli::error::CliError;
+use crate::cli::protected::confirm_protected_env;
 use crate::cli::settings::get_settings;
 use crate::cli::terminal::message::success;
+use crate::env_file::{path_from_env_name, Env};
 
+use super::run::setup_hook;
 use super::sync::{sync_workflow, SyncSettings};
 
 pub fn env_edit(app: &ArgMatches) -> Result<()> {
@@ -21,33 +26,56 @@ pub fn env_edit(app: &ArgMatches) -> Result<()> {
     let env_name = settings.env()?;
 
     let editor = app.value_of("editor");
-    let sync_settings = SyncSettings::new(app);
+    let mut sync_settings = SyncSettings::new(app);
 
     let setup = cfg.current_setup(settings.setup()?)?;
-    let env_file = setup.env_file(env_name)?;
+    let env = setup.env(env_name)?;
+    if setup.is_protected(&env) {
+        confirm_protected_env(env_name)?;
+    }
+
+    // Vault envs are edited through a temporary file and written back after.
+    let tmp_dir = TempDir::new("edit")?;
+    let edit_file = match env.file() {
+        Some(file) => file.clone(),
+        None => {
+            let edit_file = path_from_env_name(tmp_dir.path(), env_name);
+            env.copy(edit_file.clone()).save()?;
+            edit_file
+        }
+    };
 
-    let command = |editor: &str| Command::new(editor).arg(&env_file).status();
+    let command = |editor: &str| Command::new(editor).arg(&edit_file).status();
     let exist_code = if let Some(editor) = editor {
         command(editor)?
     } else if let Ok(editor) = env::var("EDITOR") {
         command(editor.as_str())?
     } else {
-        open::that(&env_file)?
+        open::that(&edit_file)?
     };
 
     if exist_code.code().is_none() || exist_code.code().unwrap() > 0 {
         return Err(CliError::OpenEditorFail.into());
     }
 
+    if env.file().is_none() {
+        let mut edited_env = Env::from_file_reader(&edit_file)?;
+        edited_env.set_location(env.location().clone());
+        edited_env.save()?;
+    }
+
     let env = setup
         .env(env_name)
         .context(format!("fail to check env file `{}`", env_name.bold()))?;
 
     success(format!("`{}` edited", env_name.bold()).as_str());
 
-    let envs = setup.envs().into_iter().filter_map(|r| r.ok()).collect();
+    let envs: Vec<_> = setup.envs().into_iter().filter_map(|r| r.ok()).collect();
+    sync_settings.protect_envs(&setup, &envs);
 
     sync_workflow(env, envs, sync_settings)?;
 
+    setup_hook(&setup, &setup.env(env_name)?, Hook::PostEdit)?;
+
     Ok(())
 }
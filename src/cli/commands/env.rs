@@ -0,0 +1,92 @@
+use std::env;
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use clap::ArgMatches;
+
+use crate::cli::cfg::get_cfg;
+use crate::cli::error::CliError;
+use crate::cli::settings::get_settings;
+use crate::cli::shell::{is_shell_control_var, Shell, EXPORTED_VARS_VAR};
+use crate::BIN_NAME;
+
+use super::run::setup_env_vars;
+
+pub fn env(app: &ArgMatches) -> Result<()> {
+    let shell = match app.value_of("shell") {
+        Some(shell) => Shell::from_str(shell).context(format!("unknown shell `{}`", shell))?,
+        None => Shell::current().1,
+    };
+
+    if app.is_present("hook") {
+        print!("{}", shell.hook(BIN_NAME)?);
+        return Ok(());
+    }
+
+    let exported: Vec<String> = env::var(EXPORTED_VARS_VAR)
+        .unwrap_or_default()
+        .split(':')
+        .filter(|name| !name.is_empty())
+        .map(|name| name.to_string())
+        .collect();
+
+    // Outside of a project the vars of the previous call are unset.
+    let vars = match export_vars(app) {
+        Ok(vars) => vars,
+        Err(err) => {
+            for name in exported.iter() {
+                println!("{}", shell.unset(name));
+            }
+            if !exported.is_empty() {
+                println!("{}", shell.unset(EXPORTED_VARS_VAR));
+            }
+            return Err(err);
+        }
+    };
+
+    for name in exported.iter() {
+        if !vars.iter().any(|(var_name, _)| var_name == name) {
+            println!("{}", shell.unset(name));
+        }
+    }
+    for (name, value) in vars.iter() {
+        println!("{}", shell.export(name, value));
+    }
+    let names: Vec<_> = vars.iter().map(|(name, _)| name.as_str()).collect();
+    println!(
+        "{}",
+        shell.export(EXPORTED_VARS_VAR, names.join(":").as_str())
+    );
+
+    Ok(())
+}
+
+fn export_vars(app: &ArgMatches) -> Result<Vec<(String, String)>> {
+    let mut cfg = get_cfg()?;
+    cfg.sync_local_to_global()?;
+    let cfg = cfg;
+
+    let settings = get_settings(app, &cfg);
+
+    let setup_name = settings.setup()?;
+    let env = settings.env()?;
+    let setup = cfg.current_setup(setup_name)?;
+
+    let env = setup.env(env)?;
+    if setup.is_protected(&env) {
+        return Err(CliError::ProtectedEnvNotExported(env.name()?).into());
+    }
+
+    let env_vars = setup_env_vars(&setup, &env)?;
+    let mut vars = vec![];
+    for env_var in env_vars.iter() {
+        let name = env_var.var().to_env_var();
+        if is_shell_control_var(&name) {
+            // A comment, the output is evaluated by the shell.
+            println!("# {} not exported, it is read by the shell", name);
+            continue;
+        }
+        vars.push((name, env_var.env_value().to_string()));
+    }
+    Ok(vars)
+}
@@ -0,0 +1,59 @@
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use clap::ArgMatches;
+
+use crate::cli::cfg::get_cfg;
+use crate::cli::history::{record, HistoryCommand, HistoryEntry};
+use crate::cli::protected::confirm_protected_env;
+use crate::cli::settings::get_settings;
+use crate::run_file;
+use crate::run_file::{program_command, RunMode};
+
+use super::run::{exit_status, mask_secrets, secret_vars, setup_env_vars};
+
+pub fn exec(app: &ArgMatches) -> Result<()> {
+    let mut cfg = get_cfg()?;
+    cfg.sync_local_to_global()?;
+    let cfg = cfg;
+
+    let settings = get_settings(app, &cfg);
+
+    let command_line = app.values_of_lossy("command").unwrap_or(vec![]);
+    let (program, args) = command_line
+        .split_first()
+        .ok_or_else(|| anyhow!("no command to execute"))?;
+
+    let setup_name = settings.setup()?;
+    let env = settings.env()?;
+    let setup = cfg.current_setup(setup_name)?;
+
+    let env = setup.env(env)?;
+    if setup.is_protected(&env) {
+        confirm_protected_env(&env.name()?)?;
+    }
+
+    let env_vars = setup_env_vars(&setup, &env)?;
+
+    let command = program_command(program, &env_vars, args);
+    let mut entry = HistoryEntry::new(
+        HistoryCommand::Exec,
+        setup.local_cfg_dir()?,
+        setup_name.clone(),
+        env.name()?,
+        &command_line,
+        mask_secrets(
+            &command_line,
+            &env_vars,
+            &secret_vars(&setup, &env, &env_vars),
+        ),
+    );
+    let start = Instant::now();
+    let output = run_file::run(RunMode::detect(), command, None, None)
+        .context(format!("fail to exec `{}`", program))?;
+    entry.duration_ms = start.elapsed().as_millis() as u64;
+    entry.status = output.status;
+    record(&entry);
+
+    exit_status(output.status)
+}
@@ -106,6 +106,13 @@ fn generate_template_workflow(
 
         let file = target_directory.join(local_setup.file());
         local_setup.set_file(file);
+
+        for env_cfg in local_setup.env_cfgs_mut() {
+            env_cfg.file = env_cfg
+                .file
+                .as_ref()
+                .map(|file| target_directory.join(file));
+        }
     }
     local_setup.set_name(setup_name.clone());
     cfg.add_local_setup_cfg(local_setup);
@@ -0,0 +1,110 @@
+use colored::*;
+use std::env::current_exe;
+
+use anyhow::{Context, Result};
+use clap::ArgMatches;
+
+use crate::cli::error::CliError;
+use crate::cli::history::{entries, HistoryEntry};
+use crate::cli::terminal::message::{info, message};
+use crate::run_file;
+use crate::run_file::{program_command, RunMode};
+
+use super::run::exit_status;
+
+pub fn history(app: &ArgMatches) -> Result<()> {
+    if let Some(args) = app.subcommand_matches("rerun") {
+        return rerun(args);
+    }
+
+    let setup = app.value_of("setup");
+    let env = app.value_of("environment");
+    let mut entries: Vec<HistoryEntry> = entries()?
+        .into_iter()
+        .filter(|entry| setup.is_none() || setup == Some(entry.setup.as_str()))
+        .filter(|entry| env.is_none() || env == Some(entry.env.as_str()))
+        .collect();
+    if let Some(limit) = app.value_of("limit") {
+        let limit: usize = limit
+            .parse()
+            .context(format!("invalid limit `{}`", limit))?;
+        let skip = entries.len().saturating_sub(limit);
+        entries.drain(..skip);
+    }
+
+    if app.is_present("json") {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    for entry in entries.iter() {
+        let args: Vec<String> = entry
+            .options
+            .iter()
+            .chain(entry.args.iter())
+            .cloned()
+            .collect();
+        let status = if entry.status == 0 {
+            entry.status.to_string().green()
+        } else {
+            entry.status.to_string().red()
+        };
+        message(
+            format!(
+                "{:>4} {} {} {}:{} {} {} [status {}, {}] {}",
+                entry.id,
+                entry.timestamp,
+                entry.user,
+                entry.setup.bold(),
+                entry.env.bold(),
+                entry.command.as_ref(),
+                args.join(" "),
+                status,
+                humantime::format_duration(entry.duration()),
+                entry.project.to_string_lossy()
+            )
+            .as_str(),
+        );
+    }
+    Ok(())
+}
+
+/// Run again the entry, from the same directory with the same setup, env, options and args. An
+/// entry with masked args can't be, the values of the secrets are not recorded.
+fn rerun(app: &ArgMatches) -> Result<()> {
+    let id = app.value_of("id").unwrap();
+    let id: usize = id.parse().context(format!("invalid id `{}`", id))?;
+    let entry = entries()?
+        .into_iter()
+        .find(|entry| entry.id == id)
+        .ok_or(CliError::HistoryEntryNotFound(id))?;
+    if !entry.masked.is_empty() {
+        return Err(CliError::HistoryEntryMasked(id).into());
+    }
+
+    let mut args = vec![
+        entry.command.as_ref().to_string(),
+        "-s".to_string(),
+        entry.setup.clone(),
+        "-e".to_string(),
+        entry.env.clone(),
+    ];
+    args.extend(entry.options.iter().cloned());
+    args.push("--".to_string());
+    args.extend(entry.args.iter().cloned());
+
+    let directory = if entry.directory.is_dir() {
+        &entry.directory
+    } else {
+        &entry.project
+    };
+
+    let bin = current_exe().context("fail to find current executable")?;
+    let mut command = program_command(&bin.to_string_lossy(), &[], &args);
+    command.current_dir(directory);
+
+    info(format!("rerun {} `{}`", id, args.join(" ")).as_str());
+    let output = run_file::run(RunMode::detect(), command, None, None)
+        .context(format!("fail to rerun {}", id))?;
+    exit_status(output.status)
+}
@@ -0,0 +1,56 @@
+use std::fs::File;
+use std::io;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::thread::sleep;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use clap::ArgMatches;
+
+use crate::cli::instances::select_instance;
+
+const DEFAULT_LINES: usize = 20;
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Print the end of the log of an instance, `--follow` prints the new lines until it stops.
+pub fn logs(app: &ArgMatches) -> Result<()> {
+    let instance = select_instance(app)?;
+    let lines: usize = match app.value_of("lines") {
+        Some(lines) => lines
+            .parse()
+            .context(format!("invalid lines `{}`", lines))?,
+        None => DEFAULT_LINES,
+    };
+
+    let mut file =
+        File::open(&instance.log).context(format!("fail to open log file {:?}", instance.log))?;
+    let mut content = String::new();
+    file.read_to_string(&mut content)?;
+    let all_lines: Vec<&str> = content.lines().collect();
+    let mut stdout = io::stdout();
+    for line in all_lines[all_lines.len().saturating_sub(lines)..].iter() {
+        writeln!(stdout, "{}", line)?;
+    }
+
+    if !app.is_present("follow") {
+        return Ok(());
+    }
+    let mut position = file.seek(SeekFrom::End(0))?;
+    loop {
+        // Checked before the read, to not miss the last lines.
+        let running = instance.is_running();
+        let len = file.metadata()?.len();
+        if len < position {
+            // Truncated, read it again from the start.
+            position = file.seek(SeekFrom::Start(0))?;
+        }
+        let mut bytes = vec![];
+        position += file.read_to_end(&mut bytes)? as u64;
+        stdout.write_all(&bytes)?;
+        stdout.flush()?;
+        if !running {
+            return Ok(());
+        }
+        sleep(FOLLOW_INTERVAL);
+    }
+}
@@ -7,7 +7,7 @@ use crate::cli::cfg::get_cfg;
 use crate::cli::settings::get_settings;
 use crate::cli::terminal::emoji;
 use crate::cli::terminal::message::message;
-use crate::env_file::Env;
+use crate::env_file::{Env, EnvLocation};
 
 fn line(msg: &str, selected: &bool, colored: &bool) {
     let output = if *selected {
@@ -40,7 +40,16 @@ pub fn ls(app: &ArgMatches) -> Result<()> {
 
         let local_setup_cfg = local_setup.local_setup().unwrap();
         let local_setup_cfg = local_setup_cfg.borrow();
-        let run_file = local_setup_cfg.file();
+        let mut run_file = vec![];
+        if let Some(interpreter) = local_setup_cfg.interpreter() {
+            run_file.push(interpreter.clone());
+        }
+        run_file.push(local_setup_cfg.file().to_string_lossy().into_owned());
+        run_file.extend(local_setup_cfg.args());
+        let mut run_file = run_file.join(" ");
+        if let Some(cwd) = local_setup_cfg.cwd() {
+            run_file.push_str(format!(", cwd {}", cwd).as_str());
+        }
 
         let mut selected = false;
         let mut colored = false;
@@ -54,11 +63,29 @@ pub fn ls(app: &ArgMatches) -> Result<()> {
         }
 
         line(
-            format!("{} ({})", &setup_name.bold(), run_file.to_string_lossy()).as_str(),
+            format!("{} ({})", &setup_name.bold(), run_file).as_str(),
             &selected,
             &colored,
         );
 
+        for (command_name, command) in local_setup_cfg.commands() {
+            let detail = command
+                .description()
+                .cloned()
+                .or_else(|| {
+                    command
+                        .file()
+                        .map(|file| file.to_string_lossy().into_owned())
+                })
+                .or_else(|| command.command().cloned())
+                .unwrap_or_default();
+            line(
+                format!("   run {} ({})", command_name.bold(), detail).as_str(),
+                &false,
+                &colored,
+            );
+        }
+
         let envs: Vec<Env> = local_setup
             .envs()
             .into_iter()
@@ -94,13 +121,17 @@ pub fn ls(app: &ArgMatches) -> Result<()> {
                     false
                 };
 
-                let env_file = env
-                    .file()
-                    .strip_prefix(project.dir()?)
-                    .unwrap_or(env.file());
+                let env_location = match env.location() {
+                    EnvLocation::File(file) => file
+                        .strip_prefix(project.dir()?)
+                        .unwrap_or(file)
+                        .to_string_lossy()
+                        .into_owned(),
+                    env_location => env_location.to_string(),
+                };
 
                 line(
-                    format!("   {} ({})", &env_name.bold(), env_file.to_string_lossy()).as_str(),
+                    format!("   {} ({})", &env_name.bold(), env_location).as_str(),
                     &selected,
                     &selected,
                 );
@@ -1,29 +1,55 @@
+pub use allow::allow;
+pub use check_requirements::check_requirements;
 pub use dir::env_dir;
 pub use edit::env_edit;
+pub use env::env;
 pub use envs::envs;
+pub use exec::exec;
 pub use generate::generate;
+pub use history::history;
 pub use init::init;
+pub use logs::logs;
 pub use ls::ls;
 pub use new::env_new;
 pub use pdir::env_pdir;
+pub use ps::ps;
 pub use r#use::r#use;
 pub use rename::rename;
-pub use run::run;
+pub use run::{run, run_help};
+pub use scan::scan;
+pub use shell::shell;
 pub use show::{show, DEFAULT_SHOW_FORMAT};
+pub use ssm::ssm;
+pub use start::start;
+pub use stop::stop;
 pub use sync::{env_sync, sync_workflow, SyncConfirmEnum, SyncSettings};
+pub use up::up;
 pub use vars::vars;
 
+mod allow;
+mod check_requirements;
 mod dir;
 mod edit;
+mod env;
 mod envs;
+mod exec;
 mod generate;
+mod history;
 mod init;
+mod logs;
 mod ls;
 mod new;
 mod pdir;
+mod ps;
 mod rename;
 mod run;
+mod scan;
+mod shell;
 mod show;
+mod ssm;
+mod start;
+mod stop;
 mod sync;
+mod up;
 mod r#use;
 mod vars;
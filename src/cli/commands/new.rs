@@ -1,7 +1,6 @@
 use anyhow::Result;
 use clap::ArgMatches;
 use colored::*;
-use std::path::PathBuf;
 
 use crate::cfg::Cfg;
 use crate::cli::cfg::get_cfg;
@@ -9,7 +8,7 @@ use crate::cli::commands::sync::{sync_workflow, SyncSettings};
 use crate::cli::error::CliError;
 use crate::cli::settings::get_settings;
 use crate::cli::terminal::message::success;
-use crate::env_file::{path_from_env_name, Env};
+use crate::env_file::{path_from_env_name, Env, EnvLocation};
 
 use super::r#use::use_workflow;
 
@@ -19,15 +18,16 @@ pub fn env_new(app: &ArgMatches) -> Result<()> {
     let cfg = cfg;
 
     let mut settings = get_settings(app, &cfg);
-    let sync_settings = SyncSettings::new(&app);
+    let mut sync_settings = SyncSettings::new(&app);
 
     let setup_name = settings.setup()?;
     let env_name: String = app.value_of("name").unwrap().into();
     let private = app.is_present("private");
 
     let setup = cfg.current_setup(setup_name)?;
-    let mut envs = setup.envs().into_iter().filter_map(|r| r.ok()).collect();
+    let mut envs: Vec<_> = setup.envs().into_iter().filter_map(|r| r.ok()).collect();
     let recent_env = Env::recent(&envs);
+    sync_settings.protect_envs(&setup, &envs);
 
     let new_env = env_new_workflow(&cfg, &setup_name, &env_name, &private, &false)?;
     envs.push(new_env.clone());
@@ -44,7 +44,14 @@ pub fn env_new(app: &ArgMatches) -> Result<()> {
     use_workflow(&cfg, &settings)?;
     cfg.save()?;
 
-    success(format!("env `{}` created : `{:?}`", env_name.bold(), new_env.file()).as_str());
+    success(
+        format!(
+            "env `{}` created : `{}`",
+            env_name.bold(),
+            new_env.location()
+        )
+        .as_str(),
+    );
     Ok(())
 }
 
@@ -57,26 +64,31 @@ pub fn env_new_workflow(
 ) -> Result<Env> {
     let setup = cfg.current_setup(setup_name)?;
 
-    let retrieve_env_is_not_exists = |dir: PathBuf| -> Result<Env> {
-        let env = path_from_env_name(dir, env_name);
-        let mut env: Env = env.into();
+    let retrieve_env_is_not_exists = |env_location: EnvLocation| -> Result<Env> {
+        let mut env = Env::from_location(env_location);
         if *example {
             env.add("VAR1", "VALUE1");
             env.add("VAR2", "VALUE2");
         }
-        if env.file().exists() {
-            return Err(CliError::EnvFileAlreadyExists(env.file().clone(), env.clone()).into());
+        if env.location().exists()? {
+            return Err(CliError::EnvFileAlreadyExists(env.location().clone(), env.clone()).into());
         } else {
             Ok(env)
         }
     };
 
-    let public_env = setup.envs_public_dir().map(retrieve_env_is_not_exists);
+    let public_env = setup
+        .envs_public_dir()
+        .map(|dir| EnvLocation::File(path_from_env_name(dir, env_name)))
+        .map(retrieve_env_is_not_exists);
     if let Ok(Err(err)) = public_env {
         return Err(err);
     };
 
-    let private_env = setup.envs_private_dir().map(retrieve_env_is_not_exists);
+    let private_env = setup
+        .envs_private_dir()
+        .map(|dir| dir.env_location(env_name))
+        .map(retrieve_env_is_not_exists);
     if let Ok(Err(err)) = private_env {
         return Err(err);
     };
@@ -9,6 +9,7 @@ use crate::cli::cfg::get_cfg;
 use crate::cli::error::CliError;
 use crate::cli::settings::{get_settings, Settings};
 use crate::cli::terminal::message::success;
+use crate::vault::VaultSource;
 
 pub fn env_pdir(app: &ArgMatches) -> Result<()> {
     let mut cfg = get_cfg()?;
@@ -18,7 +19,11 @@ pub fn env_pdir(app: &ArgMatches) -> Result<()> {
     let settings = get_settings(app, &cfg);
 
     if let Some(env_dir) = app.value_of("env_dir") {
-        set(cfg, settings, env_dir.into())
+        if VaultSource::is_source(env_dir) {
+            set_source(cfg, settings, env_dir.into())
+        } else {
+            set(cfg, settings, env_dir.into())
+        }
     } else if app.is_present("unset") {
         unset(cfg, settings)
     } else {
@@ -49,13 +54,33 @@ fn set(cfg: Cfg, settings: Settings, env_dir: PathBuf) -> Result<()> {
     Ok(())
 }
 
+fn set_source(cfg: Cfg, settings: Settings, source: String) -> Result<()> {
+    let setup_name = settings.setup()?;
+    let setup = cfg.current_setup(setup_name)?;
+
+    let global_setup = setup.global_setup().unwrap();
+    let mut global_setup = global_setup.borrow_mut();
+    global_setup.set_private_env_source(source.clone())?;
+    drop(global_setup);
+
+    cfg.save()?;
+
+    success(format!("private env source set to `{}`", source).as_str());
+
+    Ok(())
+}
+
 fn unset(cfg: Cfg, settings: Settings) -> Result<()> {
     let setup_name = settings.setup()?;
     let setup = cfg.current_setup(setup_name)?;
 
     let global_setup = setup.global_setup().unwrap();
     let mut global_setup = global_setup.borrow_mut();
-    global_setup.unset_private_env_dir()?;
+    if global_setup.private_env_source().is_ok() {
+        global_setup.unset_private_env_source()?;
+    } else {
+        global_setup.unset_private_env_dir()?;
+    }
     drop(global_setup);
 
     cfg.save()?;
@@ -0,0 +1,51 @@
+use anyhow::Result;
+use clap::ArgMatches;
+use colored::*;
+
+use crate::cli::instances::{instances, remove};
+use crate::cli::terminal::message::{good_info, message};
+
+/// List the instances started by `sht start`, `--clean` removes the ones no longer running.
+pub fn ps(app: &ArgMatches) -> Result<()> {
+    let instances = instances()?;
+
+    if app.is_present("clean") {
+        let mut removed = 0;
+        for instance in instances.iter().filter(|instance| !instance.is_running()) {
+            remove(instance)?;
+            removed += 1;
+        }
+        good_info(format!("{} stopped instance(s) removed", removed).as_str());
+        return Ok(());
+    }
+
+    let width = instances
+        .iter()
+        .map(|instance| instance.name().len() + instance.env.len() + 1)
+        .max()
+        .unwrap_or(0);
+    for instance in instances.iter() {
+        let (status, uptime) = if instance.is_running() {
+            (
+                "running".green(),
+                humantime::format_duration(instance.uptime()).to_string(),
+            )
+        } else {
+            ("stopped".red(), "-".to_string())
+        };
+        let name = format!("{}:{}", instance.name(), instance.env);
+        message(
+            format!(
+                "{:>7} {:width$} {} {} {}",
+                instance.pid,
+                name,
+                status,
+                uptime,
+                instance.project.to_string_lossy(),
+                width = width
+            )
+            .as_str(),
+        );
+    }
+    Ok(())
+}
@@ -1,45 +1,1050 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::process::Command;
+use std::thread;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
 use anyhow::{Context, Result};
 use clap::ArgMatches;
+use colored::*;
+use serde::Serialize;
 
+use crate::cfg::{Cfg, CfgError, Hook, Setup, SetupArgument, SetupHooks};
+use crate::cli::arguments::{parse_arguments, print_arguments_help};
 use crate::cli::cfg::get_cfg;
-use crate::cli::settings::get_settings;
-use crate::run_file::{generate_env_vars, run_as_stream, EnvVar};
+use crate::cli::error::CliError;
+use crate::cli::history::{record, HistoryCommand, HistoryEntry};
+use crate::cli::hooks::run_hook;
+use crate::cli::protected::confirm_protected_env;
+use crate::cli::requirements::{check_requirements, ensure_requirements, RequirementCheck};
+use crate::cli::settings::{get_settings, Settings};
+use crate::cli::steps::{run_steps, Plan, StepId};
+use crate::cli::terminal::message::{bad_info, good_info, info, message};
+use crate::cli::watch::{watch, WatchRun, WatchSettings};
+use crate::env_file::{is_secret_value, Env, Var};
+use crate::run_file;
+use crate::run_file::{
+    file_command, generate_env_vars, inline_command, interpreted_file_command, parse_signal,
+    EnvValue, EnvVar, RunMode,
+};
+
+use super::allow::{check_allowed, check_dependency_allowed, check_hooks_allowed};
+
+const SECRET_MASK: &str = "********";
+const DEFAULT_RETRY_BACKOFF: Duration = Duration::from_secs(1);
+/// Options of `run` with a value recorded in the history, by name.
+const HISTORY_OPTIONS: [(&str, &str); 8] = [
+    ("log", "--log"),
+    ("jobs", "--jobs"),
+    ("timeout", "--timeout"),
+    ("retries", "--retries"),
+    ("retry_backoff", "--retry-backoff"),
+    ("stop_signal", "--stop-signal"),
+    ("grace_period", "--grace-period"),
+    ("debounce", "--debounce"),
+];
+/// Colors of the output prefixes when several envs or processes run.
+pub const ENV_COLORS: [Color; 6] = [
+    Color::Cyan,
+    Color::Magenta,
+    Color::Yellow,
+    Color::Green,
+    Color::Blue,
+    Color::Red,
+];
+
+/// What a run executes: the run file of the setup or one of its `commands`.
+#[derive(Debug, Clone)]
+pub enum Script {
+    File(PathBuf),
+    /// Command line run by `sh`.
+    Inline(String),
+}
+
+/// Run file of a setup, or of its command `command_name`, with the vars of an env, ready to be run.
+pub struct PreparedRun {
+    pub project_dir: PathBuf,
+    pub setup_name: String,
+    pub env_name: String,
+    pub command_name: Option<String>,
+    pub script: Script,
+    /// Program that runs the run file, see `LocalSetupCfg::interpreter`.
+    pub interpreter: Option<String>,
+    /// Working directory, instead of the directory of the run file or the project directory.
+    pub cwd: Option<PathBuf>,
+    /// Args of the setup or of the command, passed before the args of the run.
+    pub default_args: Vec<String>,
+    /// Arguments declared by the setup, none for a command.
+    pub arguments: Vec<SetupArgument>,
+    pub env_vars: Vec<EnvVar>,
+    /// Names of the env vars declared as `secrets`, whose value looks like a secret or read from a
+    /// private env.
+    pub secret_vars: Vec<String>,
+    /// Files read to prepare the run: env, local cfg and run file. An env in a vault has none.
+    pub files: Vec<PathBuf>,
+    pub timeout: Option<Duration>,
+    pub retries: u32,
+    pub retry_backoff: Duration,
+    /// `pre_run` and `post_run` run around `sht run`, each restart of `--watch` and before `start`.
+    pub hooks: SetupHooks,
+}
+
+impl PreparedRun {
+    /// `args` are the args of `sht run`, starting with the command name when it is set.
+    pub fn command(&self, args: &[String]) -> Result<Command> {
+        let args = self.args(args);
+        let mut command = match (&self.script, &self.interpreter) {
+            (Script::File(file), Some(interpreter)) => {
+                interpreted_file_command(interpreter, file, &self.env_vars, &args)?
+            }
+            (Script::File(file), None) => file_command(file, &self.env_vars, &args)?,
+            (Script::Inline(line), _) => {
+                inline_command(line, &self.project_dir, &self.env_vars, &args)
+            }
+        };
+        if let Some(cwd) = &self.cwd {
+            command.current_dir(cwd);
+        }
+        Ok(command)
+    }
+
+    /// Args given to the script : the default args of the setup or of the command, then `args`
+    /// without the command name.
+    pub fn args(&self, args: &[String]) -> Vec<String> {
+        let skip = if self.command_name.is_some() { 1 } else { 0 };
+        self.default_args
+            .iter()
+            .chain(args.iter().skip(skip))
+            .cloned()
+            .collect()
+    }
+
+    /// Check the args against the declared `arguments`, their values are added to the env vars.
+    pub fn parse_arguments(&mut self, args: &[String]) -> Result<()> {
+        if self.arguments.is_empty() {
+            return Ok(());
+        }
+        let args = self.args(args);
+        let env_vars = parse_arguments(&self.setup_name, &self.arguments, &args)?;
+        self.env_vars.extend(env_vars);
+        Ok(())
+    }
+
+    pub fn working_directory(&self) -> Result<PathBuf> {
+        if let Some(cwd) = &self.cwd {
+            return Ok(cwd.clone());
+        }
+        match &self.script {
+            Script::File(file) => {
+                let file = file.canonicalize()?;
+                Ok(file
+                    .parent()
+                    .map(|parent| parent.to_path_buf())
+                    .unwrap_or_default())
+            }
+            Script::Inline(_) => Ok(self.project_dir.clone()),
+        }
+    }
+
+    /// Name and value of the env vars, secret values are masked when `masked` is set.
+    pub fn vars(&self, masked: bool) -> Vec<(String, String)> {
+        self.env_vars
+            .iter()
+            .map(|env_var| {
+                let name = env_var.var().to_env_var();
+                let value = if masked && self.secret_vars.contains(&name) {
+                    SECRET_MASK.to_string()
+                } else {
+                    env_var.env_value().to_string()
+                };
+                (name, value)
+            })
+            .collect()
+    }
+
+    /// Run the `hook` of the setup with the vars of the run, see `run_hook`.
+    pub fn run_hook(&self, hook: Hook, exit_status: Option<i32>, mode: &RunMode) -> Result<()> {
+        run_hook(
+            &self.setup_name,
+            &self.hooks,
+            hook,
+            &self.project_dir,
+            &self.env_vars,
+            exit_status,
+            mode,
+        )
+    }
+
+    /// `args` with the values of the secret vars masked, as recorded in the history.
+    pub fn masked_args(&self, args: &[String]) -> Vec<String> {
+        mask_secrets(args, &self.env_vars, &self.secret_vars)
+    }
+
+    /// History entry of a run with `args` and the `options` of `run`.
+    pub fn history_entry(&self, args: &[String], options: Vec<String>) -> HistoryEntry {
+        let mut entry = HistoryEntry::new(
+            HistoryCommand::Run,
+            self.project_dir.clone(),
+            self.setup_name.clone(),
+            self.env_name.clone(),
+            args,
+            self.masked_args(args),
+        );
+        entry.options = options;
+        entry
+    }
+}
+
+/// Replace in `args` the values of the env vars named in `secret_vars` by a mask.
+pub fn mask_secrets(args: &[String], env_vars: &[EnvVar], secret_vars: &[String]) -> Vec<String> {
+    let mut values: Vec<String> = vec![];
+    for env_var in env_vars {
+        if !secret_vars.contains(&env_var.var().to_env_var()) {
+            continue;
+        }
+        values.push(env_var.env_value().to_string());
+        if let EnvValue::ArrayVar((_, vars)) = env_var.env_value() {
+            values.extend(vars.iter().map(|var| var.value().clone()));
+        }
+    }
+    // The longest first, a value can contain another one.
+    values.retain(|value| !value.is_empty());
+    values.sort_by_key(|value| std::cmp::Reverse(value.len()));
+    args.iter()
+        .map(|arg| {
+            values
+                .iter()
+                .fold(arg.clone(), |arg, value| arg.replace(value, SECRET_MASK))
+        })
+        .collect()
+}
+
+#[derive(Serialize)]
+struct DryRun {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    command: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    interpreter: Option<String>,
+    script: String,
+    working_directory: PathBuf,
+    args: Vec<String>,
+    vars: BTreeMap<String, String>,
+    /// Steps run before, in order.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    depends_on: Vec<String>,
+}
 
 pub fn run(app: &ArgMatches) -> Result<()> {
+    let args = app.values_of_lossy("args").unwrap_or(vec![]);
+
+    if app.occurrences_of("environment") > 1
+        && (app.is_present("watch") || app.is_present("dry_run"))
+    {
+        bail!("several envs can't be used with --watch or --dry-run");
+    }
+    if app.is_present("watch") {
+        return run_watch(app, &args);
+    }
+    if app.is_present("dry_run") {
+        return run_dry(app, args);
+    }
+
+    let log = app.value_of("log").map(PathBuf::from);
+
+    let jobs: usize = match app.value_of("jobs") {
+        Some(jobs) => jobs.parse().context(format!("invalid jobs `{}`", jobs))?,
+        None => 1,
+    };
+
     let mut cfg = get_cfg()?;
     cfg.sync_local_to_global()?;
     let cfg = cfg;
 
     let settings = get_settings(app, &cfg);
+    let setup_name = settings.setup()?;
+    let setup = cfg.current_setup(setup_name)?;
+    let env_names = run_env_names(app, &settings, &setup)?;
 
-    let args = app.values_of_lossy("args").unwrap_or(vec![]);
+    let target = StepId::new(setup_name.clone(), args_command_name(&setup, app));
+    let plan = Plan::new(target, |step| step_depends_on(&cfg, step))?;
+
+    let mut env_runs = vec![];
+    for env_name in env_names.iter() {
+        let mut prepared_runs = prepare_plan(&cfg, &plan, env_name, true, true)?;
+        let target_run = prepared_runs.get_mut(&plan.target).unwrap();
+        override_run_settings(app, target_run)?;
+        target_run.parse_arguments(&args)?;
+        env_runs.push(prepared_runs);
+    }
+    check_run_hooks_allowed(&setup)?;
+
+    if env_runs.len() == 1 {
+        let (status, duration) = run_env(
+            &plan,
+            &env_runs[0],
+            &args,
+            log.as_ref(),
+            jobs,
+            RunMode::detect(),
+        );
+        record_run(app, &env_runs[0][&plan.target], &args, duration, &status);
+        return exit_status(status?);
+    }
+
+    let width = env_names.iter().map(|name| name.len()).max().unwrap_or(0);
+    let modes: Vec<RunMode> = env_names
+        .iter()
+        .enumerate()
+        .map(|(i, env_name)| {
+            let prefix = format!("{:width$} |", env_name, width = width);
+            RunMode::Prefixed(prefix.color(ENV_COLORS[i % ENV_COLORS.len()]).to_string())
+        })
+        .collect();
+    let results: Vec<(Result<i32>, Duration)> = if app.is_present("parallel") {
+        thread::scope(|scope| {
+            let threads: Vec<_> = env_runs
+                .iter()
+                .zip(modes.iter())
+                .map(|(prepared_runs, mode)| {
+                    let (plan, args, log) = (&plan, &args, log.as_ref());
+                    scope.spawn(move || run_env(plan, prepared_runs, args, log, jobs, mode.clone()))
+                })
+                .collect();
+            threads
+                .into_iter()
+                .map(|thread| thread.join().expect("fail to wait env run"))
+                .collect()
+        })
+    } else {
+        env_runs
+            .iter()
+            .zip(modes.iter())
+            .map(|(prepared_runs, mode)| {
+                run_env(
+                    &plan,
+                    prepared_runs,
+                    &args,
+                    log.as_ref(),
+                    jobs,
+                    mode.clone(),
+                )
+            })
+            .collect()
+    };
+
+    let mut exit = 0;
+    for ((prepared_runs, mode), (status, duration)) in
+        env_runs.iter().zip(modes.iter()).zip(results.iter())
+    {
+        record_run(app, &prepared_runs[&plan.target], &args, *duration, status);
+        if let Err(err) = status {
+            bad_info(with_prefix(mode, format!("{:#}", err)).as_str());
+        }
+    }
+    info("summary");
+    for (env_name, (status, _)) in env_names.iter().zip(results.iter()) {
+        let status = match status {
+            Ok(0) => "0".green(),
+            Ok(status) => status.to_string().red(),
+            Err(_) => "error".red(),
+        };
+        message(format!("{:width$} {}", env_name.bold(), status, width = width).as_str());
+    }
+    for (status, _) in results.iter() {
+        let status = *status.as_ref().unwrap_or(&1);
+        if status != 0 {
+            exit = status;
+            break;
+        }
+    }
+    exit_status(exit)
+}
+
+/// Envs of the run : all the envs of the setup with `--all-envs`, the `-e` envs or the current env.
+fn run_env_names(app: &ArgMatches, settings: &Settings, setup: &Setup) -> Result<Vec<String>> {
+    if app.is_present("all_envs") {
+        let mut env_names: Vec<String> = setup
+            .envs()
+            .into_iter()
+            .filter_map(|env| env.ok())
+            .filter_map(|env| env.name().ok())
+            .collect();
+        env_names.sort();
+        env_names.dedup();
+        if env_names.is_empty() {
+            bail!("no env found for setup `{}`", setup.name()?);
+        }
+        return Ok(env_names);
+    }
+    match app.values_of_lossy("environment") {
+        Some(names) => {
+            let mut env_names: Vec<String> = vec![];
+            for name in names {
+                if !env_names.contains(&name) {
+                    env_names.push(name);
+                }
+            }
+            Ok(env_names)
+        }
+        None => Ok(vec![settings.env()?.clone()]),
+    }
+}
+
+/// Prepare all the steps of the plan with the env `env_name`, a protected env is confirmed once.
+/// With `check_run` the dependencies of the target must have been allowed.
+fn prepare_plan(
+    cfg: &Cfg,
+    plan: &Plan,
+    env_name: &String,
+    check_run: bool,
+    mut confirm_protected: bool,
+) -> Result<BTreeMap<StepId, PreparedRun>> {
+    let mut prepared_runs = BTreeMap::new();
+    for step in plan.steps.iter() {
+        let setup = cfg.current_setup(&step.setup)?;
+        if check_run && step != &plan.target {
+            check_dependency_allowed(&setup.for_env(env_name)?, step.command.as_deref())?;
+        }
+        let prepared_run = prepare_setup_run(
+            cfg,
+            &setup,
+            env_name,
+            step.command.clone(),
+            check_run,
+            confirm_protected,
+        )?;
+        if setup.is_protected(&setup.env(env_name)?) {
+            confirm_protected = false;
+        }
+        prepared_runs.insert(step.clone(), prepared_run);
+    }
+    Ok(prepared_runs)
+}
+
+/// Timeout and retries given on the command line.
+fn override_run_settings(app: &ArgMatches, prepared_run: &mut PreparedRun) -> Result<()> {
+    if let Some(timeout) = app.value_of("timeout") {
+        prepared_run.timeout = Some(
+            humantime::parse_duration(timeout).context(format!("invalid timeout `{}`", timeout))?,
+        );
+    }
+    if let Some(retries) = app.value_of("retries") {
+        prepared_run.retries = retries
+            .parse()
+            .context(format!("invalid retries `{}`", retries))?;
+    }
+    if let Some(retry_backoff) = app.value_of("retry_backoff") {
+        prepared_run.retry_backoff = humantime::parse_duration(retry_backoff)
+            .context(format!("invalid retry backoff `{}`", retry_backoff))?;
+    }
+    Ok(())
+}
+
+/// Run the plan with the prepared runs of one env, return its exit status and duration.
+fn run_env(
+    plan: &Plan,
+    prepared_runs: &BTreeMap<StepId, PreparedRun>,
+    args: &[String],
+    log: Option<&PathBuf>,
+    jobs: usize,
+    mode: RunMode,
+) -> (Result<i32>, Duration) {
+    let start = Instant::now();
+    let target_run = &prepared_runs[&plan.target];
+    let hook = |hook: Hook, exit_status: Option<i32>| target_run.run_hook(hook, exit_status, &mode);
+    if let Err(err) = hook(Hook::PreRun, None) {
+        return (Err(err), start.elapsed());
+    }
+    let status = if plan.steps.len() == 1 {
+        run_with_retries(target_run, args, log, &mode)
+    } else {
+        run_plan(plan, prepared_runs, args, log, jobs, mode.clone())
+    };
+    let status = hook(Hook::PostRun, Some(*status.as_ref().unwrap_or(&1))).and(status);
+    (status, start.elapsed())
+}
+
+fn record_run(
+    app: &ArgMatches,
+    prepared_run: &PreparedRun,
+    args: &[String],
+    duration: Duration,
+    status: &Result<i32>,
+) {
+    let mut entry = prepared_run.history_entry(args, history_options(app));
+    entry.duration_ms = duration.as_millis() as u64;
+    entry.status = *status.as_ref().unwrap_or(&1);
+    record(&entry);
+}
+
+/// Options of `run` recorded in the history, given again by `history rerun`. The options that
+/// select the envs are not, an entry is recorded for each env.
+fn history_options(app: &ArgMatches) -> Vec<String> {
+    let mut options = vec![];
+    if app.is_present("watch") {
+        options.push("--watch".to_string());
+    }
+    for (name, long) in HISTORY_OPTIONS.iter() {
+        if let Some(value) = app.value_of(name) {
+            options.push(long.to_string());
+            options.push(value.to_string());
+        }
+    }
+    options
+}
+
+/// Prefix the message with the prefix of the mode, if any.
+fn with_prefix(mode: &RunMode, msg: String) -> String {
+    match mode {
+        RunMode::Prefixed(prefix) => format!("{} {}", prefix, msg),
+        _ => msg,
+    }
+}
+
+/// Steps that `step` depends on.
+fn step_depends_on(cfg: &Cfg, step: &StepId) -> Result<Vec<StepId>> {
+    let setup = cfg.current_setup(&step.setup)?;
+    let local_setup = setup.local_setup().unwrap();
+    let local_setup = local_setup.borrow();
+    let depends_on = match &step.command {
+        Some(command_name) => setup.local_cfg_command(command_name)?.depends_on().clone(),
+        None => local_setup.depends_on(),
+    };
+    depends_on
+        .iter()
+        .map(|dependency| {
+            let mut split = dependency.splitn(2, ':');
+            match (split.next(), split.next()) {
+                (Some(setup_name), Some(command_name)) => {
+                    let setup = cfg.current_setup(&setup_name.to_string())?;
+                    setup.local_cfg_command(command_name)?;
+                    Ok(StepId::new(
+                        setup_name.to_string(),
+                        Some(command_name.to_string()),
+                    ))
+                }
+                _ if local_setup.command(dependency).is_some() => {
+                    Ok(StepId::new(step.setup.clone(), Some(dependency.clone())))
+                }
+                _ if cfg.current_setup(dependency).is_ok() => {
+                    Ok(StepId::new(dependency.clone(), None))
+                }
+                _ => Err(CliError::DependencyNotFound(dependency.clone(), step.to_string()).into()),
+            }
+        })
+        .collect()
+}
+
+/// Run the steps of the plan, the target gets the args of the run, its dependencies their
+/// default args. Return the exit status of the failed step, if any.
+fn run_plan(
+    plan: &Plan,
+    prepared_runs: &BTreeMap<StepId, PreparedRun>,
+    args: &[String],
+    log: Option<&PathBuf>,
+    jobs: usize,
+    mode: RunMode,
+) -> Result<i32> {
+    // Parallel steps can't share the terminal.
+    let mode = match mode {
+        RunMode::Interactive if jobs > 1 => RunMode::Stream,
+        mode => mode,
+    };
+    let failure = run_steps(plan, jobs, |step| {
+        let step_args: Vec<String> = if step == &plan.target {
+            args.to_vec()
+        } else {
+            step.command.iter().cloned().collect()
+        };
+        info(with_prefix(&mode, format!("run `{}`", step)).as_str());
+        run_with_retries(&prepared_runs[step], &step_args, log, &mode)
+    })?;
+
+    match failure {
+        None => Ok(0),
+        Some((step, status)) => {
+            let path: Vec<String> = plan.path_to(&step).iter().map(|s| s.to_string()).collect();
+            bad_info(
+                with_prefix(
+                    &mode,
+                    format!(
+                        "`{}` failed with status {} : {}",
+                        step,
+                        status,
+                        path.join(" -> ")
+                    ),
+                )
+                .as_str(),
+            );
+            Ok(status)
+        }
+    }
+}
+
+/// Run what comes before a target run apart by `--watch` or `start` : its `pre_run` hook, then its
+/// dependencies one after the other. The first dependency that fails runs the `post_run` hook and
+/// is an error.
+pub fn run_before_target(
+    plan: &Plan,
+    prepared_runs: &BTreeMap<StepId, PreparedRun>,
+    target_run: &PreparedRun,
+    mode: &RunMode,
+) -> Result<()> {
+    target_run.run_hook(Hook::PreRun, None, mode)?;
+    for step in plan.steps.iter().filter(|step| *step != &plan.target) {
+        info(format!("run `{}`", step).as_str());
+        let step_args: Vec<String> = step.command.iter().cloned().collect();
+        let status = run_with_retries(&prepared_runs[step], &step_args, None, mode)?;
+        if status != 0 {
+            target_run.run_hook(Hook::PostRun, Some(status), mode)?;
+            let path: Vec<String> = plan.path_to(step).iter().map(|s| s.to_string()).collect();
+            return Err(
+                CliError::DependencyFailed(step.to_string(), status, path.join(" -> ")).into(),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Run again the run file on a non-zero exit, up to `retries` times, waiting `retry_backoff`
+/// doubled at each retry. Return the exit status of the last attempt.
+fn run_with_retries(
+    prepared_run: &PreparedRun,
+    args: &[String],
+    log: Option<&PathBuf>,
+    mode: &RunMode,
+) -> Result<i32> {
+    let attempts = prepared_run.retries + 1;
+    let mut backoff = prepared_run.retry_backoff;
+    let mut attempt = 1;
+    loop {
+        let command = prepared_run.command(args)?;
+        let output = run_file::run(mode.clone(), command, log, prepared_run.timeout)
+            .context(format!("fail to run {:?}", prepared_run.script))?;
+        if output.timed_out {
+            bad_info(
+                with_prefix(
+                    mode,
+                    format!(
+                        "timeout after {}",
+                        humantime::format_duration(prepared_run.timeout.unwrap_or_default())
+                    ),
+                )
+                .as_str(),
+            );
+        }
+        if attempts == 1 {
+            return Ok(output.status);
+        }
+        if output.status == 0 {
+            good_info(
+                with_prefix(mode, format!("attempt {}/{} succeeded", attempt, attempts)).as_str(),
+            );
+            return Ok(output.status);
+        }
+        if attempt == attempts || output.interrupted {
+            bad_info(
+                with_prefix(
+                    mode,
+                    format!(
+                        "attempt {}/{} exit with status {}, give up",
+                        attempt, attempts, output.status
+                    ),
+                )
+                .as_str(),
+            );
+            return Ok(output.status);
+        }
+        bad_info(
+            with_prefix(
+                mode,
+                format!(
+                    "attempt {}/{} exit with status {}, retry in {}",
+                    attempt,
+                    attempts,
+                    output.status,
+                    humantime::format_duration(backoff)
+                ),
+            )
+            .as_str(),
+        );
+        sleep(backoff);
+        backoff *= 2;
+        attempt += 1;
+    }
+}
+
+fn run_watch(app: &ArgMatches, args: &[String]) -> Result<()> {
+    let mut settings = WatchSettings::default();
+    if let Some(stop_signal) = app.value_of("stop_signal") {
+        settings.stop_signal = parse_signal(stop_signal)?;
+    }
+    if let Some(grace_period) = app.value_of("grace_period") {
+        settings.grace_period = humantime::parse_duration(grace_period)
+            .context(format!("invalid grace period `{}`", grace_period))?;
+    }
+    if let Some(debounce) = app.value_of("debounce") {
+        settings.debounce = humantime::parse_duration(debounce)
+            .context(format!("invalid debounce `{}`", debounce))?;
+    }
+
+    // A protected env is confirmed once, not at each restart.
+    let mut confirm_protected = true;
+    let status = watch(&settings, || {
+        let (plan, mut prepared_runs) = prepare_run(app, true, confirm_protected)?;
+        // The dependencies run again at each restart, their files are watched too.
+        let mut files: Vec<PathBuf> = prepared_runs
+            .values()
+            .flat_map(|prepared_run| prepared_run.files.iter().cloned())
+            .collect();
+        files.sort();
+        files.dedup();
+        let mut prepared_run = prepared_runs.remove(&plan.target).unwrap();
+        prepared_run.parse_arguments(args)?;
+        confirm_protected = false;
+        run_before_target(&plan, &prepared_runs, &prepared_run, &RunMode::detect())?;
+        Ok(WatchRun {
+            command: prepared_run.command(args)?,
+            history: prepared_run.history_entry(args, history_options(app)),
+            vars: prepared_run
+                .env_vars
+                .iter()
+                .map(|env_var| (env_var.var().to_env_var(), env_var.env_value().to_string()))
+                .collect(),
+            files,
+            post_run: Box::new(move |status| {
+                let hook = prepared_run.run_hook(Hook::PostRun, Some(status), &RunMode::detect());
+                if let Err(err) = hook {
+                    bad_info(format!("{:#}", err).as_str());
+                }
+            }),
+        })
+    })?;
+
+    exit_status(status)
+}
+
+/// Print what would be run, without running it.
+fn run_dry(app: &ArgMatches, args: Vec<String>) -> Result<()> {
+    let (plan, mut prepared_runs) = prepare_run(app, false, false)?;
+    let mut prepared_run = prepared_runs.remove(&plan.target).unwrap();
+    prepared_run.parse_arguments(&args)?;
+
+    let script = match &prepared_run.script {
+        Script::File(file) => file.canonicalize()?.to_string_lossy().into_owned(),
+        Script::Inline(line) => line.clone(),
+    };
+    let dry_run = DryRun {
+        command: prepared_run.command_name.clone(),
+        interpreter: prepared_run.interpreter.clone(),
+        script,
+        working_directory: prepared_run.working_directory()?,
+        args: prepared_run.args(&args),
+        vars: prepared_run.vars(true).into_iter().collect(),
+        depends_on: plan
+            .steps
+            .iter()
+            .filter(|step| *step != &plan.target)
+            .map(|step| step.to_string())
+            .collect(),
+    };
+
+    if app.is_present("json") {
+        println!("{}", serde_json::to_string_pretty(&dry_run)?);
+        return Ok(());
+    }
+
+    let mut lines = vec![];
+    if let Some(command) = &dry_run.command {
+        lines.push(("command".to_string(), command.clone()));
+    }
+    if let Some(interpreter) = &dry_run.interpreter {
+        lines.push(("interpreter".to_string(), interpreter.clone()));
+    }
+    lines.extend(vec![
+        ("script".to_string(), dry_run.script),
+        (
+            "working directory".to_string(),
+            dry_run.working_directory.to_string_lossy().into_owned(),
+        ),
+        ("args".to_string(), dry_run.args.join(" ")),
+    ]);
+    if !dry_run.depends_on.is_empty() {
+        lines.push(("depends on".to_string(), dry_run.depends_on.join(", ")));
+    }
+    lines.extend(dry_run.vars);
+    let width = lines.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    for (name, value) in lines.iter() {
+        let name = format!("{:width$}", name, width = width);
+        message(format!("{} {}", name.bold(), value).as_str());
+    }
+    Ok(())
+}
+
+/// Script of the setup, or of its command `command_name`.
+pub fn setup_script(setup: &Setup, command_name: Option<&str>) -> Result<Script> {
+    let command_name = match command_name {
+        Some(command_name) => command_name,
+        None => return Ok(Script::File(setup.local_cfg_run_file()?)),
+    };
+    let command = setup.local_cfg_command(command_name)?;
+    match (command.file(), command.command()) {
+        (Some(file), None) => Ok(Script::File(setup.local_cfg_dir()?.join(file))),
+        (None, Some(line)) => Ok(Script::Inline(line.clone())),
+        _ => Err(CfgError::CommandWithoutScript(command_name.to_string(), setup.name()?).into()),
+    }
+}
+
+/// The `cwd` of the setup, `script:` is relative to the directory of the script, or to the
+/// project directory for an inline command.
+pub fn setup_cwd(setup: &Setup, script: &Script) -> Result<Option<PathBuf>> {
+    let project_dir = setup.local_cfg_dir()?;
+    let script_dir = match script {
+        Script::File(file) => file.parent().unwrap_or(&project_dir),
+        Script::Inline(_) => &project_dir,
+    };
+    let local_setup = setup.local_setup().unwrap();
+    let cwd = local_setup
+        .borrow()
+        .cwd()
+        .map(|cwd| cwd.resolve(&project_dir, script_dir));
+    Ok(cwd)
+}
+
+/// Load the cfg, plan the run with its dependencies and resolve the vars of the env for each step,
+/// with `check_run` check that they are allowed and that the `requires` of their setups are met.
+/// When the first arg of the run is the name of one of the setup `commands`, it is the target
+/// instead of the run file.
+pub fn prepare_run(
+    app: &ArgMatches,
+    check_run: bool,
+    confirm_protected: bool,
+) -> Result<(Plan, BTreeMap<StepId, PreparedRun>)> {
+    let mut cfg = get_cfg()?;
+    cfg.sync_local_to_global()?;
+    let cfg = cfg;
+
+    let settings = get_settings(app, &cfg);
 
     let setup_name = settings.setup()?;
     let env = settings.env()?;
     let setup = cfg.current_setup(setup_name)?;
+    let target = StepId::new(setup_name.clone(), args_command_name(&setup, app));
+    let plan = Plan::new(target, |step| step_depends_on(&cfg, step))?;
+    let prepared_runs = prepare_plan(&cfg, &plan, env, check_run, confirm_protected)?;
+    if check_run {
+        check_run_hooks_allowed(&setup)?;
+    }
+    Ok((plan, prepared_runs))
+}
 
-    let script_file = setup.local_cfg_run_file()?;
-    let env = setup.env(&env)?;
+/// Print the `arguments` declared by the setup, shown after the help of `run`.
+pub fn run_help(app: &ArgMatches) -> Result<()> {
+    let mut cfg = get_cfg()?;
+    cfg.sync_local_to_global()?;
+    let cfg = cfg;
 
+    let settings = get_settings(app, &cfg);
+    let setup_name = settings.setup()?;
+    let setup = cfg.current_setup(setup_name)?;
+    let local_setup = setup.local_setup().unwrap();
+    let arguments = local_setup.borrow().arguments();
+    if arguments.is_empty() {
+        return Ok(());
+    }
+    println!();
+    print_arguments_help(setup_name, &arguments)
+}
+
+/// Name of the setup command given as first arg of the run, if any.
+fn args_command_name(setup: &Setup, app: &ArgMatches) -> Option<String> {
+    let local_setup = setup.local_setup().unwrap();
+    let local_setup = local_setup.borrow();
+    app.values_of_lossy("args")
+        .and_then(|args| args.first().cloned())
+        .filter(|name| local_setup.command(name).is_some())
+}
+
+/// Same as `prepare_run` for the run file of `setup`, or its command `command_name`.
+pub fn prepare_setup_run(
+    cfg: &Cfg,
+    setup: &Setup,
+    env: &String,
+    command_name: Option<String>,
+    check_run: bool,
+    confirm_protected: bool,
+) -> Result<PreparedRun> {
+    let setup = &setup.for_env(env)?;
+    let setup_name = setup.name()?;
+    let local_setup = setup.local_setup().unwrap();
+    let local_setup = local_setup.borrow();
+    let (default_args, interpreter, arguments) = match &command_name {
+        Some(command_name) => (
+            setup.local_cfg_command(command_name)?.args().clone(),
+            None,
+            vec![],
+        ),
+        None => (
+            local_setup.args(),
+            local_setup.interpreter().cloned(),
+            local_setup.arguments(),
+        ),
+    };
+    let project_dir = setup.local_cfg_dir()?;
+    drop(local_setup);
+
+    let script = setup_script(setup, command_name.as_deref())?;
+    let cwd = setup_cwd(setup, &script)?;
+    if let Some(cwd) = &cwd {
+        if !cwd.is_dir() {
+            bail!(
+                "working directory {:?} of setup `{}` not found",
+                cwd,
+                setup_name
+            );
+        }
+    }
+    if check_run {
+        check_allowed(cfg, setup, command_name.as_deref())?;
+    }
+    let env = setup.env(env)?;
+    let env_vars = setup_env_vars(setup, &env)?;
+    if check_run {
+        let checks = setup_requirements(setup, &env, &env_vars)?;
+        ensure_requirements(&setup_name, &checks)?;
+    }
+    if confirm_protected && setup.is_protected(&env) {
+        confirm_protected_env(&env.name()?)?;
+    }
+
+    let secret_vars = secret_vars(setup, &env, &env_vars);
+
+    let mut files: Vec<PathBuf> = env.file().into_iter().cloned().collect();
+    files.push(setup.local_cfg_file()?.clone());
+    if let Script::File(file) = &script {
+        files.push(file.clone());
+    }
+    let local_setup = setup.local_setup().unwrap();
+    let local_setup = local_setup.borrow();
+    let timeout = local_setup.timeout()?;
+    let retries = local_setup.retries();
+    let hooks = local_setup.hooks();
+    let retry_backoff = local_setup
+        .retry_backoff()?
+        .unwrap_or(DEFAULT_RETRY_BACKOFF);
+    drop(local_setup);
+
+    Ok(PreparedRun {
+        project_dir,
+        setup_name,
+        env_name: env.name()?,
+        command_name,
+        script,
+        interpreter,
+        cwd,
+        default_args,
+        arguments,
+        env_vars,
+        secret_vars,
+        files,
+        timeout,
+        retries,
+        retry_backoff,
+        hooks,
+    })
+}
+
+/// Check the `requires` of the setup with the vars of `env`.
+pub fn setup_requirements(
+    setup: &Setup,
+    env: &Env,
+    env_vars: &[EnvVar],
+) -> Result<Vec<RequirementCheck>> {
+    let local_setup = setup.local_setup().unwrap();
+    let requires = local_setup.borrow().requires();
+    let vars: Vec<(String, String)> = env_vars
+        .iter()
+        .map(|env_var| (env_var.var().to_env_var(), env_var.env_value().to_string()))
+        .collect();
+    check_requirements(&requires, &vars, &env.name()?, &setup.local_cfg_dir()?)
+}
+
+/// Names of the env vars that are secret, all the vars read from `env` when it is private.
+pub fn secret_vars(setup: &Setup, env: &Env, env_vars: &[EnvVar]) -> Vec<String> {
+    let is_private = setup.is_private(env);
+    let local_setup = setup.local_setup().unwrap();
+    let local_setup = local_setup.borrow();
+    let is_secret = |var: &Var| {
+        local_setup.is_secret(var.name()) || (is_private && env.get(var.name()).is_ok())
+    };
+    env_vars
+        .iter()
+        .filter(|env_var| {
+            let is_secret = match env_var.env_value() {
+                EnvValue::Var(var) => is_secret(var),
+                EnvValue::ArrayVar((_, vars)) => vars.iter().any(is_secret),
+            };
+            is_secret || is_secret_value(env_var.env_value().to_string().as_str())
+        })
+        .map(|env_var| env_var.var().to_env_var())
+        .collect()
+}
+
+/// Vars of `env` mapped by the setup, plus the `SHORT_ENV` and `SHORT_SETUP` vars.
+pub fn setup_env_vars(setup: &Setup, env: &Env) -> Result<Vec<EnvVar>> {
+    let setup = &setup.for_env(&env.name()?)?;
     let local_setup = setup.local_setup().unwrap();
     let local_setup = local_setup.borrow();
     let array_vars = local_setup.array_vars().unwrap_or_default();
     let vars = local_setup.vars();
     drop(local_setup);
 
-    let mut env_vars = generate_env_vars(&env, array_vars.borrow(), vars)?;
+    let mut env_vars = generate_env_vars(env, array_vars.borrow(), vars)?;
 
-    env_vars.push(
-        EnvVar::from_env(&env).context(format!("fail to generate var from env `{:?}`", env))?,
-    );
+    env_vars
+        .push(EnvVar::from_env(env).context(format!("fail to generate var from env `{:?}`", env))?);
 
     env_vars.push(
-        EnvVar::from_setup(&setup)
+        EnvVar::from_setup(setup)
             .context(format!("fail to generate var from setup `{:?}`", setup))?,
     );
 
-    run_as_stream(&script_file, &env_vars, &args)
-        .context(format!("fail to run {:?} with env {:?}", script_file, env))?;
+    Ok(env_vars)
+}
+
+/// The `pre_run` and `post_run` hooks of the setup, if any, must have been allowed.
+fn check_run_hooks_allowed(setup: &Setup) -> Result<()> {
+    let hooks = setup.local_setup().unwrap().borrow().hooks();
+    if hooks.get(Hook::PreRun).is_some() || hooks.get(Hook::PostRun).is_some() {
+        check_hooks_allowed(setup)?;
+    }
+    Ok(())
+}
+
+/// Run the `hook` of the setup with the vars of `env`, once the hooks are allowed.
+pub fn setup_hook(setup: &Setup, env: &Env, hook: Hook) -> Result<()> {
+    let local_setup = setup.local_setup().unwrap();
+    let hooks = local_setup.borrow().hooks();
+    if hooks.get(hook).is_none() {
+        return Ok(());
+    }
+    check_hooks_allowed(setup)?;
+    let env_vars = setup_env_vars(setup, env)?;
+    run_hook(
+        &setup.name()?,
+        &hooks,
+        hook,
+        &setup.local_cfg_dir()?,
+        &env_vars,
+        None,
+        &RunMode::detect(),
+    )
+}
 
+pub fn exit_status(status: i32) -> Result<()> {
+    if status != 0 {
+        return Err(CliError::RunExitStatus(status).into());
+    }
     Ok(())
 }
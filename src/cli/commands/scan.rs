@@ -0,0 +1,126 @@
+use colored::*;
+use std::collections::BTreeMap;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::ArgMatches;
+use git2::Repository;
+
+use crate::cli::cfg::get_cfg;
+use crate::cli::error::CliError;
+use crate::cli::terminal::message::{bad_info, info, message, success};
+use crate::env_file::ALLOW_ANNOTATION;
+use crate::run_file::set_exec_permision;
+use crate::utils::write_all::write_all_dir;
+use crate::BIN_NAME;
+
+const PRE_COMMIT_HOOK: &str = "pre-commit";
+
+fn pre_commit_hook_content() -> String {
+    format!(
+        r#"#!/bin/sh
+# Installed by `{bin} scan --install-hook`, abort the commit if a public env contains a secret.
+{bin} scan
+"#,
+        bin = BIN_NAME
+    )
+}
+
+pub fn scan(app: &ArgMatches) -> Result<()> {
+    let mut cfg = get_cfg()?;
+    cfg.sync_local_to_global()?;
+    let cfg = cfg;
+
+    let project = cfg.current_project()?;
+    let project_dir = project.borrow().dir()?.to_path_buf();
+
+    if app.is_present("install_hook") {
+        return install_hook(&project_dir);
+    }
+
+    // Several setups can share the same public env directory.
+    let mut envs = BTreeMap::new();
+    for setup in cfg.current_setups()? {
+        for env in setup.envs_public() {
+            let env = env?;
+            if let Some(file) = env.file() {
+                envs.insert(file.clone(), env);
+            }
+        }
+    }
+
+    let mut count = 0;
+    for (file, env) in envs.iter() {
+        let findings = env.scan();
+        if findings.is_empty() {
+            continue;
+        }
+        let file = file.strip_prefix(&project_dir).unwrap_or(file);
+        info(file.to_string_lossy().bold().to_string().as_str());
+        for finding in findings.iter() {
+            message(
+                format!(
+                    "  {}:{} `{}` {}",
+                    file.to_string_lossy(),
+                    finding.line,
+                    finding.var.bold(),
+                    finding.kind.to_string().red()
+                )
+                .as_str(),
+            );
+        }
+        count += findings.len();
+    }
+
+    if count > 0 {
+        bad_info(
+            format!(
+                "add a `# {}` comment above a var to ignore it",
+                ALLOW_ANNOTATION
+            )
+            .as_str(),
+        );
+        return Err(CliError::SecretsFound(count).into());
+    }
+
+    success(format!("no secret found in {} public env(s)", envs.len()).as_str());
+    Ok(())
+}
+
+fn install_hook(project_dir: &Path) -> Result<()> {
+    let repository = Repository::discover(project_dir)
+        .context(format!("fail to find git repository of {:?}", project_dir))?;
+    let hook_file = hooks_dir(&repository)?.join(PRE_COMMIT_HOOK);
+
+    if let Ok(content) = read_to_string(&hook_file) {
+        if content == pre_commit_hook_content() {
+            success("pre-commit hook already installed");
+            return Ok(());
+        }
+        return Err(CliError::GitHookAlreadyExists(hook_file).into());
+    }
+
+    write_all_dir(&hook_file, pre_commit_hook_content())?;
+    set_exec_permision(&hook_file)?;
+
+    success(format!("pre-commit hook installed {:?}", hook_file).as_str());
+    Ok(())
+}
+
+/// Hooks directory of the repository, `core.hooksPath` when it is set (e.g. by husky or
+/// lefthook). A relative path is relative to the working tree, where the hooks run.
+fn hooks_dir(repository: &Repository) -> Result<PathBuf> {
+    let config = repository
+        .config()
+        .context("fail to read git config of the repository")?;
+    let hooks_dir = match config.get_path("core.hooksPath") {
+        Ok(hooks_path) if hooks_path.is_absolute() => hooks_path,
+        Ok(hooks_path) => repository
+            .workdir()
+            .unwrap_or_else(|| repository.path())
+            .join(hooks_path),
+        Err(_) => repository.path().join("hooks"),
+    };
+    Ok(hooks_dir)
+}
@@ -0,0 +1,52 @@
+use std::env;
+
+use anyhow::{Context, Result};
+use clap::ArgMatches;
+use tempdir::TempDir;
+
+use crate::cli::cfg::get_cfg;
+use crate::cli::error::CliError;
+use crate::cli::protected::confirm_protected_env;
+use crate::cli::settings::get_settings;
+use crate::cli::shell::{Shell, SHELL_VAR};
+use crate::cli::terminal::message::info;
+use crate::run_file::{program_command, run_interactive};
+
+use super::run::{exit_status, setup_env_vars};
+
+pub fn shell(app: &ArgMatches) -> Result<()> {
+    if let Ok(current) = env::var(SHELL_VAR) {
+        return Err(CliError::NestedShell(current).into());
+    }
+
+    let mut cfg = get_cfg()?;
+    cfg.sync_local_to_global()?;
+    let cfg = cfg;
+
+    let settings = get_settings(app, &cfg);
+
+    let setup_name = settings.setup()?;
+    let env = settings.env()?;
+    let setup = cfg.current_setup(setup_name)?;
+
+    let env = setup.env(env)?;
+    if setup.is_protected(&env) {
+        confirm_protected_env(&env.name()?)?;
+    }
+
+    let env_vars = setup_env_vars(&setup, &env)?;
+    let name = format!("{}:{}", setup_name, env.name()?);
+
+    let (shell_file, shell) = Shell::current();
+    let rc_dir = TempDir::new("shell")?;
+    let mut command = program_command(&shell_file.to_string_lossy(), &env_vars, &[]);
+    command.env(SHELL_VAR, &name);
+    shell.set_prompt_prefix(&mut command, format!("[{}]", name).as_str(), rc_dir.path())?;
+
+    info(format!("enter shell `{}`, exit to leave", name).as_str());
+    let output =
+        run_interactive(command, None).context(format!("fail to run shell {:?}", shell_file))?;
+    info(format!("leave shell `{}`", name).as_str());
+
+    exit_status(output.status)
+}
@@ -2,17 +2,29 @@ use anyhow::Result;
 use clap::ArgMatches;
 use colored::*;
 
+use crate::cfg::Cfg;
 use crate::cli::cfg::get_cfg;
 use crate::cli::settings::Settings;
 use crate::cli::terminal::message::{bad_info, good_info};
 
 pub const DEFAULT_SHOW_FORMAT: &'static str = "[{setup}:{env}]";
+pub const PROTECTED_MARKER: &str = "🔒";
 
 fn cfg() -> Result<Settings> {
+    Ok(cfg_and_settings()?.1)
+}
+
+fn cfg_and_settings() -> Result<(Cfg, Settings)> {
     let mut cfg = get_cfg()?;
     cfg.sync_local_to_global()?;
     let settings: Settings = (&cfg).into();
-    Ok(settings)
+    Ok((cfg, settings))
+}
+
+fn is_protected(cfg: &Cfg, settings: &Settings) -> Result<bool> {
+    let setup = cfg.current_setup(settings.setup()?)?;
+    let env = setup.env(settings.env()?)?;
+    Ok(setup.is_protected(&env))
 }
 
 pub fn show(args: &ArgMatches) -> Result<()> {
@@ -29,7 +41,7 @@ pub fn show(args: &ArgMatches) -> Result<()> {
             }
         }
     } else if args.is_present("format") {
-        if let Ok(settings) = cfg() {
+        if let Ok((cfg, settings)) = cfg_and_settings() {
             let format = args
                 .value_of_lossy("format")
                 .map(|c| c.into_owned())
@@ -37,6 +49,16 @@ pub fn show(args: &ArgMatches) -> Result<()> {
 
             let format = format.replace("{setup}", settings.setup().unwrap_or(&"".into()));
             let format = format.replace("{env}", settings.env().unwrap_or(&"".into()));
+            let format = if format.contains("{protected}") {
+                let marker = if is_protected(&cfg, &settings).unwrap_or(false) {
+                    PROTECTED_MARKER
+                } else {
+                    ""
+                };
+                format.replace("{protected}", marker)
+            } else {
+                format
+            };
 
             print!("{}", format);
         }
@@ -0,0 +1,151 @@
+use colored::*;
+use std::borrow::Cow;
+use std::rc::Rc;
+
+use anyhow::{Context, Result};
+use clap::ArgMatches;
+
+use crate::cli::cfg::get_cfg;
+use crate::cli::error::CliError;
+use crate::cli::protected::confirm_protected_env;
+use crate::cli::settings::get_settings;
+use crate::cli::terminal::confirm::{confirm, EnumConfirm};
+use crate::cli::terminal::message::{message, success};
+use crate::env_file::EnvDiffController;
+use crate::ssm::{env_from_parameters, parameters_from_env, SsmClient, SsmError};
+
+use super::sync::SyncConfirmEnum;
+
+pub fn ssm(app: &ArgMatches) -> Result<()> {
+    if let Some(args) = app.subcommand_matches("push") {
+        ssm_push(args)
+    } else if let Some(args) = app.subcommand_matches("pull") {
+        ssm_pull(args)
+    } else {
+        unreachable!()
+    }
+}
+
+fn ssm_push(app: &ArgMatches) -> Result<()> {
+    let mut cfg = get_cfg()?;
+    cfg.sync_local_to_global()?;
+    let cfg = cfg;
+
+    let settings = get_settings(app, &cfg);
+    let setup_name = settings.setup()?;
+    let env_name = settings.env()?;
+    let setup = cfg.current_setup(setup_name)?;
+    let env = setup.env(env_name)?;
+
+    let local_setup = setup.local_setup().unwrap();
+    let local_setup = local_setup.borrow();
+    let path = local_setup.ssm().path(setup_name, env_name);
+    let parameters = parameters_from_env(&path, &env, |name| local_setup.is_secret(name));
+    drop(local_setup);
+
+    let client = SsmClient::from_env()?;
+    for parameter in parameters.iter() {
+        client
+            .put_parameter(parameter)
+            .context(format!("fail to push `{}`", parameter.name))?;
+        let kind = if parameter.secure { " (secure)" } else { "" };
+        message(format!("{} {}{}", "+".green(), parameter.name, kind).as_str());
+    }
+
+    success(format!("`{}` pushed to `{}`", env_name.bold(), path.bold()).as_str());
+
+    Ok(())
+}
+
+fn ssm_pull(app: &ArgMatches) -> Result<()> {
+    let mut cfg = get_cfg()?;
+    cfg.sync_local_to_global()?;
+    let cfg = cfg;
+
+    let settings = get_settings(app, &cfg);
+    let setup_name = settings.setup()?;
+    let env_name = settings.env()?;
+    let setup = cfg.current_setup(setup_name)?;
+    let mut env = setup.env(env_name)?;
+    let yes = app.is_present("yes");
+
+    let local_setup = setup.local_setup().unwrap();
+    let path = local_setup.borrow().ssm().path(setup_name, env_name);
+
+    let client = SsmClient::from_env()?;
+    let parameters = client
+        .parameters_by_path(&path)
+        .context(format!("fail to pull `{}`", path))?;
+    // An empty or mistyped path would remove all the vars of the env.
+    if parameters.is_empty() {
+        bail!(SsmError::NoParameters(path));
+    }
+    let source_env = env_from_parameters(&path, &env, &parameters);
+    if setup.is_protected(&env) && source_env.to_string() != env.to_string() {
+        confirm_protected_env(env_name)?;
+    }
+
+    let env_name = Rc::new(env_name.clone());
+    let env_name_delete_var = Rc::clone(&env_name);
+    let env_name_change_var = Rc::clone(&env_name);
+
+    let controller = EnvDiffController::new(
+        move |var| {
+            message(format!("{} {}={}", "+".green(), var.name(), var.value()).as_str());
+            Ok(Cow::Borrowed(var))
+        },
+        move |var| {
+            if yes {
+                message(format!("{} {}", "-".red(), var.name()).as_str());
+                return Ok(true);
+            }
+            let r = confirm(
+                std::io::stdout(),
+                format!(
+                    "Remove `{}`:`{}`=`{}`",
+                    env_name_delete_var.bold(),
+                    var.name().bold(),
+                    var.value().bold()
+                )
+                .as_str(),
+                SyncConfirmEnum::to_vec(),
+            )?;
+            Ok(r == SyncConfirmEnum::y)
+        },
+    )
+    .with_change_var(move |target_var, source_var| {
+        if yes {
+            message(
+                format!(
+                    "{} {}={}",
+                    "~".yellow(),
+                    source_var.name(),
+                    source_var.value()
+                )
+                .as_str(),
+            );
+            return Ok(true);
+        }
+        let r = confirm(
+            std::io::stdout(),
+            format!(
+                "Change `{}`:`{}`=`{}` to `{}`",
+                env_name_change_var.bold(),
+                target_var.name().bold(),
+                target_var.value().bold(),
+                source_var.value().bold()
+            )
+            .as_str(),
+            SyncConfirmEnum::to_vec(),
+        )?;
+        Ok(r == SyncConfirmEnum::y)
+    });
+
+    env.update_by_diff(&source_env, &controller)
+        .context((CliError::EnvFileMustBeSync).to_string())?;
+    env.save()?;
+
+    success(format!("`{}` pulled from `{}`", env_name.bold(), path.bold()).as_str());
+
+    Ok(())
+}
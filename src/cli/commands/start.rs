@@ -0,0 +1,63 @@
+use anyhow::Result;
+use clap::ArgMatches;
+
+use crate::cfg::Hook;
+use crate::cli::error::CliError;
+use crate::cli::instances::{instances, log_file, save, Instance};
+use crate::cli::terminal::message::{good_info, info};
+use crate::run_file::{spawn_detached, RunMode};
+
+use super::run::{prepare_run, run_before_target};
+
+/// Start the run file in background, it is listed by `sht ps` until `sht stop`. Its `pre_run` hook
+/// and its dependencies are run before, in the foreground.
+pub fn start(app: &ArgMatches) -> Result<()> {
+    let args = app.values_of_lossy("args").unwrap_or(vec![]);
+    let (plan, mut prepared_runs) = prepare_run(app, true, true)?;
+    let mut prepared_run = prepared_runs.remove(&plan.target).unwrap();
+    prepared_run.parse_arguments(&args)?;
+    // Short is gone when a detached run exits.
+    if prepared_run.hooks.get(Hook::PostRun).is_some() {
+        return Err(CliError::PostRunNotDetached(prepared_run.setup_name).into());
+    }
+
+    let log = log_file(
+        &prepared_run.project_dir,
+        &prepared_run.setup_name,
+        prepared_run.command_name.as_deref(),
+        &prepared_run.env_name,
+    )?;
+    let mut instance = Instance::new(
+        0,
+        prepared_run.project_dir.clone(),
+        prepared_run.setup_name.clone(),
+        prepared_run.env_name.clone(),
+        prepared_run.command_name.clone(),
+        args.clone(),
+        log.clone(),
+    );
+    if let Some(running) = instances()?
+        .into_iter()
+        .find(|running| running.is_same_run(&instance) && running.is_running())
+    {
+        return Err(CliError::InstanceAlreadyRunning(running.name(), running.pid).into());
+    }
+
+    run_before_target(&plan, &prepared_runs, &prepared_run, &RunMode::detect())?;
+    let (pid, start_time) = spawn_detached(prepared_run.command(&args)?, &log)?;
+    instance.pid = pid;
+    instance.start_time = start_time;
+    save(&instance)?;
+
+    good_info(
+        format!(
+            "`{}` started on `{}` with pid {}",
+            instance.name(),
+            instance.env,
+            instance.pid
+        )
+        .as_str(),
+    );
+    info(format!("log {}", log.to_string_lossy()).as_str());
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use clap::ArgMatches;
+
+use crate::cli::instances::{remove, select_instance};
+use crate::cli::terminal::message::{bad_info, good_info, info};
+use crate::run_file::{kill_process_group, parse_signal};
+
+const DEFAULT_GRACE_PERIOD: Duration = Duration::from_secs(5);
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Send the stop signal to the process group of an instance, kill it if it is still alive
+/// after the grace period, then remove the instance. An instance whose pid is not its process
+/// anymore is only removed.
+pub fn stop(app: &ArgMatches) -> Result<()> {
+    let instance = select_instance(app)?;
+    let name = format!("{}:{}", instance.name(), instance.env);
+
+    if !instance.is_running() {
+        remove(&instance)?;
+        info(format!("`{}` was not running, removed", name).as_str());
+        return Ok(());
+    }
+
+    let signal = match app.value_of("stop_signal") {
+        Some(signal) => parse_signal(signal)?,
+        None => libc::SIGTERM,
+    };
+    let grace_period = match app.value_of("grace_period") {
+        Some(grace_period) => humantime::parse_duration(grace_period)
+            .context(format!("invalid grace period `{}`", grace_period))?,
+        None => DEFAULT_GRACE_PERIOD,
+    };
+
+    kill_process_group(instance.pid, signal);
+    let start = Instant::now();
+    while instance.is_running() && start.elapsed() < grace_period {
+        sleep(POLL_INTERVAL);
+    }
+    if instance.is_running() {
+        bad_info(
+            format!(
+                "still running after {}, kill it",
+                humantime::format_duration(grace_period)
+            )
+            .as_str(),
+        );
+        kill_process_group(instance.pid, libc::SIGKILL);
+    }
+
+    remove(&instance)?;
+    good_info(format!("`{}` stopped, pid {}", name, instance.pid).as_str());
+    Ok(())
+}
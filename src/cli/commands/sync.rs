@@ -6,13 +6,17 @@ use std::rc::Rc;
 use anyhow::{Context, Result};
 use clap::ArgMatches;
 
+use crate::cfg::{Hook, Setup};
 use crate::cli::cfg::get_cfg;
 use crate::cli::error::CliError;
+use crate::cli::protected::confirm_protected_env;
 use crate::cli::settings::get_settings;
 use crate::cli::terminal::confirm::{confirm, EnumConfirm};
 use crate::cli::terminal::message::success;
 use crate::env_file::{Env, EnvDiffController};
 
+use super::run::setup_hook;
+
 #[derive(Debug)]
 pub struct SyncSettings {
     pub empty: bool,
@@ -20,6 +24,7 @@ pub struct SyncSettings {
     pub delete: bool,
     pub no_delete: bool,
     pub file: Option<String>,
+    pub protected_envs: Vec<String>,
 }
 
 impl SyncSettings {
@@ -30,8 +35,18 @@ impl SyncSettings {
             delete: args.is_present("delete"),
             no_delete: args.is_present("no_delete"),
             file: args.value_of("file").map(|f| f.to_string()),
+            protected_envs: vec![],
         }
     }
+
+    /// Changes of protected envs must be confirmed before being saved.
+    pub fn protect_envs(&mut self, setup: &Setup, envs: &[Env]) {
+        self.protected_envs = envs
+            .iter()
+            .filter(|env| setup.is_protected(env))
+            .filter_map(|env| env.name().ok())
+            .collect();
+    }
 }
 
 enum_confirm!(SyncConfirmEnum, y, n);
@@ -42,16 +57,27 @@ pub fn env_sync(app: &ArgMatches) -> Result<()> {
     let cfg = cfg;
 
     let settings = get_settings(app, &cfg);
-    let sync_settings = SyncSettings::new(app);
+    let mut sync_settings = SyncSettings::new(app);
 
     let setup = cfg.current_setup(settings.setup()?)?;
     let envs = setup.envs();
     let envs: Vec<_> = envs.into_iter().filter_map(|r| r.ok()).collect();
+    sync_settings.protect_envs(&setup, &envs);
 
     let recent_env = Env::recent(&envs)?;
 
+    // The hooks get the vars of the current env, or of the most recent one.
+    let hook_env = settings
+        .env()
+        .ok()
+        .and_then(|env_name| setup.env(env_name).ok())
+        .unwrap_or_else(|| recent_env.clone());
+    setup_hook(&setup, &hook_env, Hook::PreSync)?;
+
     sync_workflow(recent_env, envs, sync_settings)?;
 
+    setup_hook(&setup, &setup.env(&hook_env.name()?)?, Hook::PostSync)?;
+
     success("files synchronized");
 
     Ok(())
@@ -73,10 +99,14 @@ pub fn sync_workflow(
 
     for env_cell in envs.iter() {
         let mut env = env_cell.borrow_mut();
-        if env.file() == source_env.file() {
+        if env.location() == source_env.location() {
             continue;
         }
+        let protected_header = env.is_protected();
         let env_name = Rc::new(env.name()?);
+        if sync_settings.protected_envs.contains(&env_name) && env.has_var_diff(&source_env) {
+            confirm_protected_env(&env_name)?;
+        }
         let env_name_update_var = Rc::clone(&env_name);
         let env_name_delete_var = Rc::clone(&env_name);
 
@@ -167,6 +197,10 @@ pub fn sync_workflow(
         );
         env.update_by_diff(&source_env, &controller)
             .context((CliError::EnvFileMustBeSync).to_string())?;
+        // The protected annotation of the target must survive the sync.
+        if protected_header {
+            env.set_protected();
+        }
         env.save().unwrap();
     }
 
@@ -0,0 +1,146 @@
+use std::fs::read_to_string;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::ArgMatches;
+use colored::*;
+
+use crate::cfg::{Cfg, Process, ProcessGroup, SetupCommand};
+use crate::cli::cfg::get_cfg;
+use crate::cli::error::CliError;
+use crate::cli::processes::{parse_procfile, up as up_processes, UpProcess, UpSettings};
+use crate::cli::settings::get_settings;
+use crate::cli::steps::StepId;
+use crate::cli::terminal::message::{good_info, info};
+
+use super::run::{exit_status, prepare_setup_run, ENV_COLORS};
+
+const PROCFILE_GROUP: &str = "procfile";
+
+pub fn up(app: &ArgMatches) -> Result<()> {
+    if let Some(procfile) = app.value_of("import") {
+        return import_procfile(app, PathBuf::from(procfile));
+    }
+
+    let mut cfg = get_cfg()?;
+    cfg.sync_local_to_global()?;
+    let cfg = cfg;
+
+    let settings = get_settings(app, &cfg);
+    let (group_name, group) = select_group(&cfg, app.value_of("group"))?;
+
+    let up_settings = UpSettings {
+        stop_on_exit: group.stop_on_exit() && !app.is_present("keep_running"),
+        ..UpSettings::default()
+    };
+
+    let width = group
+        .processes()
+        .keys()
+        .map(|name| name.len())
+        .max()
+        .unwrap_or(0);
+    // A protected env is confirmed once, even if several processes use it.
+    let mut confirmed_envs = vec![];
+    let mut processes = vec![];
+    for (i, (name, process)) in group.processes().iter().enumerate() {
+        let step = StepId::parse(process.run());
+        let env_name = match process.env() {
+            Some(env_name) => env_name.clone(),
+            None => settings.env()?.clone(),
+        };
+        let setup = cfg.current_setup(&step.setup)?;
+        let confirm_protected = !confirmed_envs.contains(&env_name);
+        let mut prepared_run = prepare_setup_run(
+            &cfg,
+            &setup,
+            &env_name,
+            step.command.clone(),
+            true,
+            confirm_protected,
+        )
+        .context(format!("fail to prepare process `{}`", name))?;
+        confirmed_envs.push(env_name);
+
+        let args: Vec<String> = step.command.iter().cloned().collect();
+        prepared_run
+            .parse_arguments(&args)
+            .context(format!("fail to prepare process `{}`", name))?;
+        let prefix = format!("{:width$} |", name, width = width);
+        processes.push(UpProcess {
+            prefix: prefix.color(ENV_COLORS[i % ENV_COLORS.len()]).to_string(),
+            command: prepared_run.command(&args)?,
+        });
+    }
+
+    info(format!("up `{}` : {} process(es)", group_name, processes.len()).as_str());
+    let status = up_processes(processes, &up_settings)?;
+    exit_status(status)
+}
+
+/// The group `name`, or the only group of short.yaml.
+fn select_group(cfg: &Cfg, name: Option<&str>) -> Result<(String, ProcessGroup)> {
+    let groups = cfg.process_groups();
+    if let Some(name) = name {
+        return match groups.get(name) {
+            Some(group) => Ok((name.to_string(), group.clone())),
+            None => Err(CliError::ProcessGroupNotFound(name.to_string()).into()),
+        };
+    }
+    let mut groups = groups.iter();
+    match (groups.next(), groups.next()) {
+        (Some((name, group)), None) => Ok((name.clone(), group.clone())),
+        (None, _) => bail!("no process group found, declare it in `groups` of short.yaml"),
+        _ => {
+            let names: Vec<&String> = cfg.process_groups().keys().collect();
+            bail!(
+                "several process groups found, choose one of {}",
+                names
+                    .iter()
+                    .map(|name| format!("`{}`", name))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            )
+        }
+    }
+}
+
+/// Add the processes of the Procfile as commands of the setup, and a group that runs them.
+fn import_procfile(app: &ArgMatches, procfile: PathBuf) -> Result<()> {
+    let content =
+        read_to_string(&procfile).context(format!("fail to read Procfile {:?}", procfile))?;
+    let procfile_processes = parse_procfile(&content)?;
+
+    let mut cfg = get_cfg()?;
+    cfg.sync_local_to_global()?;
+
+    let settings = get_settings(app, &cfg);
+    let setup_name = settings.setup()?.clone();
+    let setup = cfg.current_setup(&setup_name)?;
+    let group_name = app.value_of("group").unwrap_or(PROCFILE_GROUP).to_string();
+
+    let mut group = ProcessGroup::default();
+    let local_setup = setup.local_setup().unwrap();
+    for (name, command) in procfile_processes.iter() {
+        local_setup
+            .borrow_mut()
+            .set_command(name.clone(), SetupCommand::inline(command.clone()));
+        group.add_process(
+            name.clone(),
+            Process::new(StepId::new(setup_name.clone(), Some(name.clone())).to_string()),
+        );
+    }
+    cfg.set_process_group(group_name.clone(), group);
+    cfg.save()?;
+
+    good_info(
+        format!(
+            "{} process(es) imported in the group `{}` as commands of `{}`",
+            procfile_processes.len(),
+            group_name,
+            setup_name
+        )
+        .as_str(),
+    );
+    Ok(())
+}
@@ -1,11 +1,13 @@
 use anyhow::{Context, Result};
 use clap::ArgMatches;
 
-use crate::cfg::Cfg;
+use crate::cfg::{Cfg, Hook};
 use crate::cli::cfg::get_cfg;
-use crate::cli::settings::{Settings};
+use crate::cli::settings::Settings;
 use crate::cli::terminal::message::success;
 
+use super::run::setup_hook;
+
 pub fn r#use(app: &ArgMatches) -> Result<()> {
     let mut cfg = get_cfg()?;
     cfg.sync_local_to_global()?;
@@ -37,6 +39,10 @@ pub fn r#use(app: &ArgMatches) -> Result<()> {
         use_workflow(&cfg, &settings)?;
         cfg.save()?;
         success(format!("your current setup is `{}`", settings).as_str());
+        if let Ok(env_name) = settings.env() {
+            let setup = cfg.current_setup(settings.setup()?)?;
+            setup_hook(&setup, &setup.env(env_name)?, Hook::PostUse)?;
+        }
     }
 
     Ok(())
@@ -58,7 +64,7 @@ pub fn use_workflow(cfg: &Cfg, settings: &Settings) -> Result<()> {
     global_project.set_current_setup_name(setup_name.to_owned());
     if let Ok(env_name) = settings.env() {
         setup
-            .env_file(env_name)
+            .env_location(env_name)
             .context(format!("fail to found env {:?}", env_name))?;
         global_project.set_current_env_name(env_name.to_owned());
     }
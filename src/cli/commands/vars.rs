@@ -43,7 +43,8 @@ pub fn vars(app: &ArgMatches) -> Result<()> {
 
     let env_ref = envs.get(0).map(|env| env.clone()).unwrap();
 
-    // Retrieve vars / array_vars
+    // Retrieve vars / array_vars, with the overrides of the reference env
+    let setup = setup.for_env(&env_ref.name()?)?;
     let local_setup = setup.local_setup().unwrap();
     let local_setup = local_setup.borrow();
     let array_vars = local_setup.array_vars().unwrap_or_default();
@@ -2,7 +2,7 @@ use std::path::PathBuf;
 
 use thiserror::Error;
 
-use crate::env_file::Env;
+use crate::env_file::{Env, EnvLocation};
 
 type SetupName = String;
 
@@ -19,10 +19,62 @@ pub enum CliError {
     DeleteVarNowAllowed(String, String, String),
     #[error("env must be sync, please change it manually or run \"short env sync\"")]
     EnvFileMustBeSync,
-    #[error("env file `{0:?}` already exists")]
-    EnvFileAlreadyExists(PathBuf, Env),
+    #[error("env `{0}` already exists")]
+    EnvFileAlreadyExists(EnvLocation, Env),
     #[error("user stop sync")]
     UserStopSync,
+    #[error("{0} potential secret(s) found in public envs")]
+    SecretsFound(usize),
+    #[error("run file `{0:?}` of `{1}` is not allowed, review it and run \"sht allow\"")]
+    RunFileNotAllowed(PathBuf, SetupName),
+    #[error("command `{0}` of `{1}` is not allowed, review it and run \"sht allow {0}\"")]
+    CommandNotAllowed(String, SetupName),
+    #[error("override of `{0}` for env `{1}` is not allowed, review it and run \"sht allow {2}\"")]
+    EnvOverrideNotAllowed(SetupName, String, String),
+    #[error("hooks of `{0}` are not allowed, review them and run \"sht allow\"")]
+    HooksNotAllowed(SetupName),
+    #[error("protected env `{0}` not confirmed")]
+    ProtectedEnvNotConfirmed(String),
+    #[error("run file exit with status {0}")]
+    RunExitStatus(i32),
+    #[error("protected env `{0}` can't be exported, use \"sht shell\"")]
+    ProtectedEnvNotExported(String),
+    #[error("invalid arguments of `{0}`, see \"sht run -s {0} --help\" : {1}")]
+    InvalidArguments(String, String),
+    #[error("invalid requirement `{0}`, expected e.g. `node >=14`")]
+    InvalidRequirement(String),
+    #[error("{1} requirement(s) of `{0}` missing, see \"sht check-requirements\"")]
+    MissingRequirements(String, usize),
+    #[error("hook `{0}` of `{1}` failed with status {2}, aborted")]
+    HookFailed(String, SetupName, i32),
+    #[error("the `post_run` hook of `{0}` can't run after a start in background, use \"sht run\"")]
+    PostRunNotDetached(SetupName),
+    #[error("dependency cycle {0}")]
+    DependencyCycle(String),
+    #[error("dependency `{0}` of `{1}` not found")]
+    DependencyNotFound(String, String),
+    #[error("dependency `{0}` failed with status {1} : {2}")]
+    DependencyFailed(String, i32, String),
+    #[error("dependency `{0}` is not allowed, review it and run \"sht allow {1}\"")]
+    DependencyNotAllowed(String, String),
+    #[error("process group `{0}` not found")]
+    ProcessGroupNotFound(String),
+    #[error("invalid Procfile line {0} : `{1}`")]
+    InvalidProcfileLine(usize, String),
+    #[error("instance `{0}` not found, see \"sht ps\"")]
+    InstanceNotFound(String),
+    #[error("`{0}` is already running with pid {1}, stop it with \"sht stop {1}\"")]
+    InstanceAlreadyRunning(String, u32),
+    #[error("history entry `{0}` not found")]
+    HistoryEntryNotFound(usize),
+    #[error("history entry `{0}` has secret values masked in its args, it can't be rerun")]
+    HistoryEntryMasked(usize),
+    #[error("already in the shell `{0}`, exit it first")]
+    NestedShell(String),
+    #[error("no hook for the shell `{0}`, use \"--shell bash|zsh|fish\"")]
+    ShellHookNotSupported(String),
+    #[error("git hook `{0:?}` already exists")]
+    GitHookAlreadyExists(PathBuf),
     #[error("Unknown error")]
     UnknownError(#[from] anyhow::Error),
 }
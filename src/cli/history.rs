@@ -0,0 +1,152 @@
+use std::env;
+use std::fs::{create_dir_all, read_to_string, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use log::*;
+use serde::{Deserialize, Serialize};
+use strum_macros::AsRefStr;
+
+use crate::cfg::global_cfg_directory;
+use crate::cli::cfg::reach_directories;
+
+const HISTORY_FILE: &str = "history.jsonl";
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize, AsRefStr)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum HistoryCommand {
+    Run,
+    Exec,
+}
+
+/// A `run` or an `exec`, one json object by line in the history file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Line of the entry in the history file, start at 1. Not stored.
+    #[serde(default, skip_serializing_if = "is_unset_id")]
+    pub id: usize,
+    pub timestamp: String,
+    pub command: HistoryCommand,
+    pub project: PathBuf,
+    /// Directory where the command has been run.
+    pub directory: PathBuf,
+    pub setup: String,
+    pub env: String,
+    pub args: Vec<String>,
+    /// Indexes of the args where the value of a secret var has been masked, such an entry can't
+    /// be run again.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub masked: Vec<usize>,
+    /// Options of `run` given again by `history rerun`, e.g. `--timeout 10s`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<String>,
+    pub duration_ms: u64,
+    pub status: i32,
+    pub user: String,
+}
+
+impl HistoryEntry {
+    /// `masked_args` are the `args` with the values of the secret vars masked, they are the ones
+    /// recorded.
+    pub fn new(
+        command: HistoryCommand,
+        project: PathBuf,
+        setup: String,
+        env: String,
+        args: &[String],
+        masked_args: Vec<String>,
+    ) -> Self {
+        let masked = args
+            .iter()
+            .zip(masked_args.iter())
+            .enumerate()
+            .filter(|(_, (arg, masked_arg))| arg != masked_arg)
+            .map(|(index, _)| index)
+            .collect();
+        Self {
+            id: 0,
+            timestamp: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+            command,
+            project,
+            directory: env::current_dir().unwrap_or_default(),
+            setup,
+            env,
+            args: masked_args,
+            masked,
+            options: vec![],
+            duration_ms: 0,
+            status: 0,
+            user: current_user(),
+        }
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.duration_ms)
+    }
+}
+
+fn is_unset_id(id: &usize) -> bool {
+    *id == 0
+}
+
+fn current_user() -> String {
+    env::var("USER")
+        .or_else(|_| env::var("LOGNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+fn history_file() -> Result<PathBuf> {
+    let (_, global_dir) = reach_directories()?;
+    Ok(global_cfg_directory(&global_dir).join(HISTORY_FILE))
+}
+
+/// Append the entry to the history, a failure is only logged to not fail the run.
+pub fn record(entry: &HistoryEntry) {
+    if let Err(err) = append(entry) {
+        warn!("fail to record history : {:#}", err);
+    }
+}
+
+fn append(entry: &HistoryEntry) -> Result<()> {
+    let history_file = history_file()?;
+    if let Some(parent) = history_file.parent() {
+        create_dir_all(parent)?;
+    }
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&history_file)
+        .context(format!("fail to open history file {:?}", history_file))?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// All the entries, oldest first. Unreadable lines are skipped.
+pub fn entries() -> Result<Vec<HistoryEntry>> {
+    let history_file = history_file()?;
+    let content = match read_to_string(&history_file) {
+        Ok(content) => content,
+        Err(_) => return Ok(vec![]),
+    };
+    Ok(content
+        .lines()
+        .enumerate()
+        .filter_map(
+            |(index, line)| match serde_json::from_str::<HistoryEntry>(line) {
+                Ok(mut entry) => {
+                    entry.id = index + 1;
+                    Some(entry)
+                }
+                Err(err) => {
+                    warn!("skip history line {} : {}", index + 1, err);
+                    None
+                }
+            },
+        )
+        .collect())
+}
@@ -0,0 +1,61 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::cfg::{Hook, SetupHooks};
+use crate::cli::error::CliError;
+use crate::cli::terminal::message::bad_info;
+use crate::run_file;
+use crate::run_file::{inline_command, EnvVar, RunMode};
+
+/// Run the `hook` of the setup, if any, by `sh` in `dir` with `env_vars`. `exit_status` is the
+/// status of the run given to `post_run`. A failing pre hook is an error, a failing post hook is
+/// only reported.
+pub fn run_hook(
+    setup_name: &str,
+    hooks: &SetupHooks,
+    hook: Hook,
+    dir: &Path,
+    env_vars: &[EnvVar],
+    exit_status: Option<i32>,
+    mode: &RunMode,
+) -> Result<()> {
+    let line = match hooks.get(hook) {
+        Some(line) => line,
+        None => return Ok(()),
+    };
+    let mut command = inline_command(line, dir, env_vars, &[]);
+    if let Some(exit_status) = exit_status {
+        let status_var = EnvVar::from_exit_status(exit_status);
+        command.env(
+            status_var.var().to_env_var(),
+            status_var.env_value().to_string(),
+        );
+    }
+    let output = run_file::run(mode.clone(), command, None, None).context(format!(
+        "fail to run hook `{}` of `{}`",
+        hook.as_ref(),
+        setup_name
+    ))?;
+    if output.status == 0 {
+        return Ok(());
+    }
+    if hook.is_pre() {
+        return Err(CliError::HookFailed(
+            hook.as_ref().to_string(),
+            setup_name.to_string(),
+            output.status,
+        )
+        .into());
+    }
+    bad_info(
+        format!(
+            "hook `{}` of `{}` exit with status {}",
+            hook.as_ref(),
+            setup_name,
+            output.status
+        )
+        .as_str(),
+    );
+    Ok(())
+}
@@ -0,0 +1,189 @@
+use std::fs::{create_dir_all, read_dir, read_to_string, remove_file, write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use clap::ArgMatches;
+use log::*;
+use serde::{Deserialize, Serialize};
+
+use crate::cfg::global_cfg_directory;
+use crate::cli::cfg::{get_cfg, reach_directories};
+use crate::cli::error::CliError;
+use crate::cli::settings::get_settings;
+use crate::cli::steps::StepId;
+use crate::run_file::{is_alive, process_start_time};
+use crate::utils::hash::{hex, sha256};
+
+const INSTANCES_DIR: &str = "instances";
+const LOGS_DIR: &str = "logs";
+
+/// A run file started in background by `sht start`, stored as `<pid>.json` in the instances
+/// directory of the global cfg.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Instance {
+    pub pid: u32,
+    /// Start time of the process, see `process_start_time`. A process with the same pid but
+    /// another start time is not the instance. Without it, any process with the pid is.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<u64>,
+    pub started_at: String,
+    pub project: PathBuf,
+    pub setup: String,
+    pub env: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    pub args: Vec<String>,
+    pub log: PathBuf,
+}
+
+impl Instance {
+    pub fn new(
+        pid: u32,
+        project: PathBuf,
+        setup: String,
+        env: String,
+        command: Option<String>,
+        args: Vec<String>,
+        log: PathBuf,
+    ) -> Self {
+        Self {
+            pid,
+            start_time: None,
+            started_at: humantime::format_rfc3339_seconds(SystemTime::now()).to_string(),
+            project,
+            setup,
+            env,
+            command,
+            args,
+            log,
+        }
+    }
+
+    /// `<setup>` or `<setup>:<command>`.
+    pub fn name(&self) -> String {
+        StepId::new(self.setup.clone(), self.command.clone()).to_string()
+    }
+
+    /// The process of the instance is alive, not another one that has been given its pid.
+    pub fn is_running(&self) -> bool {
+        match self.start_time {
+            Some(start_time) => {
+                is_alive(self.pid) && process_start_time(self.pid) == Some(start_time)
+            }
+            None => is_alive(self.pid),
+        }
+    }
+
+    pub fn uptime(&self) -> Duration {
+        humantime::parse_rfc3339(&self.started_at)
+            .ok()
+            .and_then(|started_at| SystemTime::now().duration_since(started_at).ok())
+            .map(|uptime| Duration::from_secs(uptime.as_secs()))
+            .unwrap_or_default()
+    }
+
+    /// Same project, setup, command and env.
+    pub fn is_same_run(&self, other: &Instance) -> bool {
+        self.project == other.project
+            && self.setup == other.setup
+            && self.command == other.command
+            && self.env == other.env
+    }
+}
+
+fn global_dir(name: &str) -> Result<PathBuf> {
+    let (_, global_dir) = reach_directories()?;
+    Ok(global_cfg_directory(&global_dir).join(name))
+}
+
+fn instance_file(pid: u32) -> Result<PathBuf> {
+    Ok(global_dir(INSTANCES_DIR)?.join(format!("{}.json", pid)))
+}
+
+/// Log file of the setup run in `env`, kept across the starts.
+pub fn log_file(project: &Path, setup: &str, command: Option<&str>, env: &str) -> Result<PathBuf> {
+    let project_hash = hex(&sha256(project.to_string_lossy().as_bytes()));
+    let name = match command {
+        Some(command) => format!("{}.{}.{}.{}.log", setup, command, env, &project_hash[..8]),
+        None => format!("{}.{}.{}.log", setup, env, &project_hash[..8]),
+    };
+    Ok(global_dir(LOGS_DIR)?.join(name))
+}
+
+pub fn save(instance: &Instance) -> Result<()> {
+    let file = instance_file(instance.pid)?;
+    if let Some(parent) = file.parent() {
+        create_dir_all(parent)?;
+    }
+    write(&file, serde_json::to_string_pretty(instance)?)
+        .context(format!("fail to write instance file {:?}", file))?;
+    Ok(())
+}
+
+pub fn remove(instance: &Instance) -> Result<()> {
+    let file = instance_file(instance.pid)?;
+    remove_file(&file).context(format!("fail to remove instance file {:?}", file))?;
+    Ok(())
+}
+
+/// All the instances, oldest first. Unreadable files are skipped.
+pub fn instances() -> Result<Vec<Instance>> {
+    let dir = global_dir(INSTANCES_DIR)?;
+    let entries = match read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(vec![]),
+    };
+    let mut instances: Vec<Instance> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .filter_map(|path| {
+            let instance = read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|content| Ok(serde_json::from_str::<Instance>(&content)?));
+            match instance {
+                Ok(instance) => Some(instance),
+                Err(err) => {
+                    warn!("skip instance file {:?} : {}", path, err);
+                    None
+                }
+            }
+        })
+        .collect();
+    instances.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+    Ok(instances)
+}
+
+/// The instance `pid` argument, or the last started instance of the current setup and env in
+/// the current project, running ones first.
+pub fn select_instance(app: &ArgMatches) -> Result<Instance> {
+    if let Some(pid) = app.value_of("pid") {
+        let pid: u32 = pid.parse().context(format!("invalid pid `{}`", pid))?;
+        return instances()?
+            .into_iter()
+            .find(|instance| instance.pid == pid)
+            .ok_or_else(|| CliError::InstanceNotFound(pid.to_string()).into());
+    }
+
+    let mut cfg = get_cfg()?;
+    cfg.sync_local_to_global()?;
+    let settings = get_settings(app, &cfg);
+    let setup_name = settings.setup()?;
+    let env = settings.env()?;
+    let project = cfg.current_setup(setup_name)?.local_cfg_dir()?;
+
+    let mut candidates: Vec<Instance> = instances()?
+        .into_iter()
+        .filter(|instance| {
+            instance.project == project && &instance.setup == setup_name && &instance.env == env
+        })
+        .collect();
+    candidates.sort_by_key(|instance| instance.is_running());
+    candidates
+        .pop()
+        .ok_or_else(|| CliError::InstanceNotFound(format!("{}:{}", setup_name, env)).into())
+}
@@ -1,7 +1,17 @@
 #[macro_use]
 pub mod terminal;
+pub mod arguments;
 pub mod cfg;
 pub mod commands;
 pub mod error;
+pub mod history;
+pub mod hooks;
+pub mod instances;
+pub mod processes;
+pub mod protected;
+pub mod requirements;
 mod selected_envs;
 pub mod settings;
+pub mod shell;
+pub mod steps;
+pub mod watch;
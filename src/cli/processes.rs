@@ -0,0 +1,195 @@
+use std::process::Command;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use libc::c_int;
+use log::*;
+
+use crate::cli::error::CliError;
+use crate::cli::terminal::message::{bad_info, info};
+use crate::run_file::{
+    exit_code, kill_process_group, spawn_prefixed, PrefixedChild, SignalListener,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Time given to the last lines of an exited process to be written.
+const OUTPUTS_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone)]
+pub struct UpSettings {
+    /// Stop all the processes when one of them exits.
+    pub stop_on_exit: bool,
+    /// Time given to the processes to stop before to be killed.
+    pub grace_period: Duration,
+}
+
+impl Default for UpSettings {
+    fn default() -> Self {
+        Self {
+            stop_on_exit: true,
+            grace_period: Duration::from_secs(5),
+        }
+    }
+}
+
+/// A process of `sht up`, its outputs are written after `prefix`.
+pub struct UpProcess {
+    pub prefix: String,
+    pub command: Command,
+}
+
+struct Running {
+    prefix: String,
+    child: PrefixedChild,
+}
+
+/// Start all the processes and wait for them. They are stopped when short receives one of
+/// `FORWARDED_SIGNALS`, forwarded to each of them, or when one exits with `stop_on_exit`.
+/// Return the first non-zero exit status of a process that has not been stopped by short.
+pub fn up(processes: Vec<UpProcess>, settings: &UpSettings) -> Result<i32> {
+    let signals = SignalListener::new()?;
+
+    let mut running = vec![];
+    for process in processes {
+        match spawn_prefixed(process.command, &process.prefix) {
+            Ok(child) => running.push(Running {
+                prefix: process.prefix,
+                child,
+            }),
+            Err(err) => {
+                stop(running, libc::SIGTERM, settings)?;
+                return Err(err);
+            }
+        }
+    }
+
+    let mut status = 0;
+    let stop_signal = loop {
+        if let Some(signal) = signals.pending() {
+            debug!("stop the processes on signal {}", signal);
+            break Some(signal);
+        }
+
+        let mut exited = false;
+        let mut i = 0;
+        while i < running.len() {
+            match running[i].child.child.try_wait()? {
+                Some(exit_status) => {
+                    let process = running.remove(i);
+                    process.child.wait_outputs(OUTPUTS_TIMEOUT);
+                    let code = exit_code(&exit_status);
+                    let msg = format!("{} exit with status {}", process.prefix, code);
+                    if code == 0 {
+                        info(msg.as_str());
+                    } else {
+                        bad_info(msg.as_str());
+                        if status == 0 {
+                            status = code;
+                        }
+                    }
+                    exited = true;
+                }
+                None => i += 1,
+            }
+        }
+
+        if running.is_empty() {
+            break None;
+        }
+        if exited && settings.stop_on_exit {
+            info("stop the other processes");
+            break Some(libc::SIGTERM);
+        }
+        sleep(POLL_INTERVAL);
+    };
+
+    if let Some(signal) = stop_signal {
+        stop(running, signal, settings)?;
+    }
+    Ok(status)
+}
+
+/// Send `signal` to the process groups, kill the ones still alive after the grace period.
+fn stop(mut running: Vec<Running>, signal: c_int, settings: &UpSettings) -> Result<()> {
+    for process in running.iter() {
+        kill_process_group(process.child.child.id(), signal);
+    }
+    let start = Instant::now();
+    while !running.is_empty() && start.elapsed() < settings.grace_period {
+        let mut i = 0;
+        while i < running.len() {
+            if running[i].child.child.try_wait()?.is_some() {
+                running.remove(i).child.wait_outputs(OUTPUTS_TIMEOUT);
+            } else {
+                i += 1;
+            }
+        }
+        sleep(POLL_INTERVAL);
+    }
+    for mut process in running {
+        bad_info(
+            format!(
+                "{} still running after {}, kill it",
+                process.prefix,
+                humantime::format_duration(settings.grace_period)
+            )
+            .as_str(),
+        );
+        kill_process_group(process.child.child.id(), libc::SIGKILL);
+        process.child.child.wait()?;
+        process.child.wait_outputs(OUTPUTS_TIMEOUT);
+    }
+    Ok(())
+}
+
+/// Names and command lines of a Procfile, `<name>: <command>` per line.
+pub fn parse_procfile(content: &str) -> Result<Vec<(String, String)>> {
+    let mut processes = vec![];
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut split = line.splitn(2, ':');
+        match (split.next(), split.next()) {
+            (Some(name), Some(command))
+                if !name.trim().is_empty() && !command.trim().is_empty() =>
+            {
+                processes.push((name.trim().to_string(), command.trim().to_string()))
+            }
+            _ => return Err(CliError::InvalidProcfileLine(i + 1, line.to_string()).into()),
+        }
+    }
+    Ok(processes)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cli::processes::parse_procfile;
+
+    #[test]
+    fn procfile() {
+        let processes = parse_procfile(
+            r#"
+# services
+web: bundle exec rails server -p $PORT
+worker:bundle exec sidekiq
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            processes,
+            vec![
+                (
+                    "web".to_string(),
+                    "bundle exec rails server -p $PORT".to_string()
+                ),
+                ("worker".to_string(), "bundle exec sidekiq".to_string()),
+            ]
+        );
+
+        let err = parse_procfile("web: serve\nworker\n").unwrap_err();
+        assert_eq!(err.to_string(), "invalid Procfile line 2 : `worker`");
+    }
+}
@@ -0,0 +1,29 @@
+use colored::*;
+
+use anyhow::Result;
+
+use crate::cli::error::CliError;
+use crate::cli::terminal::confirm::confirm_input;
+use crate::cli::terminal::message::message;
+
+pub fn protected_banner(env_name: &str) {
+    let banner = format!(" PROTECTED ENV `{}` ", env_name);
+    let line = "=".repeat(banner.len());
+    message(line.red().bold().to_string().as_str());
+    message(banner.red().bold().to_string().as_str());
+    message(line.red().bold().to_string().as_str());
+}
+
+/// The env name must be typed to go further.
+pub fn confirm_protected_env(env_name: &str) -> Result<()> {
+    protected_banner(env_name);
+    let input = confirm_input(
+        std::io::stdout(),
+        format!("Type `{}` to confirm", env_name.bold()).as_str(),
+    )?;
+    if input == env_name {
+        Ok(())
+    } else {
+        Err(CliError::ProtectedEnvNotConfirmed(env_name.to_string()).into())
+    }
+}
@@ -0,0 +1,339 @@
+use std::cmp::Ordering;
+use std::env;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::Result;
+use regex::Regex;
+
+use crate::cfg::SetupRequirement;
+use crate::cli::error::CliError;
+use crate::cli::terminal::message::{bad_info, good_info};
+
+/// Result of the check of a requirement : what was found, or why it's missing.
+pub struct RequirementCheck {
+    pub requirement: SetupRequirement,
+    pub found: Option<String>,
+    pub missing: Option<String>,
+}
+
+impl RequirementCheck {
+    fn found(requirement: &SetupRequirement, found: Option<String>) -> Self {
+        Self {
+            requirement: requirement.clone(),
+            found,
+            missing: None,
+        }
+    }
+
+    fn missing(requirement: &SetupRequirement, missing: String) -> Self {
+        Self {
+            requirement: requirement.clone(),
+            found: None,
+            missing: Some(missing),
+        }
+    }
+
+    pub fn is_met(&self) -> bool {
+        self.missing.is_none()
+    }
+
+    /// Print the requirement, with the found version or what is missing.
+    pub fn print(&self) {
+        match (&self.missing, &self.found) {
+            (Some(missing), _) => {
+                bad_info(format!("`{}` : {}", self.requirement, missing).as_str())
+            }
+            (None, Some(found)) => {
+                good_info(format!("`{}` : {}", self.requirement, found).as_str())
+            }
+            (None, None) => good_info(format!("`{}`", self.requirement).as_str()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum VersionOp {
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+    Equal,
+}
+
+impl VersionOp {
+    /// Split `>=14` into the operator and the version.
+    fn parse(constraint: &str) -> Option<(Self, &str)> {
+        let ops = [
+            (">=", VersionOp::GreaterOrEqual),
+            ("<=", VersionOp::LessOrEqual),
+            ("==", VersionOp::Equal),
+            (">", VersionOp::Greater),
+            ("<", VersionOp::Less),
+            ("=", VersionOp::Equal),
+        ];
+        let (prefix, op) = ops
+            .iter()
+            .find(|(prefix, _)| constraint.starts_with(prefix))?;
+        let version = constraint[prefix.len()..].trim();
+        if version.is_empty() {
+            return None;
+        }
+        Some((*op, version))
+    }
+
+    fn matches(&self, ordering: Ordering) -> bool {
+        match self {
+            VersionOp::Greater => ordering == Ordering::Greater,
+            VersionOp::GreaterOrEqual => ordering != Ordering::Less,
+            VersionOp::Less => ordering == Ordering::Less,
+            VersionOp::LessOrEqual => ordering != Ordering::Greater,
+            VersionOp::Equal => ordering == Ordering::Equal,
+        }
+    }
+}
+
+/// Tool name and version constraint of `node >=14`.
+fn parse_tool(spec: &str) -> Result<(&str, Option<(VersionOp, &str)>)> {
+    let spec = spec.trim();
+    let (tool, constraint) = match spec.find(char::is_whitespace) {
+        Some(i) => (&spec[..i], spec[i..].trim()),
+        None => (spec, ""),
+    };
+    if tool.is_empty() {
+        return Err(CliError::InvalidRequirement(spec.to_string()).into());
+    }
+    if constraint.is_empty() {
+        return Ok((tool, None));
+    }
+    match VersionOp::parse(constraint) {
+        Some(constraint) => Ok((tool, Some(constraint))),
+        None => Err(CliError::InvalidRequirement(spec.to_string()).into()),
+    }
+}
+
+/// Executable `tool` found in the `PATH`, or at this path relative to the project directory if
+/// it contains a `/`.
+fn find_in_path(tool: &str, project_dir: &Path) -> Option<PathBuf> {
+    let is_executable = |path: &Path| {
+        path.metadata()
+            .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+            .unwrap_or(false)
+    };
+    if tool.contains('/') {
+        let path = project_dir.join(tool);
+        return Some(path).filter(|path| is_executable(path));
+    }
+    env::var_os("PATH").and_then(|paths| {
+        env::split_paths(&paths)
+            .map(|dir| dir.join(tool))
+            .find(|path| is_executable(path))
+    })
+}
+
+/// First version found in the output of `<tool> --version`, `tool` is found in the `PATH`.
+fn tool_version(path: &Path) -> Option<String> {
+    let output = Command::new(path)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .output()
+        .ok()?;
+    let output = format!(
+        "{}\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let re = Regex::new(r"\d+(\.\d+)*").unwrap();
+    re.find(&output).map(|version| version.as_str().to_string())
+}
+
+#[cfg(feature = "versions")]
+fn compare_versions(found: &str, required: &str) -> Option<Ordering> {
+    let found = versions::Versioning::new(found)?;
+    let required = versions::Versioning::new(required)?;
+    found.partial_cmp(&required)
+}
+
+#[cfg(not(feature = "versions"))]
+fn compare_versions(_found: &str, _required: &str) -> Option<Ordering> {
+    None
+}
+
+fn check_tool(
+    requirement: &SetupRequirement,
+    spec: &str,
+    project_dir: &Path,
+) -> Result<RequirementCheck> {
+    let (tool, constraint) = parse_tool(spec)?;
+    let path = match find_in_path(tool, project_dir) {
+        Some(path) => path,
+        None => {
+            return Ok(RequirementCheck::missing(
+                requirement,
+                "not found in the PATH, install it".to_string(),
+            ))
+        }
+    };
+    let (op, required) = match constraint {
+        Some(constraint) => constraint,
+        None => {
+            return Ok(RequirementCheck::found(
+                requirement,
+                Some(path.to_string_lossy().into_owned()),
+            ))
+        }
+    };
+    // Not allowed like the run file, a tool of the project is not run to get its version.
+    if tool.contains('/') {
+        return Ok(RequirementCheck::missing(
+            requirement,
+            "the version of a tool of the project is not checked, remove the constraint"
+                .to_string(),
+        ));
+    }
+    let version = match tool_version(&path) {
+        Some(version) => version,
+        None => {
+            return Ok(RequirementCheck::missing(
+                requirement,
+                format!("no version found with \"{} --version\"", tool),
+            ))
+        }
+    };
+    let check = match compare_versions(&version, required) {
+        Some(ordering) if op.matches(ordering) => {
+            RequirementCheck::found(requirement, Some(format!("version {}", version)))
+        }
+        Some(_) => RequirementCheck::missing(
+            requirement,
+            format!("version {} found, upgrade or switch it", version),
+        ),
+        None => RequirementCheck::missing(
+            requirement,
+            format!("fail to compare version {} to {}", version, required),
+        ),
+    };
+    Ok(check)
+}
+
+/// Check the `requires` of a setup. `vars` are the vars of the env, `project_dir` is the
+/// directory of the required files.
+pub fn check_requirements(
+    requirements: &[SetupRequirement],
+    vars: &[(String, String)],
+    env_name: &str,
+    project_dir: &Path,
+) -> Result<Vec<RequirementCheck>> {
+    let mut checks = vec![];
+    for requirement in requirements {
+        let check = match requirement {
+            SetupRequirement::Tool(spec) => check_tool(requirement, spec, project_dir)?,
+            SetupRequirement::Var { var } => {
+                let is_set = vars
+                    .iter()
+                    .any(|(name, value)| name == &var.to_uppercase() && !value.is_empty());
+                if is_set {
+                    RequirementCheck::found(requirement, None)
+                } else {
+                    RequirementCheck::missing(
+                        requirement,
+                        format!("not set in the env `{}`, add it to the env file", env_name),
+                    )
+                }
+            }
+            SetupRequirement::File { file } => {
+                if project_dir.join(file).exists() {
+                    RequirementCheck::found(requirement, None)
+                } else {
+                    RequirementCheck::missing(
+                        requirement,
+                        "not found in the project directory".to_string(),
+                    )
+                }
+            }
+        };
+        checks.push(check);
+    }
+    Ok(checks)
+}
+
+/// Fail if a requirement is missing, after listing the missing ones.
+pub fn ensure_requirements(setup_name: &str, checks: &[RequirementCheck]) -> Result<()> {
+    let missing: Vec<&RequirementCheck> = checks.iter().filter(|check| !check.is_met()).collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+    for check in missing.iter() {
+        check.print();
+    }
+    Err(CliError::MissingRequirements(setup_name.to_string(), missing.len()).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::cfg::SetupRequirement;
+    use crate::cli::requirements::{check_requirements, parse_tool, VersionOp};
+
+    #[test]
+    fn tool_spec() {
+        assert_eq!(parse_tool("aws").unwrap(), ("aws", None));
+        assert_eq!(
+            parse_tool("node >=14").unwrap(),
+            ("node", Some((VersionOp::GreaterOrEqual, "14")))
+        );
+        assert_eq!(
+            parse_tool("docker-compose < 2.1").unwrap(),
+            ("docker-compose", Some((VersionOp::Less, "2.1")))
+        );
+        assert!(parse_tool("node ~14").is_err());
+        assert!(parse_tool("node >=").is_err());
+    }
+
+    #[cfg(feature = "versions")]
+    #[test]
+    fn versions() {
+        use crate::cli::requirements::compare_versions;
+        use std::cmp::Ordering;
+
+        assert_eq!(compare_versions("14.17.0", "14"), Some(Ordering::Greater));
+        assert_eq!(compare_versions("12.3.1", "14"), Some(Ordering::Less));
+        assert_eq!(compare_versions("1.29.2", "1.29.2"), Some(Ordering::Equal));
+    }
+
+    #[test]
+    fn requirements() {
+        let requirements = vec![
+            SetupRequirement::Tool("sh".into()),
+            SetupRequirement::Tool("not-a-tool-of-short".into()),
+            SetupRequirement::Var {
+                var: "database_url".into(),
+            },
+            SetupRequirement::Var {
+                var: "API_KEY".into(),
+            },
+            SetupRequirement::File {
+                file: "Cargo.toml".into(),
+            },
+        ];
+        let vars = vec![
+            ("DATABASE_URL".to_string(), "postgres://".to_string()),
+            ("API_KEY".to_string(), "".to_string()),
+        ];
+        let checks = check_requirements(
+            &requirements,
+            &vars,
+            "dev",
+            Path::new(env!("CARGO_MANIFEST_DIR")),
+        )
+        .unwrap();
+        let met: Vec<bool> = checks.iter().map(|check| check.is_met()).collect();
+        assert_eq!(met, vec![true, false, true, false, true]);
+        assert_eq!(
+            checks[3].missing.as_ref().unwrap(),
+            "not set in the env `dev`, add it to the env file"
+        );
+    }
+}
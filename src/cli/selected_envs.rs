@@ -18,7 +18,8 @@ pub fn selected_envs(app: &ArgMatches, setup: &Setup, settings: &Settings) -> Re
 
     let envs: Vec<_> = setup.envs().into_iter().filter_map(|r| r.ok()).collect();
     let recent_env = Env::recent(&envs)?;
-    let sync_settings = SyncSettings::new(app);
+    let mut sync_settings = SyncSettings::new(app);
+    sync_settings.protect_envs(setup, &envs);
     let mut envs = sync_workflow(recent_env, envs, sync_settings)?;
     envs.sort();
     let envs: Vec<_> = envs
@@ -98,7 +98,7 @@ impl From<&Cfg> for Settings {
 
                 if let Some((setup_name, env_name)) = current_setup {
                     if let Ok(current_setup) = cfg.current_setup(&setup_name) {
-                        if current_setup.env_file(&env_name).is_err() {
+                        if current_setup.env_location(&env_name).is_err() {
                             let mut current_project = current_project_ref.borrow_mut();
                             current_project.unset_current_env_name();
                         }
@@ -0,0 +1,257 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
+
+use anyhow::Result;
+use strum_macros::{AsRefStr, EnumIter, EnumString};
+
+use crate::cli::error::CliError;
+use crate::utils::write_all::write_all_dir;
+
+/// Set in the spawned shell to `<setup>:<env>`, used to detect nested shells.
+pub const SHELL_VAR: &str = "SHORT_SHELL";
+/// Names of the vars exported by `env --export`, separated by `:`, to unset them on the next call.
+pub const EXPORTED_VARS_VAR: &str = "SHORT_EXPORTED";
+const ORIGINAL_ZDOTDIR_VAR: &str = "SHORT_ZDOTDIR";
+const DEFAULT_SHELL: &str = "/bin/sh";
+/// Vars read by the shells to run code or to find programs, never exported by `env --export`:
+/// the env file of a project must not run code in the shell of the user.
+const SHELL_CONTROL_VARS: [&str; 23] = [
+    "PROMPT_COMMAND",
+    "PS0",
+    "PS1",
+    "PS2",
+    "PS3",
+    "PS4",
+    "PROMPT",
+    "RPROMPT",
+    "RPS1",
+    "BASH_ENV",
+    "ENV",
+    "ZDOTDIR",
+    "SHELLOPTS",
+    "BASHOPTS",
+    "IFS",
+    "CDPATH",
+    "PATH",
+    "HOME",
+    "HISTFILE",
+    "LD_PRELOAD",
+    "LD_LIBRARY_PATH",
+    "DYLD_INSERT_LIBRARIES",
+    "DYLD_LIBRARY_PATH",
+];
+/// Prefix of the functions exported by bash.
+const BASH_FUNCTION_PREFIX: &str = "BASH_FUNC_";
+
+/// True if the var is read by the shell to run code, see `SHELL_CONTROL_VARS`.
+pub fn is_shell_control_var(name: &str) -> bool {
+    SHELL_CONTROL_VARS.contains(&name) || name.starts_with(BASH_FUNCTION_PREFIX)
+}
+
+#[derive(EnumString, AsRefStr, EnumIter, Debug, Clone, Copy, Eq, PartialEq)]
+#[strum(serialize_all = "snake_case")]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Sh,
+}
+
+impl Shell {
+    /// Kind of the shell from its executable name, `sh` when it is unknown.
+    pub fn from_path(path: &Path) -> Self {
+        path.file_name()
+            .and_then(|name| Shell::from_str(&name.to_string_lossy()).ok())
+            .unwrap_or(Shell::Sh)
+    }
+
+    /// The user shell, from `$SHELL`.
+    pub fn current() -> (PathBuf, Self) {
+        let path = PathBuf::from(env::var("SHELL").unwrap_or_else(|_| DEFAULT_SHELL.to_string()));
+        let shell = Shell::from_path(&path);
+        (path, shell)
+    }
+
+    /// Quote `value` to be read literally by the shell.
+    pub fn quote(&self, value: &str) -> String {
+        match self {
+            Shell::Fish => format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'")),
+            _ => format!("'{}'", value.replace('\'', "'\\''")),
+        }
+    }
+
+    pub fn export(&self, name: &str, value: &str) -> String {
+        match self {
+            Shell::Fish => format!("set -gx {} {};", name, self.quote(value)),
+            _ => format!("export {}={};", name, self.quote(value)),
+        }
+    }
+
+    pub fn unset(&self, name: &str) -> String {
+        match self {
+            Shell::Fish => format!("set -e {};", name),
+            _ => format!("unset {};", name),
+        }
+    }
+
+    /// Code to eval in the rc file of the shell, it exports the vars of the current setup and env
+    /// when the current directory changes and unset them outside of a project. A POSIX `sh` has
+    /// no way to run a hook on `cd`.
+    pub fn hook(&self, bin: &str) -> Result<String> {
+        let hook = match self {
+            Shell::Fish => format!(
+                r#"function _short_hook --on-variable PWD
+    {bin} env --export --shell fish 2>/dev/null | source
+end
+_short_hook
+"#,
+                bin = bin
+            ),
+            Shell::Zsh => format!(
+                r#"_short_hook() {{
+  eval "$({bin} env --export --shell zsh 2>/dev/null)"
+}}
+typeset -ag chpwd_functions
+if [[ -z "${{chpwd_functions[(r)_short_hook]+1}}" ]]; then
+  chpwd_functions=(_short_hook ${{chpwd_functions[@]}})
+fi
+_short_hook
+"#,
+                bin = bin
+            ),
+            Shell::Bash => format!(
+                r#"_short_hook() {{
+  local previous_exit_status=$?
+  if [ "$PWD" != "$_SHORT_HOOK_PWD" ]; then
+    _SHORT_HOOK_PWD="$PWD"
+    eval "$({bin} env --export --shell bash 2>/dev/null)"
+  fi
+  return $previous_exit_status
+}}
+if [[ ";${{PROMPT_COMMAND:-}};" != *";_short_hook;"* ]]; then
+  PROMPT_COMMAND="_short_hook${{PROMPT_COMMAND:+;$PROMPT_COMMAND}}"
+fi
+"#,
+                bin = bin
+            ),
+            Shell::Sh => {
+                return Err(CliError::ShellHookNotSupported(self.as_ref().to_string()).into())
+            }
+        };
+        Ok(hook)
+    }
+
+    /// Prefix the prompt of the interactive shell, user rc files are still loaded.
+    /// The generated rc files are written in `rc_dir` that must live as long as the shell.
+    pub fn set_prompt_prefix(
+        &self,
+        command: &mut Command,
+        prefix: &str,
+        rc_dir: &Path,
+    ) -> Result<()> {
+        let quoted_prefix = self.quote(format!("{} ", prefix).as_str());
+        match self {
+            Shell::Bash => {
+                let rc_file = rc_dir.join(".bashrc");
+                write_all_dir(
+                    &rc_file,
+                    format!(
+                        r#"[ -f ~/.bashrc ] && . ~/.bashrc
+PS1={prefix}"$PS1"
+"#,
+                        prefix = quoted_prefix
+                    ),
+                )?;
+                command.arg("--rcfile").arg(&rc_file);
+            }
+            Shell::Zsh => {
+                let zdotdir = env::var("ZDOTDIR")
+                    .ok()
+                    .or_else(|| env::var("HOME").ok())
+                    .unwrap_or_default();
+                write_all_dir(
+                    rc_dir.join(".zshenv"),
+                    format!(
+                        r#"[ -f "${var}/.zshenv" ] && . "${var}/.zshenv"
+"#,
+                        var = ORIGINAL_ZDOTDIR_VAR
+                    ),
+                )?;
+                write_all_dir(
+                    rc_dir.join(".zshrc"),
+                    format!(
+                        r#"ZDOTDIR="${var}"
+unset {var}
+[ -f "$ZDOTDIR/.zshrc" ] && . "$ZDOTDIR/.zshrc"
+PROMPT={prefix}"$PROMPT"
+"#,
+                        var = ORIGINAL_ZDOTDIR_VAR,
+                        prefix = quoted_prefix
+                    ),
+                )?;
+                command.env(ORIGINAL_ZDOTDIR_VAR, zdotdir);
+                command.env("ZDOTDIR", rc_dir);
+            }
+            Shell::Fish => {
+                command.arg("--init-command").arg(format!(
+                    r#"functions -c fish_prompt _short_fish_prompt
+function fish_prompt; echo -n {prefix}; _short_fish_prompt; end"#,
+                    prefix = quoted_prefix
+                ));
+            }
+            Shell::Sh => {
+                let ps1 = env::var("PS1").unwrap_or_else(|_| "$ ".to_string());
+                command.env("PS1", format!("{} {}", prefix, ps1));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::cli::shell::{is_shell_control_var, Shell};
+
+    #[test]
+    fn shell_from_path() {
+        assert_eq!(Shell::from_path(&PathBuf::from("/bin/bash")), Shell::Bash);
+        assert_eq!(Shell::from_path(&PathBuf::from("/usr/bin/zsh")), Shell::Zsh);
+        assert_eq!(Shell::from_path(&PathBuf::from("fish")), Shell::Fish);
+        assert_eq!(Shell::from_path(&PathBuf::from("/bin/dash")), Shell::Sh);
+    }
+
+    #[test]
+    fn shell_quote() {
+        assert_eq!(Shell::Bash.quote("it's $HOME"), r#"'it'\''s $HOME'"#);
+        assert_eq!(Shell::Fish.quote(r"it's \o/"), r"'it\'s \\o/'");
+    }
+
+    #[test]
+    fn shell_export() {
+        assert_eq!(Shell::Bash.export("VAR", "a b"), "export VAR='a b';");
+        assert_eq!(Shell::Bash.unset("VAR"), "unset VAR;");
+        assert_eq!(Shell::Fish.export("VAR", "a b"), "set -gx VAR 'a b';");
+        assert_eq!(Shell::Fish.unset("VAR"), "set -e VAR;");
+    }
+
+    #[test]
+    fn shell_hook() {
+        assert!(Shell::Bash.hook("sht").unwrap().contains("PROMPT_COMMAND"));
+        assert!(Shell::Fish
+            .hook("sht")
+            .unwrap()
+            .contains("--on-variable PWD"));
+        assert!(Shell::Sh.hook("sht").is_err());
+    }
+
+    #[test]
+    fn shell_control_var() {
+        assert!(is_shell_control_var("PROMPT_COMMAND"));
+        assert!(is_shell_control_var("BASH_FUNC_ls%%"));
+        assert!(!is_shell_control_var("DATABASE_URL"));
+    }
+}
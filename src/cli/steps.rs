@@ -0,0 +1,266 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::sync::mpsc::channel;
+use std::thread;
+
+use anyhow::Result;
+
+use crate::cli::error::CliError;
+
+/// The run file of a setup, `<setup>`, or one of its commands, `<setup>:<command>`.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub struct StepId {
+    pub setup: String,
+    pub command: Option<String>,
+}
+
+impl StepId {
+    pub fn new(setup: String, command: Option<String>) -> Self {
+        Self { setup, command }
+    }
+
+    /// Parse `<setup>` or `<setup>:<command>`.
+    pub fn parse(step: &str) -> Self {
+        let mut split = step.splitn(2, ':');
+        let setup = split.next().unwrap_or_default().to_string();
+        Self::new(setup, split.next().map(|command| command.to_string()))
+    }
+}
+
+impl fmt::Display for StepId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.command {
+            Some(command) => write!(f, "{}:{}", self.setup, command),
+            None => write!(f, "{}", self.setup),
+        }
+    }
+}
+
+/// Steps to run for a target, each one after its dependencies. A step required by several
+/// others is run once.
+#[derive(Debug)]
+pub struct Plan {
+    pub target: StepId,
+    pub steps: Vec<StepId>,
+    depends_on: BTreeMap<StepId, Vec<StepId>>,
+}
+
+impl Plan {
+    /// Walk the dependencies of `target` given by `depends_on`, fail on a cycle.
+    pub fn new<F>(target: StepId, mut depends_on: F) -> Result<Self>
+    where
+        F: FnMut(&StepId) -> Result<Vec<StepId>>,
+    {
+        let mut plan = Self {
+            target: target.clone(),
+            steps: vec![],
+            depends_on: BTreeMap::new(),
+        };
+        let mut path = vec![];
+        plan.visit(target, &mut path, &mut depends_on)?;
+        Ok(plan)
+    }
+
+    fn visit<F>(&mut self, step: StepId, path: &mut Vec<StepId>, depends_on: &mut F) -> Result<()>
+    where
+        F: FnMut(&StepId) -> Result<Vec<StepId>>,
+    {
+        if let Some(position) = path.iter().position(|visiting| visiting == &step) {
+            let mut cycle: Vec<String> = path[position..].iter().map(|s| s.to_string()).collect();
+            cycle.push(step.to_string());
+            return Err(CliError::DependencyCycle(cycle.join(" -> ")).into());
+        }
+        if self.depends_on.contains_key(&step) {
+            return Ok(());
+        }
+
+        let dependencies = depends_on(&step)?;
+        path.push(step.clone());
+        for dependency in dependencies.iter() {
+            self.visit(dependency.clone(), path, depends_on)?;
+        }
+        path.pop();
+
+        self.depends_on.insert(step.clone(), dependencies);
+        self.steps.push(step);
+        Ok(())
+    }
+
+    pub fn depends_on(&self, step: &StepId) -> &[StepId] {
+        self.depends_on
+            .get(step)
+            .map(|dependencies| dependencies.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Steps from the target to `step` through the dependencies, e.g. `deploy -> test -> build`.
+    pub fn path_to(&self, step: &StepId) -> Vec<StepId> {
+        let mut path = vec![self.target.clone()];
+        self.find_path(step, &mut path);
+        path
+    }
+
+    fn find_path(&self, step: &StepId, path: &mut Vec<StepId>) -> bool {
+        let current = path.last().unwrap().clone();
+        if &current == step {
+            return true;
+        }
+        for dependency in self.depends_on(&current) {
+            path.push(dependency.clone());
+            if self.find_path(step, path) {
+                return true;
+            }
+            path.pop();
+        }
+        false
+    }
+}
+
+/// Run the steps of `plan` with `run`, up to `jobs` at the same time, a step starts once
+/// all its dependencies have succeeded. After a failure no step starts and the running ones
+/// are awaited. Return the first failed step with its exit status.
+pub fn run_steps<F>(plan: &Plan, jobs: usize, run: F) -> Result<Option<(StepId, i32)>>
+where
+    F: Fn(&StepId) -> Result<i32> + Sync,
+{
+    let jobs = jobs.max(1);
+    let mut pending: Vec<&StepId> = plan.steps.iter().collect();
+    let mut succeeded: BTreeSet<&StepId> = BTreeSet::new();
+    let mut failure: Option<(StepId, i32)> = None;
+    let mut error = None;
+
+    let (sender, receiver) = channel();
+    let run = &run;
+    thread::scope(|scope| {
+        let mut running = 0;
+        loop {
+            while failure.is_none() && error.is_none() && running < jobs {
+                let ready = pending.iter().position(|step| {
+                    plan.depends_on(step)
+                        .iter()
+                        .all(|dependency| succeeded.contains(dependency))
+                });
+                let step = match ready {
+                    Some(index) => pending.remove(index),
+                    None => break,
+                };
+                let sender = sender.clone();
+                scope.spawn(move || {
+                    let _ = sender.send((step, run(step)));
+                });
+                running += 1;
+            }
+            if running == 0 {
+                break;
+            }
+
+            let (step, result) = receiver.recv().expect("fail to receive step result");
+            running -= 1;
+            match result {
+                Ok(0) => {
+                    succeeded.insert(step);
+                }
+                Ok(status) => {
+                    failure.get_or_insert((step.clone(), status));
+                }
+                Err(err) => {
+                    error.get_or_insert(err);
+                }
+            }
+        }
+    });
+
+    match error {
+        Some(err) => Err(err),
+        None => Ok(failure),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::Mutex;
+
+    use crate::cli::steps::{run_steps, Plan, StepId};
+
+    fn step(name: &str) -> StepId {
+        StepId::new("app".to_string(), Some(name.to_string()))
+    }
+
+    fn plan(target: &str, graph: &[(&str, &[&str])]) -> anyhow::Result<Plan> {
+        let graph: BTreeMap<StepId, Vec<StepId>> = graph
+            .iter()
+            .map(|(name, dependencies)| {
+                (step(name), dependencies.iter().map(|d| step(d)).collect())
+            })
+            .collect();
+        Plan::new(step(target), |step| {
+            Ok(graph.get(step).cloned().unwrap_or_default())
+        })
+    }
+
+    #[test]
+    fn plan_order() {
+        let plan = plan(
+            "deploy",
+            &[
+                ("deploy", &["test", "build"]),
+                ("test", &["build"]),
+                ("build", &[]),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            plan.steps,
+            vec![step("build"), step("test"), step("deploy")]
+        );
+        assert_eq!(
+            plan.path_to(&step("build")),
+            vec![step("deploy"), step("test"), step("build")]
+        );
+        assert_eq!(step("build").to_string(), "app:build");
+        assert_eq!(StepId::parse("app:build"), step("build"));
+        assert_eq!(StepId::parse("app"), StepId::new("app".to_string(), None));
+    }
+
+    #[test]
+    fn plan_cycle() {
+        let err = plan(
+            "deploy",
+            &[
+                ("deploy", &["test"]),
+                ("test", &["build"]),
+                ("build", &["test"]),
+            ],
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "dependency cycle app:test -> app:build -> app:test"
+        );
+    }
+
+    #[test]
+    fn run_steps_failure() {
+        let plan = plan(
+            "deploy",
+            &[
+                ("deploy", &["test", "lint"]),
+                ("test", &["build"]),
+                ("lint", &[]),
+            ],
+        )
+        .unwrap();
+        let ran = Mutex::new(vec![]);
+        let failure = run_steps(&plan, 1, |step| {
+            ran.lock().unwrap().push(step.clone());
+            Ok(if step == &self::step("test") { 2 } else { 0 })
+        })
+        .unwrap();
+        assert_eq!(failure, Some((step("test"), 2)));
+        assert_eq!(ran.into_inner().unwrap(), vec![step("build"), step("test")]);
+
+        let failure = run_steps(&plan, 4, |_| Ok(0)).unwrap();
+        assert_eq!(failure, None);
+    }
+}
@@ -3,11 +3,28 @@ use anyhow::Result;
 use colored::*;
 use crossterm::event::{read, Event, KeyCode, KeyEvent, KeyModifiers};
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+use crossterm::tty::IsTty;
 use serde::export::fmt::Debug;
 use std::fmt::Write;
 use std::io;
 use std::string::ToString;
 
+/// Raw mode of the terminal, disabled on drop, even when the read of a key fails.
+struct RawMode;
+
+impl RawMode {
+    fn enable() -> Result<Self> {
+        enable_raw_mode()?;
+        Ok(RawMode)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+    }
+}
+
 pub fn confirm<W, E>(mut writer: W, question: &str, e: Vec<E>) -> Result<E>
 where
     W: io::Write,
@@ -16,13 +33,12 @@ where
     let mut write_question_line = || writeln!(writer, "{} : {}", question, &e.to_string()).unwrap();
 
     write_question_line();
-    enable_raw_mode()?;
+    let mut raw_mode = RawMode::enable()?;
     let e = loop {
         if let Event::Key(event) = read()? {
             if event == KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)
                 || event == KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL)
             {
-                disable_raw_mode()?;
                 bail!(UserStopSync);
             }
 
@@ -35,16 +51,61 @@ where
             }) {
                 break e;
             } else {
-                disable_raw_mode()?;
+                drop(raw_mode);
                 write_question_line();
-                enable_raw_mode()?;
+                raw_mode = RawMode::enable()?;
             }
         }
     };
-    disable_raw_mode()?;
+    drop(raw_mode);
     Ok(e.clone())
 }
 
+/// Ask the user to type a text, ended by enter.
+/// Fall back to read a line from stdin when it's not a terminal.
+pub fn confirm_input<W>(mut writer: W, question: &str) -> Result<String>
+where
+    W: io::Write,
+{
+    write!(writer, "{} : ", question)?;
+    writer.flush()?;
+
+    if !io::stdin().is_tty() {
+        let mut input = String::new();
+        io::stdin().read_line(&mut input)?;
+        return Ok(input.trim().to_string());
+    }
+
+    let mut input = String::new();
+    let raw_mode = RawMode::enable()?;
+    loop {
+        if let Event::Key(event) = read()? {
+            if event == KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)
+                || event == KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL)
+            {
+                drop(raw_mode);
+                writeln!(writer)?;
+                bail!(UserStopSync);
+            }
+            match event.code {
+                KeyCode::Enter => break,
+                KeyCode::Backspace if input.pop().is_some() => {
+                    write!(writer, "\u{8} \u{8}")?;
+                }
+                KeyCode::Char(c) => {
+                    input.push(c);
+                    write!(writer, "{}", c)?;
+                }
+                _ => {}
+            }
+            writer.flush()?;
+        }
+    }
+    drop(raw_mode);
+    writeln!(writer)?;
+    Ok(input)
+}
+
 pub trait EnumConfirm {
     type T: EnumConfirm + Sized;
     fn to_vec() -> Vec<Self::T>;
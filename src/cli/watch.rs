@@ -0,0 +1,250 @@
+use std::collections::BTreeMap;
+use std::fs::metadata;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::thread::sleep;
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::{Context, Result};
+use libc::c_int;
+use log::*;
+
+use crate::cli::history::{record, HistoryEntry};
+use crate::cli::terminal::message::{bad_info, info};
+use crate::run_file::{exit_code, kill_process_group, set_process_group, SignalListener};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone)]
+pub struct WatchSettings {
+    /// Sent to the process group of the child to stop it before a restart.
+    pub stop_signal: c_int,
+    /// Time given to the child to stop before to be killed.
+    pub grace_period: Duration,
+    /// Time without changes to wait before a restart.
+    pub debounce: Duration,
+}
+
+impl Default for WatchSettings {
+    fn default() -> Self {
+        Self {
+            stop_signal: libc::SIGTERM,
+            grace_period: Duration::from_secs(5),
+            debounce: Duration::from_millis(300),
+        }
+    }
+}
+
+/// What to run and watch, prepared again before each restart.
+pub struct WatchRun {
+    pub command: Command,
+    /// Recorded with the duration and the exit status of each run.
+    pub history: HistoryEntry,
+    pub vars: BTreeMap<String, String>,
+    pub files: Vec<PathBuf>,
+    /// Called with the exit status once the command has exited or has been stopped.
+    pub post_run: Box<dyn FnOnce(i32)>,
+}
+
+type Fingerprint = Option<(SystemTime, u64)>;
+
+struct FilesWatcher {
+    files: Vec<(PathBuf, Fingerprint)>,
+}
+
+impl FilesWatcher {
+    fn new(files: Vec<PathBuf>) -> Self {
+        let files = files
+            .into_iter()
+            .map(|file| {
+                let fingerprint = fingerprint(&file);
+                (file, fingerprint)
+            })
+            .collect();
+        Self { files }
+    }
+
+    /// True if a file has changed since the last call.
+    fn changed(&mut self) -> bool {
+        let mut changed = false;
+        for (file, last_fingerprint) in self.files.iter_mut() {
+            let fingerprint = fingerprint(file);
+            if fingerprint != *last_fingerprint {
+                *last_fingerprint = fingerprint;
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+fn fingerprint(file: &PathBuf) -> Fingerprint {
+    let metadata = metadata(file).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Names of the vars added, removed or changed, e.g. `VAR1 changed, VAR2 added`.
+pub fn vars_summary(old: &BTreeMap<String, String>, new: &BTreeMap<String, String>) -> String {
+    let mut changes = vec![];
+    for (name, value) in new.iter() {
+        match old.get(name) {
+            None => changes.push(format!("{} added", name)),
+            Some(old_value) if old_value != value => changes.push(format!("{} changed", name)),
+            _ => {}
+        }
+    }
+    for name in old.keys() {
+        if !new.contains_key(name) {
+            changes.push(format!("{} removed", name));
+        }
+    }
+    if changes.is_empty() {
+        "no var changed".to_string()
+    } else {
+        changes.join(", ")
+    }
+}
+
+enum Event {
+    Signal(c_int),
+    Change,
+}
+
+/// Run the command of `prepare` and restart it when one of its files changes, until short
+/// receives one of `FORWARDED_SIGNALS`. Return the exit code of the last run.
+pub fn watch<F>(settings: &WatchSettings, mut prepare: F) -> Result<i32>
+where
+    F: FnMut() -> Result<WatchRun>,
+{
+    let signals = SignalListener::new()?;
+
+    let run = prepare()?;
+    let mut vars = run.vars;
+    let mut watcher = FilesWatcher::new(run.files);
+    let mut next_run = Some((run.command, run.history, run.post_run));
+    let mut status = 0;
+
+    loop {
+        let mut child = match next_run.take() {
+            Some((command, history, post_run)) => {
+                Some((spawn(command)?, history, post_run, Instant::now()))
+            }
+            None => None,
+        };
+
+        let event = loop {
+            if let Some(signal) = signals.pending() {
+                break Event::Signal(signal);
+            }
+            if let Some((running, _, _, _)) = child.as_mut() {
+                if let Some(exit_status) = running.try_wait()? {
+                    status = exit_code(&exit_status);
+                    info(format!("exit with status {}, waiting for changes", status).as_str());
+                    if let Some((_, history, post_run, start)) = child.take() {
+                        record_run(history, start, status);
+                        post_run(status);
+                    }
+                }
+            }
+            if watcher.changed() {
+                // Wait for the end of the writes.
+                loop {
+                    sleep(settings.debounce);
+                    if !watcher.changed() {
+                        break;
+                    }
+                }
+                break Event::Change;
+            }
+            sleep(POLL_INTERVAL);
+        };
+
+        if let Some((child, history, post_run, start)) = child {
+            status = stop(child, settings)?;
+            record_run(history, start, status);
+            post_run(status);
+        }
+
+        match event {
+            Event::Signal(signal) => {
+                debug!("stop watching on signal {}", signal);
+                return Ok(status);
+            }
+            Event::Change => match prepare() {
+                Ok(run) => {
+                    info(format!("restart: {}", vars_summary(&vars, &run.vars)).as_str());
+                    vars = run.vars;
+                    watcher = FilesWatcher::new(run.files);
+                    next_run = Some((run.command, run.history, run.post_run));
+                }
+                Err(err) => {
+                    bad_info(format!("{:#}", err).as_str());
+                    bad_info("fix it, waiting for changes");
+                }
+            },
+        }
+    }
+}
+
+fn record_run(mut history: HistoryEntry, start: Instant, status: i32) {
+    history.duration_ms = start.elapsed().as_millis() as u64;
+    history.status = status;
+    record(&history);
+}
+
+fn spawn(mut command: Command) -> Result<Child> {
+    set_process_group(&mut command);
+    // In its own process group the child can't read from the terminal.
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .context("fail to spawn the run file")
+}
+
+/// Send the stop signal, kill the process group if it is still alive after the grace period.
+fn stop(mut child: Child, settings: &WatchSettings) -> Result<i32> {
+    kill_process_group(child.id(), settings.stop_signal);
+    let start = Instant::now();
+    while start.elapsed() < settings.grace_period {
+        if let Some(exit_status) = child.try_wait()? {
+            return Ok(exit_code(&exit_status));
+        }
+        sleep(POLL_INTERVAL);
+    }
+    bad_info(
+        format!(
+            "still running after {}, kill it",
+            humantime::format_duration(settings.grace_period)
+        )
+        .as_str(),
+    );
+    kill_process_group(child.id(), libc::SIGKILL);
+    let exit_status = child.wait()?;
+    Ok(exit_code(&exit_status))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::cli::watch::vars_summary;
+
+    fn vars(vars: &[(&str, &str)]) -> BTreeMap<String, String> {
+        vars.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn watch_vars_summary() {
+        let old = vars(&[("VAR1", "a"), ("VAR2", "b"), ("VAR3", "c")]);
+        let new = vars(&[("VAR1", "a"), ("VAR2", "B"), ("VAR4", "d")]);
+        assert_eq!(
+            vars_summary(&old, &new),
+            "VAR2 changed, VAR4 added, VAR3 removed"
+        );
+        assert_eq!(vars_summary(&old, &old), "no var changed");
+    }
+}
@@ -21,4 +21,8 @@ impl Comment {
             _ => None,
         }
     }
+
+    pub fn value(&self) -> &String {
+        &self.value
+    }
 }
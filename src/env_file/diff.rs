@@ -1,13 +1,17 @@
 use std::borrow::Cow;
+use std::collections::BTreeSet;
 
 use anyhow::Result;
 
 use crate::env_file::entry::Entry;
 use crate::env_file::{Env, Var};
 
+type ChangeVarFn = Box<dyn Fn(&Var, &Var) -> Result<bool>>;
+
 pub struct EnvDiffController {
     update_var_fn: Box<dyn Fn(&mut Var) -> Result<Cow<Var>>>,
     delete_var_fn: Box<dyn Fn(&Var) -> Result<bool>>,
+    change_var_fn: Option<ChangeVarFn>,
 }
 
 impl EnvDiffController {
@@ -19,9 +23,20 @@ impl EnvDiffController {
         Self {
             update_var_fn: Box::new(update_var),
             delete_var_fn: Box::new(delete_var),
+            change_var_fn: None,
         }
     }
 
+    /// By default target values are kept, with this control a var present on both sides with a
+    /// different value take the source value if `change_var(target_var, source_var)` return true.
+    pub fn with_change_var<CVF>(mut self, change_var: CVF) -> Self
+    where
+        CVF: Fn(&Var, &Var) -> Result<bool> + 'static,
+    {
+        self.change_var_fn = Some(Box::new(change_var));
+        self
+    }
+
     fn update_var<'a>(&self, var: &'a mut Var) -> Result<Cow<'a, Var>> {
         (&self.update_var_fn)(var)
     }
@@ -29,9 +44,28 @@ impl EnvDiffController {
     fn delete_var(&self, var: &Var) -> Result<bool> {
         (&self.delete_var_fn)(var)
     }
+
+    fn change_var(&self, target_var: &Var, source_var: &Var) -> Result<bool> {
+        match &self.change_var_fn {
+            Some(change_var_fn) if target_var.value() != source_var.value() => {
+                change_var_fn(target_var, source_var)
+            }
+            _ => Ok(false),
+        }
+    }
 }
 
 impl Env {
+    /// True if `update_by_diff` has vars to add or delete, the values are not compared.
+    pub fn has_var_diff(&self, source_env: &Env) -> bool {
+        let names = |env: &Env| {
+            env.iter()
+                .map(|var| var.name().clone())
+                .collect::<BTreeSet<_>>()
+        };
+        names(self) != names(source_env)
+    }
+
     pub fn update_by_diff(&mut self, source_env: &Env, env_diff: &EnvDiffController) -> Result<()> {
         let mut source_entries = source_env.entries.clone();
         // Prevent delete vars.
@@ -56,7 +90,14 @@ impl Env {
         // Update vars : Vars can be update via the update control.
         for source_entry in source_entries.iter() {
             if let Some(target_entry) = self.entries.iter().find(|entry| *entry == source_entry) {
-                new_entries.push(target_entry.clone());
+                match (target_entry, source_entry) {
+                    (Entry::Var(target_var), Entry::Var(source_var))
+                        if env_diff.change_var(target_var, source_var)? =>
+                    {
+                        new_entries.push(source_entry.clone())
+                    }
+                    _ => new_entries.push(target_entry.clone()),
+                }
             } else {
                 let source_entry = source_entry.clone();
                 if let Entry::Var(var) = source_entry {
@@ -155,4 +196,26 @@ mod tests {
         env_expected.add("name1", "value1.1");
         assert_eq!(env_expected.to_string(), env_target.to_string());
     }
+
+    #[test]
+    fn update_by_diff_change_var() {
+        let mut env_source = Env::new("".into());
+        env_source.add("name1", "value1");
+        env_source.add("name2", "value2");
+
+        let mut env_target = Env::new("".into());
+        env_target.add("name1", "value1.1");
+        env_target.add("name2", "value2.1");
+        let controller = EnvDiffController::new(|v| Ok(Cow::Borrowed(v)), |_| Ok(true))
+            .with_change_var(|target_var, source_var| {
+                assert_ne!(target_var.value(), source_var.value());
+                Ok(target_var.name() == "name1")
+            });
+        env_target.update_by_diff(&env_source, &controller).unwrap();
+
+        let mut env_expected = Env::new("".into());
+        env_expected.add("name1", "value1");
+        env_expected.add("name2", "value2.1");
+        assert_eq!(env_expected.to_string(), env_target.to_string());
+    }
 }
@@ -3,6 +3,9 @@ use std::path::PathBuf;
 
 use thiserror::Error;
 
+use crate::env_file::EnvLocation;
+use crate::vault::VaultError;
+
 #[derive(Error, Debug)]
 pub enum EnvReaderError {
     #[error("io env reader error")]
@@ -28,14 +31,19 @@ pub enum EnvError {
         #[from]
         source: fs_extra::error::Error,
     },
+    #[error("vault env error")]
+    Vault {
+        #[from]
+        source: VaultError,
+    },
     #[error("fail to parse `{file:?}`")]
     FailToParse {
         #[source]
         source: EnvReaderError,
         file: PathBuf,
     },
-    #[error("env var `{0}` not found in `{1:?}`")]
-    EnvVarNotFound(String, PathBuf),
+    #[error("env var `{0}` not found in `{1}`")]
+    EnvVarNotFound(String, EnvLocation),
     #[error("env file `{0:?}` has no file name")]
     EnvFileHasNoFileName(PathBuf),
     #[error("env file `{0:?}` has an empty file name")]
@@ -0,0 +1,47 @@
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+
+use crate::env_file::Result;
+use crate::vault;
+use crate::vault::VaultSource;
+
+/// Where an env is stored : a file, or a secret of a vault.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub enum EnvLocation {
+    File(PathBuf),
+    /// Env name in the vault source.
+    Vault(VaultSource, String),
+}
+
+impl EnvLocation {
+    /// An env not found is `false`, a failing vault request is an error.
+    pub fn exists(&self) -> Result<bool> {
+        match self {
+            EnvLocation::File(file) => Ok(file.exists()),
+            EnvLocation::Vault(source, env_name) => Ok(vault::env_exists(source, env_name)?),
+        }
+    }
+
+    /// File of the env, `None` in a vault.
+    pub fn file(&self) -> Option<&PathBuf> {
+        match self {
+            EnvLocation::File(file) => Some(file),
+            EnvLocation::Vault(_, _) => None,
+        }
+    }
+}
+
+impl Display for EnvLocation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EnvLocation::File(file) => write!(f, "{}", file.to_string_lossy()),
+            EnvLocation::Vault(source, env_name) => write!(f, "{}", source.env_url(env_name)),
+        }
+    }
+}
+
+impl From<PathBuf> for EnvLocation {
+    fn from(file: PathBuf) -> Self {
+        EnvLocation::File(file)
+    }
+}
@@ -8,28 +8,36 @@ use std::path::{Path, PathBuf};
 pub use comment::Comment;
 pub use diff::EnvDiffController;
 pub use error::{EnvError, EnvReaderError};
+pub use location::EnvLocation;
 pub use read_dir::read_dir;
+pub use scan::{is_secret_value, Finding, SecretKind, ALLOW_ANNOTATION};
 pub use var::Var;
 
 use crate::env_file::entry::Entry;
 use crate::env_file::iter::EnvIterator;
 use crate::utils::write_all::write_all_dir;
+use crate::vault;
 
 mod comment;
 mod diff;
 mod entry;
 mod error;
 mod iter;
+mod location;
 mod read_dir;
 mod recent;
+mod scan;
 mod var;
 
+/// Comment to put in the env file header (before the first var) to protect the env.
+pub const PROTECTED_ANNOTATION: &str = "sht:protected";
+
 pub type Result<T> = std::result::Result<T, EnvError>;
 pub type ResultParse<T> = std::result::Result<T, EnvReaderError>;
 
 #[derive(Debug, Clone, Eq)]
 pub struct Env {
-    file: PathBuf,
+    location: EnvLocation,
     entries: Vec<Entry>,
 }
 
@@ -44,26 +52,30 @@ impl Display for Env {
 
 impl PartialEq for Env {
     fn eq(&self, other: &Self) -> bool {
-        self.file.eq(&other.file)
+        self.location.eq(&other.location)
     }
 }
 
 impl PartialOrd for Env {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.file.partial_cmp(&other.file)
+        self.location.partial_cmp(&other.location)
     }
 }
 
 impl Ord for Env {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.file.cmp(&other.file)
+        self.location.cmp(&other.location)
     }
 }
 
 impl Env {
     pub fn new(file: PathBuf) -> Self {
+        Self::from_location(EnvLocation::File(file))
+    }
+
+    pub fn from_location(location: EnvLocation) -> Self {
         Self {
-            file,
+            location,
             entries: vec![],
         }
     }
@@ -115,7 +127,7 @@ impl Env {
             })
             .ok_or(EnvError::EnvVarNotFound(
                 name.as_ref().to_owned(),
-                self.file.clone(),
+                self.location.clone(),
             ))
     }
 
@@ -128,10 +140,63 @@ impl Env {
             .map_or(false, |var| var.value() == value.as_ref())
     }
 
+    /// Update the var value if it exists, add it otherwise.
+    pub fn set<N, V>(&mut self, name: N, value: V)
+    where
+        N: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let var = self.entries.iter_mut().find_map(|entry| match entry {
+            Entry::Var(var) if var.name() == name.as_ref() => Some(var),
+            _ => None,
+        });
+        if let Some(var) = var {
+            var.set_value(value.as_ref());
+        } else {
+            self.add(name, value);
+        }
+    }
+
+    /// Keep only vars matching `f`, comments and empty lines are kept.
+    pub fn retain_vars<F>(&mut self, f: F)
+    where
+        F: Fn(&Var) -> bool,
+    {
+        self.entries.retain(|entry| match entry {
+            Entry::Var(var) => f(var),
+            _ => true,
+        });
+    }
+
+    pub fn is_protected(&self) -> bool {
+        self.entries
+            .iter()
+            .take_while(|entry| !matches!(entry, Entry::Var(_)))
+            .any(|entry| match entry {
+                Entry::Comment(comment) => comment.value().trim() == PROTECTED_ANNOTATION,
+                _ => false,
+            })
+    }
+
+    /// Add the protected annotation on top of the file.
+    pub fn set_protected(&mut self) {
+        if !self.is_protected() {
+            let comment = Comment::from_line(&format!("# {}", PROTECTED_ANNOTATION)).unwrap();
+            self.entries.insert(0, Entry::Comment(comment));
+        }
+    }
+
     pub fn add_empty_line(&mut self) {
         self.entries.append(&mut vec![Entry::Empty]);
     }
 
+    pub fn read(location: &EnvLocation) -> Result<Self> {
+        match location {
+            EnvLocation::File(file) => Self::from_file_reader(file),
+            EnvLocation::Vault(source, env_name) => Ok(vault::read_env(source, env_name)?),
+        }
+    }
+
     pub fn from_file_reader<P: AsRef<Path>>(file: P) -> Result<Self> {
         let file = file.as_ref().to_path_buf();
         let concrete_file = OpenOptions::new().read(true).open(&file)?;
@@ -164,31 +229,48 @@ impl Env {
     }
 
     pub fn set_file(&mut self, file: PathBuf) {
-        self.file = file;
+        self.location = EnvLocation::File(file);
+    }
+
+    pub fn set_location(&mut self, location: EnvLocation) {
+        self.location = location;
     }
 
-    pub fn file(&self) -> &PathBuf {
-        &self.file
+    pub fn location(&self) -> &EnvLocation {
+        &self.location
+    }
+
+    /// File of the env, `None` in a vault.
+    pub fn file(&self) -> Option<&PathBuf> {
+        self.location.file()
     }
 
     pub fn file_name(&self) -> Result<String> {
-        if let Some(file_name) = self.file.file_name() {
+        let file = match &self.location {
+            EnvLocation::File(file) => file,
+            EnvLocation::Vault(_, env_name) => return Ok(format!(".{}", env_name)),
+        };
+        if let Some(file_name) = file.file_name() {
             let file_name = file_name.to_str().unwrap().to_string();
             Ok(file_name)
         } else {
-            Err(EnvError::EnvFileHasNoFileName(self.file.to_owned()))
+            Err(EnvError::EnvFileHasNoFileName(file.to_owned()))
         }
     }
 
     pub fn name(&self) -> Result<String> {
+        let file = match &self.location {
+            EnvLocation::File(file) => file,
+            EnvLocation::Vault(_, env_name) => return Ok(env_name.clone()),
+        };
         let file_name = self.file_name()?;
         if file_name
             .chars()
             .next()
-            .ok_or(EnvError::EnvFileNameIsEmpty(self.file.clone()))?
+            .ok_or(EnvError::EnvFileNameIsEmpty(file.clone()))?
             != '.'
         {
-            return Err(EnvError::EnvFileNameIncorrect(self.file.clone()));
+            return Err(EnvError::EnvFileNameIncorrect(file.clone()));
         }
         let name = file_name.trim_start_matches('.');
         return Ok(name.to_string());
@@ -199,20 +281,25 @@ impl Env {
     }
 
     pub fn save(&self) -> Result<()> {
-        let content = self.to_string();
-        write_all_dir(&self.file, content)?;
+        match &self.location {
+            EnvLocation::File(file) => write_all_dir(file, self.to_string())?,
+            EnvLocation::Vault(source, env_name) => vault::write_env(source, env_name, self)?,
+        }
         Ok(())
     }
 
     // TODO: test
     pub fn remove(&self) -> Result<()> {
-        remove_file(&self.file)?;
+        match &self.location {
+            EnvLocation::File(file) => remove_file(file)?,
+            EnvLocation::Vault(source, env_name) => vault::remove_env(source, env_name)?,
+        }
         Ok(())
     }
 
     pub fn copy(&self, file: PathBuf) -> Self {
         Self {
-            file,
+            location: EnvLocation::File(file),
             entries: self.entries.clone(),
         }
     }
@@ -220,10 +307,7 @@ impl Env {
 
 impl From<PathBuf> for Env {
     fn from(file: PathBuf) -> Self {
-        Self {
-            file,
-            entries: vec![],
-        }
+        Self::new(file)
     }
 }
 
@@ -287,6 +371,38 @@ mod tests {
         assert!(!is_set);
     }
 
+    #[test]
+    fn is_protected() {
+        let mut content = Cursor::new("# my env\n# sht:protected\n\nVAR1=VALUE1\n");
+        let mut env = Env::new("".into());
+        env.entries_from_reader(&mut content).unwrap();
+        assert!(env.is_protected());
+
+        let mut content = Cursor::new("VAR1=VALUE1\n# sht:protected\n");
+        let mut env = Env::new("".into());
+        env.entries_from_reader(&mut content).unwrap();
+        assert!(!env.is_protected());
+
+        env.set_protected();
+        assert!(env.is_protected());
+        assert_eq!(
+            env.to_string(),
+            "# sht:protected\nVAR1=VALUE1\n# sht:protected\n"
+        );
+    }
+
+    #[test]
+    fn set_and_retain_vars() {
+        let mut env = Env::new("".into());
+        env.add("name1", "value1");
+        env.add_empty_line();
+        env.add("name2", "value2");
+        env.set("name1", "value1.1");
+        env.set("name3", "value3");
+        env.retain_vars(|var| var.name() != "name2");
+        assert_eq!(env.to_string(), "name1=value1.1\n\nname3=value3\n");
+    }
+
     #[test]
     fn empty() {
         let mut content = Cursor::new(br#""#);
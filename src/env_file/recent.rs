@@ -3,8 +3,11 @@ use crate::utils::file_time::{modification_time, ModificationTime};
 use anyhow::{Context, Result};
 
 fn env_file_time(env: &Env) -> ModificationTime {
-    let file = env.file();
-    modification_time(file)
+    match env.file() {
+        Some(file) => modification_time(file),
+        // Vault envs have no modification time, local envs are always preferred.
+        None => ModificationTime::zero(),
+    }
 }
 
 impl Env {
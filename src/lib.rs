@@ -6,7 +6,9 @@ pub mod cfg;
 pub mod cli;
 pub mod env_file;
 pub mod run_file;
+pub mod ssm;
 pub mod template;
 pub mod utils;
+pub mod vault;
 
 pub const BIN_NAME: &'static str = "sht";
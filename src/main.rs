@@ -3,6 +3,7 @@ extern crate anyhow;
 extern crate log;
 
 use std::env;
+use std::process;
 
 use anyhow::Result;
 use clap::{App, AppSettings, Arg, ArgGroup, SubCommand};
@@ -16,6 +17,7 @@ use short::{
 
 use short::cli::commands;
 use short::cli::commands::DEFAULT_SHOW_FORMAT;
+use short::cli::error::CliError;
 use short::cli::terminal::emoji;
 use short::run_file::kind::Kind;
 
@@ -32,7 +34,14 @@ fn main() -> Result<()> {
     info!("BIN_NAME {}", BIN_NAME);
     info!("VERSION v{}", VERSION);
 
-    Ok(run()?)
+    if let Err(err) = run() {
+        // Exit like the run file, its output already tell what happens.
+        if let Some(CliError::RunExitStatus(status)) = err.downcast_ref::<CliError>() {
+            process::exit(*status);
+        }
+        return Err(err);
+    }
+    Ok(())
 }
 
 fn run() -> Result<()> {
@@ -88,6 +97,120 @@ fn run() -> Result<()> {
             .multiple(false),
     ];
 
+    let run_subcommand = SubCommand::with_name("run")
+        .about("Run setup [ARGS...].")
+        // The help also shows the `arguments` of the setup.
+        .setting(AppSettings::DisableHelpFlags)
+        .setting(AppSettings::DisableVersion)
+        .arg(
+            Arg::with_name("help")
+                .long("help")
+                .short("h")
+                .help("Prints help information, with the arguments declared by the setup."),
+        )
+        .arg(
+            Arg::with_name("args")
+                .help("All arguments will be pass to the runnable script as argument. When the first one is a command of the setup, it runs this command.")
+                .index(1)
+                .multiple(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("log")
+                .long("log")
+                .takes_value(true)
+                .help("Append stdout and stderr to a log file, with timestamps."),
+        )
+        .arg(
+            Arg::with_name("jobs")
+                .long("jobs")
+                .short("j")
+                .takes_value(true)
+                .conflicts_with_all(&["watch", "dry_run"])
+                .help("Run up to <jobs> independent dependencies at the same time [default: 1]."),
+        )
+        .arg(
+            Arg::with_name("timeout")
+                .long("timeout")
+                .takes_value(true)
+                .conflicts_with("watch")
+                .help("Stop the run file when it runs longer, e.g. 10m [default: setup `timeout`]."),
+        )
+        .arg(
+            Arg::with_name("retries")
+                .long("retries")
+                .takes_value(true)
+                .conflicts_with("watch")
+                .help("Run again the run file on a non-zero exit [default: setup `retries` or 0]."),
+        )
+        .arg(
+            Arg::with_name("retry_backoff")
+                .long("retry-backoff")
+                .takes_value(true)
+                .conflicts_with("watch")
+                .help("Delay before the first retry, doubled at each retry [default: setup `retry_backoff` or 1s]."),
+        )
+        .arg(
+            Arg::with_name("dry_run")
+                .long("dry-run")
+                .conflicts_with_all(&["watch", "log"])
+                .help("Print the run file, its working directory, args and vars, without running it."),
+        )
+        .arg(
+            Arg::with_name("json")
+                .long("json")
+                .requires("dry_run")
+                .help("Print the dry run as JSON."),
+        )
+        .arg(
+            Arg::with_name("watch")
+                .long("watch")
+                .short("w")
+                .conflicts_with("log")
+                .help("Restart when the env file, short.yaml or the run file changes."),
+        )
+        .arg(
+            Arg::with_name("stop_signal")
+                .long("stop-signal")
+                .takes_value(true)
+                .requires("watch")
+                .help("Signal sent to stop the run file before a restart [default: TERM]."),
+        )
+        .arg(
+            Arg::with_name("grace_period")
+                .long("grace-period")
+                .takes_value(true)
+                .requires("watch")
+                .help("Time to wait for the run file to stop before to kill it [default: 5s]."),
+        )
+        .arg(
+            Arg::with_name("debounce")
+                .long("debounce")
+                .takes_value(true)
+                .requires("watch")
+                .help("Time without changes to wait before a restart [default: 300ms]."),
+        )
+        .arg(
+            Arg::with_name("all_envs")
+                .long("all-envs")
+                .conflicts_with_all(&["environment", "watch", "dry_run"])
+                .help("Run once per env of the setup."),
+        )
+        .arg(
+            Arg::with_name("parallel")
+                .long("parallel")
+                .conflicts_with_all(&["watch", "dry_run"])
+                .help("With several envs, run them at the same time."),
+        )
+        .arg(setup_arg.clone())
+        .arg(
+            environment_arg
+                .clone()
+                .multiple(true)
+                .number_of_values(1)
+                .help("Environment name, repeat it to run once per env"),
+        );
+
     let app = App::new(format!("{}sht", emoji::SHORT))
         .version(VERSION)
         .author("Vincent Herlemont <vincentherl@leszeros.com>")
@@ -176,19 +299,201 @@ fn run() -> Result<()> {
             ),
 
         )
+        .subcommand(run_subcommand.clone())
+        .subcommand(
+            SubCommand::with_name("up")
+                .about("Start the processes of a group, declared in `groups` of short.yaml.")
+                .arg(
+                    Arg::with_name("group")
+                        .index(1)
+                        .help("Group name, required when several groups are declared."),
+                )
+                .arg(
+                    Arg::with_name("keep_running")
+                        .long("keep-running")
+                        .help("Don't stop the other processes when one exits [default: group `stop_on_exit`]."),
+                )
+                .arg(
+                    Arg::with_name("import")
+                        .long("import")
+                        .takes_value(true)
+                        .value_name("procfile")
+                        .help("Import a Procfile as commands of the setup and a group [default group: procfile]."),
+                )
+                .arg(setup_arg.clone())
+                .arg(environment_arg.clone()),
+        )
         .subcommand(
-            SubCommand::with_name("run")
-                .about("Run setup [ARGS...].")
+            SubCommand::with_name("start")
+                .about("Start setup [ARGS...] in background.")
                 .arg(
                     Arg::with_name("args")
-                        .help("All arguments will be pass to the runnable script as argument.")
+                        .help("All arguments will be pass to the runnable script as argument. When the first one is a command of the setup, it runs this command.")
+                        .index(1)
+                        .multiple(true)
+                        .takes_value(true),
+                )
+                .arg(setup_arg.clone())
+                .arg(environment_arg.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name("ps")
+                .about("List the setups started in background.")
+                .arg(
+                    Arg::with_name("clean")
+                        .long("clean")
+                        .help("Remove the ones no longer running."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("logs")
+                .about("Display the log of a setup started in background.")
+                .arg(
+                    Arg::with_name("pid")
+                        .index(1)
+                        .help("Pid listed by \"sht ps\" [default: current setup and env]."),
+                )
+                .arg(
+                    Arg::with_name("lines")
+                        .long("lines")
+                        .short("n")
+                        .takes_value(true)
+                        .help("Number of last lines [default: 20]."),
+                )
+                .arg(
+                    Arg::with_name("follow")
+                        .long("follow")
+                        .short("f")
+                        .help("Print the new lines until it stops."),
+                )
+                .arg(setup_arg.clone())
+                .arg(environment_arg.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name("stop")
+                .about("Stop a setup started in background.")
+                .arg(
+                    Arg::with_name("pid")
+                        .index(1)
+                        .help("Pid listed by \"sht ps\" [default: current setup and env]."),
+                )
+                .arg(
+                    Arg::with_name("stop_signal")
+                        .long("stop-signal")
+                        .takes_value(true)
+                        .help("Signal sent to stop it [default: TERM]."),
+                )
+                .arg(
+                    Arg::with_name("grace_period")
+                        .long("grace-period")
+                        .takes_value(true)
+                        .help("Time to wait for it to stop before to kill it [default: 5s]."),
+                )
+                .arg(setup_arg.clone())
+                .arg(environment_arg.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name("exec")
+                .about("Execute a command with the vars of the setup [-- COMMAND ARGS...].")
+                .setting(AppSettings::TrailingVarArg)
+                .arg(
+                    Arg::with_name("command")
+                        .help("Command found through PATH and its arguments, run in the current directory.")
                         .index(1)
                         .multiple(true)
+                        .required(true)
                         .takes_value(true),
                 )
                 .arg(setup_arg.clone())
                 .arg(environment_arg.clone()),
         )
+        .subcommand(
+            SubCommand::with_name("shell")
+                .about("Spawn $SHELL with the vars of the setup.")
+                .arg(setup_arg.clone())
+                .arg(environment_arg.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name("env")
+                .about("Export the vars of the setup to the current shell.")
+                .arg(
+                    Arg::with_name("export")
+                        .long("export")
+                        .help("Print the shell commands that export the vars, to eval."),
+                )
+                .arg(
+                    Arg::with_name("hook")
+                        .long("hook")
+                        .help("Print the shell hook that exports the vars when entering a project."),
+                )
+                .group(
+                    ArgGroup::with_name("env_action")
+                        .args(&["export", "hook"])
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("shell")
+                        .long("shell")
+                        .takes_value(true)
+                        .possible_values(&["bash", "zsh", "fish", "sh"])
+                        .help("Shell syntax, $SHELL by default."),
+                )
+                .arg(setup_arg.clone())
+                .arg(environment_arg.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name("history")
+                .about("Display the history of run and exec.")
+                .arg(
+                    Arg::with_name("setup")
+                        .long("setup")
+                        .short("s")
+                        .takes_value(true)
+                        .help("Only entries of this setup"),
+                )
+                .arg(
+                    Arg::with_name("environment")
+                        .long("env")
+                        .short("e")
+                        .takes_value(true)
+                        .help("Only entries of this environment"),
+                )
+                .arg(
+                    Arg::with_name("limit")
+                        .long("limit")
+                        .short("n")
+                        .takes_value(true)
+                        .help("Only the last <limit> entries"),
+                )
+                .arg(Arg::with_name("json").long("json").help("Print as JSON"))
+                .subcommand(
+                    SubCommand::with_name("rerun")
+                        .about("Run again an entry of the history.")
+                        .arg(
+                            Arg::with_name("id")
+                                .help("Id of the entry")
+                                .index(1)
+                                .required(true),
+                        ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("check-requirements")
+                .about("Check the tools, vars and files required by the setup.")
+                .arg(setup_arg.clone())
+                .arg(environment_arg.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name("allow")
+                .about("Approve the current content of the setup run file.")
+                .arg(setup_arg.clone())
+                .arg(environment_arg.clone())
+                .arg(
+                    Arg::with_name("command")
+                        .index(1)
+                        .help("Approve a command of the setup instead of its run file."),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("rename")
                 .about("Rename setup.")
@@ -277,7 +582,7 @@ fn run() -> Result<()> {
                 .setting(AppSettings::ArgRequiredElseHelp)
                 .arg(
                     Arg::with_name("env_dir")
-                        .help("Private env directory path, have to be outside of your project directory.")
+                        .help("Private env directory path, have to be outside of your project directory. Or vault source like \"vault://secret/data/<path>/{env}\".")
                         .index(1),
                 )
                 .arg(
@@ -323,7 +628,7 @@ fn run() -> Result<()> {
                         .short("f")
                         .takes_value(true)
                         .min_values(0)
-                        .help(format!("Display format \"{}\" by default.", DEFAULT_SHOW_FORMAT).as_str())
+                        .help(format!("Display format \"{}\" by default, available markers {{setup}}, {{env}} and {{protected}}.", DEFAULT_SHOW_FORMAT).as_str())
                 )
                 .arg(
                     Arg::with_name("display_setup")
@@ -361,6 +666,38 @@ fn run() -> Result<()> {
             .about("Display/Diff environment variables.")
             .arg(setup_arg.clone())
             .arg(environments_arg.clone())
+        )
+        .subcommand(
+            SubCommand::with_name("ssm")
+                .about("Push/Pull env to/from AWS SSM Parameter Store.")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("push")
+                        .about("Push env vars as parameters, secrets as \"SecureString\".")
+                        .arg(setup_arg.clone())
+                        .arg(environment_arg.clone()),
+                )
+                .subcommand(
+                    SubCommand::with_name("pull")
+                        .about("Pull parameters into the env file.")
+                        .arg(setup_arg.clone())
+                        .arg(environment_arg.clone())
+                        .arg(
+                            Arg::with_name("yes")
+                                .long("yes")
+                                .short("y")
+                                .help("Apply changes without confirmation."),
+                        ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("scan")
+                .about("Scan public envs of all setups for secrets.")
+                .arg(
+                    Arg::with_name("install_hook")
+                        .long("install-hook")
+                        .help("Install the scan as git pre-commit hook."),
+                ),
         ).get_matches();
 
     #[cfg(all(feature = "reqwest", feature = "versions"))]
@@ -383,7 +720,39 @@ fn run() -> Result<()> {
     } else if let Some(args) = app.subcommand_matches("generate") {
         commands::generate(&args)?;
     } else if let Some(args) = app.subcommand_matches("run") {
-        commands::run(&args)?;
+        if args.is_present("help") {
+            run_subcommand
+                .bin_name(format!("{} run", BIN_NAME))
+                .print_help()?;
+            println!();
+            if let Err(err) = commands::run_help(&args) {
+                debug!("no arguments of the setup : {}", err);
+            }
+        } else {
+            commands::run(&args)?;
+        }
+    } else if let Some(args) = app.subcommand_matches("up") {
+        commands::up(&args)?;
+    } else if let Some(args) = app.subcommand_matches("start") {
+        commands::start(&args)?;
+    } else if let Some(args) = app.subcommand_matches("ps") {
+        commands::ps(&args)?;
+    } else if let Some(args) = app.subcommand_matches("logs") {
+        commands::logs(&args)?;
+    } else if let Some(args) = app.subcommand_matches("stop") {
+        commands::stop(&args)?;
+    } else if let Some(args) = app.subcommand_matches("exec") {
+        commands::exec(&args)?;
+    } else if let Some(args) = app.subcommand_matches("shell") {
+        commands::shell(&args)?;
+    } else if let Some(args) = app.subcommand_matches("env") {
+        commands::env(&args)?;
+    } else if let Some(args) = app.subcommand_matches("history") {
+        commands::history(&args)?;
+    } else if let Some(args) = app.subcommand_matches("check-requirements") {
+        commands::check_requirements(&args)?;
+    } else if let Some(args) = app.subcommand_matches("allow") {
+        commands::allow(&args)?;
     } else if let Some(args) = app.subcommand_matches("ls") {
         commands::ls(&args)?;
     } else if let Some(args) = app.subcommand_matches("rename") {
@@ -406,6 +775,10 @@ fn run() -> Result<()> {
         commands::vars(args)?;
     } else if let Some(args) = app.subcommand_matches("envs") {
         commands::envs(args)?;
+    } else if let Some(args) = app.subcommand_matches("ssm") {
+        commands::ssm(args)?;
+    } else if let Some(args) = app.subcommand_matches("scan") {
+        commands::scan(args)?;
     }
 
     Ok(())
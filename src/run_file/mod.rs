@@ -1,23 +1,40 @@
-use colored::*;
-use std::fmt::Write as FmtWrite;
+use std::fs::OpenOptions;
 use std::io;
-use std::io::{BufRead, BufReader, BufWriter, Write};
-use std::path::PathBuf;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
 use std::process;
-use std::process::{Command, Stdio};
-use std::sync::{Arc, Mutex};
+use std::process::{Child, Command, Stdio};
 use std::thread;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
 
+use crossterm::tty::IsTty;
+use log::*;
+
+use run_log::{RunLog, Stream, StreamLog};
+use signal::{
+    restore_foreground_process_group, set_foreground_process_group, set_session, wait_foreground,
+    SignalForwarder, Timeout,
+};
+
 pub use file::{set_exec_permision, File};
+pub use signal::{
+    exit_code, is_alive, kill_process_group, parse_signal, process_start_time, set_process_group,
+    SignalListener,
+};
+use stdin::claim_stdin;
 pub use var::{
-    generate_array_env_var, generate_env_var, generate_env_vars, var_name, EnvValue, EnvVar,
-    ENV_ENVIRONMENT_VAR, ENV_SETUP_VAR,
+    argument_var_name, generate_array_env_var, generate_env_var, generate_env_vars, var_name,
+    EnvValue, EnvVar, ENV_ENVIRONMENT_VAR, ENV_EXIT_STATUS_VAR, ENV_SETUP_VAR,
 };
 
 mod file;
 pub mod kind;
+mod run_log;
+mod signal;
+mod stdin;
 mod var;
 
 #[derive(Debug)]
@@ -25,6 +42,10 @@ pub struct Output {
     pub status: i32,
     pub stdout: String,
     pub stderr: String,
+    /// Stopped because it has exceeded its timeout.
+    pub timed_out: bool,
+    /// A signal received by short has been forwarded to it (Ctrl-C, ...).
+    pub interrupted: bool,
 }
 
 impl Output {
@@ -33,6 +54,8 @@ impl Output {
             status: 0,
             stdout: "".into(),
             stderr: "".into(),
+            timed_out: false,
+            interrupted: false,
         }
     }
 }
@@ -43,11 +66,53 @@ impl From<process::Output> for Output {
             status: output.status.code().map_or(0, |code| code),
             stderr: String::from_utf8_lossy(output.stderr.as_ref()).into_owned(),
             stdout: String::from_utf8_lossy(output.stdout.as_ref()).into_owned(),
+            timed_out: false,
+            interrupted: false,
         }
     }
 }
 
-pub fn run_as_stream(file: &PathBuf, vars: &Vec<EnvVar>, args: &Vec<String>) -> Result<Output> {
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum RunMode {
+    /// The run file inherits the terminal, required by interactive programs (editors, prompts, ...).
+    Interactive,
+    /// Stdin, stdout and stderr are piped through short.
+    Stream,
+    /// Stdin is closed, each line of stdout and stderr is written after the prefix, to tell
+    /// apart the outputs of runs at the same time.
+    Prefixed(String),
+}
+
+impl RunMode {
+    /// Interactive when stdin is a terminal.
+    pub fn detect() -> Self {
+        if io::stdin().is_tty() {
+            RunMode::Interactive
+        } else {
+            RunMode::Stream
+        }
+    }
+}
+
+/// Run the command, it is stopped when it runs longer than `timeout`. The outputs are not
+/// captured, `stdout` and `stderr` of the returned `Output` are empty.
+pub fn run(
+    mode: RunMode,
+    command: Command,
+    log: Option<&PathBuf>,
+    timeout: Option<Duration>,
+) -> Result<Output> {
+    debug!("run `{}` in mode {:?}", command_line(&command), mode);
+    match mode {
+        // The outputs of the terminal can't be logged.
+        RunMode::Interactive if log.is_none() => run_interactive(command, timeout),
+        RunMode::Prefixed(prefix) => stream(command, log, timeout, Some(prefix), false),
+        _ => stream(command, log, timeout, None, false),
+    }
+}
+
+/// Command of a run file, it runs in the directory of the file.
+pub fn file_command(file: &PathBuf, vars: &[EnvVar], args: &[String]) -> Result<Command> {
     let file = file.canonicalize()?;
     let mut command = Command::new(&file);
 
@@ -59,84 +124,353 @@ pub fn run_as_stream(file: &PathBuf, vars: &Vec<EnvVar>, args: &Vec<String>) ->
         command.current_dir(parent);
     }
 
+    command.args(args);
+    Ok(command)
+}
+
+/// Same as `file_command`, the file is run by `interpreter`, e.g. `python3` or `node --inspect`,
+/// so it doesn't need to be executable.
+pub fn interpreted_file_command(
+    interpreter: &str,
+    file: &Path,
+    vars: &[EnvVar],
+    args: &[String],
+) -> Result<Command> {
+    let file = file.canonicalize()?;
+    let mut words = interpreter.split_whitespace();
+    let program = match words.next() {
+        Some(program) => program,
+        None => bail!("empty interpreter for {:?}", file),
+    };
+    let mut command = program_command(program, vars, &words.map(String::from).collect::<Vec<_>>());
+    command.arg(&file);
+
+    if let Some(parent) = file.parent() {
+        command.current_dir(parent);
+    }
+
+    command.args(args);
+    Ok(command)
+}
+
+/// Command of a program found through `PATH`, it runs in the current directory.
+pub fn program_command(program: &str, vars: &[EnvVar], args: &[String]) -> Command {
+    let mut command = Command::new(program);
+
+    for env_var in vars.iter() {
+        command.env(env_var.var().to_env_var(), env_var.env_value().to_string());
+    }
+
+    command.args(args);
+    command
+}
+
+/// Command line run by `sh` in `dir`, the args are its positional parameters (`"$@"`).
+pub fn inline_command(line: &str, dir: &Path, vars: &[EnvVar], args: &[String]) -> Command {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(line).arg("sh");
+
+    for env_var in vars.iter() {
+        command.env(env_var.var().to_env_var(), env_var.env_value().to_string());
+    }
+
+    command.current_dir(dir);
+    command.args(args);
+    command
+}
+
+/// Program and arguments, without the env vars that can hold secrets.
+fn command_line(command: &Command) -> String {
+    let mut line = command.get_program().to_string_lossy().into_owned();
+    for arg in command.get_args() {
+        line.push(' ');
+        line.push_str(&arg.to_string_lossy());
+    }
+    line
+}
+
+/// Run with the stdio of short, stdout and stderr are not captured.
+pub fn run_interactive(mut command: Command, timeout: Option<Duration>) -> Result<Output> {
+    set_foreground_process_group(&mut command);
+    let mut signal_forwarder = SignalForwarder::new()?;
+
+    let child = command
+        .stdout(Stdio::inherit())
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .context(format!("command `{}` fail", command_line(&command)))?;
+    signal_forwarder.forward_to(child.id());
+    let timeout = timeout.map(|timeout| Timeout::start(child.id(), timeout));
+
+    let exit_status = wait_foreground(child.id());
+    restore_foreground_process_group();
+    let timed_out = timeout.is_some_and(|timeout| timeout.cancel());
+    let interrupted = signal_forwarder.received();
+    drop(signal_forwarder);
+    let exit_status = exit_status.context(format!("fail to wait `{}`", command_line(&command)))?;
+
+    let mut output = Output::new();
+    output.status = exit_code(&exit_status);
+    output.timed_out = timed_out;
+    output.interrupted = interrupted;
+    Ok(output)
+}
+
+/// Run with stdin, stdout and stderr piped through short, the outputs are captured byte by byte
+/// and written as is to the stdout and stderr of short. When `log` is set, the outputs are also
+/// appended to it.
+pub fn run_as_stream(
+    command: Command,
+    log: Option<&PathBuf>,
+    timeout: Option<Duration>,
+) -> Result<Output> {
+    stream(command, log, timeout, None, true)
+}
+
+/// Stdin of short is given to the child only when it is not prefixed and no other run has it. The
+/// outputs are kept in the returned `Output` only when `capture` is set.
+fn stream(
+    mut command: Command,
+    log: Option<&PathBuf>,
+    timeout: Option<Duration>,
+    prefix: Option<String>,
+    capture: bool,
+) -> Result<Output> {
+    let log = match log {
+        Some(log) => Some(RunLog::open(log)?),
+        None => None,
+    };
+
+    set_process_group(&mut command);
+    let mut signal_forwarder = SignalForwarder::new()?;
+
+    let stdin_claim = match prefix {
+        Some(_) => None,
+        None => claim_stdin(),
+    };
+    let stdin = match stdin_claim {
+        Some(_) => Stdio::piped(),
+        None => Stdio::null(),
+    };
     let mut child = command
         .stdout(Stdio::piped())
-        .stdin(Stdio::piped())
+        .stdin(stdin)
         .stderr(Stdio::piped())
-        .args(args)
         .spawn()
-        .context(format!("command {} fail", &file.to_string_lossy()))?;
-
-    let mut command_stdin = child.stdin.take().expect("fail to get stdin");
-    let read_stdin = thread::spawn(move || loop {
-        // /!\ Manually tested
-        let mut buff_writer = BufWriter::new(&mut command_stdin);
-        let mut buffer = String::new();
-        io::stdin().read_line(&mut buffer).unwrap();
-        buff_writer.write_all(buffer.as_str().as_bytes()).unwrap();
+        .context(format!("command `{}` fail", command_line(&command)))?;
+    signal_forwarder.forward_to(child.id());
+    let timeout = timeout.map(|timeout| Timeout::start(child.id(), timeout));
+    if let Some(log) = &log {
+        log.event(format!("run {}", command_line(&command)).as_str())?;
+    }
+
+    if let (Some(stdin_claim), Some(command_stdin)) = (&stdin_claim, child.stdin.take()) {
+        stdin_claim.attach(command_stdin);
+    }
+
+    let stdout = child.stdout.take().expect("fail to get stdout");
+    let stdout_log = log.as_ref().map(|log| log.stream(Stream::Stdout));
+    let stdout_prefix = prefix.clone();
+    let read_stdout = thread::spawn(move || match stdout_prefix {
+        Some(prefix) => prefix_lines(stdout, io::stdout(), &prefix, stdout_log, capture),
+        None => pipe(stdout, io::stdout(), stdout_log, capture),
     });
 
-    let output = Arc::new(Mutex::new(Output::new()));
-
-    let read_stdout = if let Some(stdout) = child.stdout.take() {
-        let output = Arc::clone(&output);
-        Some(thread::spawn(move || {
-            let buf = BufReader::new(stdout);
-            let mut buffer = String::new();
-            for line in buf.lines() {
-                let line = line.unwrap();
-                writeln!(&mut buffer, "{}", line).unwrap();
-                println!("{}", line.normal().clear());
-            }
-            let mut output = output.lock().unwrap();
-            output.stdout = buffer;
-        }))
-    } else {
-        None
-    };
-    let read_err = if let Some(stderr) = child.stderr.take() {
-        let output = Arc::clone(&output);
-        Some(thread::spawn(move || {
-            let buf = BufReader::new(stderr);
-            let mut buffer = String::new();
-            for line in buf.lines() {
-                let line = line.unwrap();
-                writeln!(&mut buffer, "{}", line).unwrap();
-                println!("{}", line.red());
-            }
-            let mut output = output.lock().unwrap();
-            output.stderr = buffer;
-        }))
-    } else {
-        None
-    };
+    let stderr = child.stderr.take().expect("fail to get stderr");
+    let stderr_log = log.as_ref().map(|log| log.stream(Stream::Stderr));
+    let read_stderr = thread::spawn(move || match prefix {
+        Some(prefix) => prefix_lines(stderr, io::stderr(), &prefix, stderr_log, capture),
+        None => pipe(stderr, io::stderr(), stderr_log, capture),
+    });
+
+    let stdout = read_stdout.join().expect("fail to wait read_stdout");
+    let stderr = read_stderr.join().expect("fail to wait read_stderr");
 
-    if let Some(read_err) = read_err {
-        read_err.join().expect("fail to wait read_err");
+    let exit_status = child.wait();
+    drop(stdin_claim);
+    let timed_out = timeout.is_some_and(|timeout| timeout.cancel());
+    let interrupted = signal_forwarder.received();
+    drop(signal_forwarder);
+    let exit_status = exit_status.context(format!("fail to wait `{}`", command_line(&command)))?;
+
+    let output = Output {
+        status: exit_code(&exit_status),
+        stdout: String::from_utf8_lossy(&stdout.context("fail to read stdout")?).into_owned(),
+        stderr: String::from_utf8_lossy(&stderr.context("fail to read stderr")?).into_owned(),
+        timed_out,
+        interrupted,
+    };
+    if let Some(log) = &log {
+        if output.timed_out {
+            log.event("timeout")?;
+        }
+        log.event(format!("exit {}", output.status).as_str())?;
     }
-    if let Some(read_stdout) = read_stdout {
-        read_stdout.join().expect("fail to wait read_stdout");
+    Ok(output)
+}
+
+/// Child spawned by `spawn_prefixed`.
+pub struct PrefixedChild {
+    pub child: Child,
+    outputs: Vec<JoinHandle<io::Result<Vec<u8>>>>,
+}
+
+impl PrefixedChild {
+    /// Wait up to `timeout` for the end of the outputs of the exited child, they can be kept open
+    /// by a process left behind by the child.
+    pub fn wait_outputs(&self, timeout: Duration) {
+        let start = Instant::now();
+        while self.outputs.iter().any(|output| !output.is_finished()) && start.elapsed() < timeout {
+            thread::sleep(Duration::from_millis(10));
+        }
     }
-    drop(read_stdin);
+}
+
+/// Spawn in its own process group with stdin closed, each line of stdout and stderr is written
+/// after `prefix`. The outputs are not captured, the child may run for long.
+pub fn spawn_prefixed(mut command: Command, prefix: &str) -> Result<PrefixedChild> {
+    set_process_group(&mut command);
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stdin(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context(format!("command `{}` fail", command_line(&command)))?;
+    debug!("spawn `{}` as {}", command_line(&command), child.id());
+
+    let stdout = child.stdout.take().expect("fail to get stdout");
+    let stdout_prefix = prefix.to_string();
+    let read_stdout =
+        thread::spawn(move || prefix_lines(stdout, io::stdout(), &stdout_prefix, None, false));
+    let stderr = child.stderr.take().expect("fail to get stderr");
+    let stderr_prefix = prefix.to_string();
+    let read_stderr =
+        thread::spawn(move || prefix_lines(stderr, io::stderr(), &stderr_prefix, None, false));
+
+    Ok(PrefixedChild {
+        child,
+        outputs: vec![read_stdout, read_stderr],
+    })
+}
 
-    let exit_status = child.wait().unwrap();
-    {
-        let mut output = output.lock().unwrap();
-        output.status = exit_status.code().unwrap_or_default();
+/// Spawn in a new session detached from short, stdin is closed and stdout and stderr are appended
+/// to `log`. Return the pid of the child, it is also the id of its process group, and its start
+/// time. The start time is read before the child can be reaped, even if it exits at once.
+pub fn spawn_detached(mut command: Command, log: &Path) -> Result<(u32, Option<u64>)> {
+    let run_log = RunLog::open(log)?;
+    run_log.event(format!("start {}", command_line(&command)).as_str())?;
+    let stdout = OpenOptions::new()
+        .append(true)
+        .open(log)
+        .context(format!("fail to open log file {:?}", log))?;
+    let stderr = stdout.try_clone()?;
+
+    set_session(&mut command);
+    let child = command
+        .stdin(Stdio::null())
+        .stdout(stdout)
+        .stderr(stderr)
+        .spawn()
+        .context(format!("command `{}` fail", command_line(&command)))?;
+    let start_time = process_start_time(child.id());
+    debug!(
+        "spawn detached `{}` as {}",
+        command_line(&command),
+        child.id()
+    );
+    Ok((child.id(), start_time))
+}
+
+/// Copy `reader` to `writer` and `log` until the end. The read bytes are only returned when
+/// `capture` is set.
+fn pipe<R, W>(
+    mut reader: R,
+    mut writer: W,
+    mut log: Option<StreamLog>,
+    capture: bool,
+) -> io::Result<Vec<u8>>
+where
+    R: Read,
+    W: Write,
+{
+    let mut output = vec![];
+    let mut buffer = [0; 8192];
+    loop {
+        let len = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(len) => len,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        let bytes = &buffer[..len];
+        writer.write_all(bytes)?;
+        writer.flush()?;
+        if let Some(log) = log.as_mut() {
+            log.write(bytes)?;
+        }
+        if capture {
+            output.extend_from_slice(bytes);
+        }
     }
+    if let Some(log) = log.as_mut() {
+        log.finish()?;
+    }
+    Ok(output)
+}
 
-    let output = Arc::try_unwrap(output).unwrap();
-    let output = output.into_inner().unwrap();
+/// Same as `pipe`, each line is written to `writer` after `prefix`. The read bytes are only
+/// returned when `capture` is set.
+fn prefix_lines<R, W>(
+    reader: R,
+    mut writer: W,
+    prefix: &str,
+    mut log: Option<StreamLog>,
+    capture: bool,
+) -> io::Result<Vec<u8>>
+where
+    R: Read,
+    W: Write,
+{
+    let mut reader = BufReader::new(reader);
+    let mut output = vec![];
+    let mut line = vec![];
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        // Written at once, not to be mixed with the lines of the other runs.
+        let mut prefixed_line = Vec::with_capacity(prefix.len() + line.len() + 2);
+        prefixed_line.extend_from_slice(prefix.as_bytes());
+        prefixed_line.push(b' ');
+        prefixed_line.extend_from_slice(&line);
+        if !line.ends_with(b"\n") {
+            prefixed_line.push(b'\n');
+        }
+        writer.write_all(&prefixed_line)?;
+        writer.flush()?;
+        if let Some(log) = log.as_mut() {
+            log.write(&line)?;
+        }
+        if capture {
+            output.extend_from_slice(&line);
+        }
+    }
+    if let Some(log) = log.as_mut() {
+        log.finish()?;
+    }
     Ok(output)
 }
 
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
+    use std::time::Duration;
 
     use cli_integration_test::IntegrationTestEnvironment;
 
-    use crate::run_file::run_as_stream;
+    use crate::run_file::{file_command, run_as_stream, run_interactive};
 
     #[test]
     fn run_integration_test_stream() {
@@ -151,17 +485,43 @@ echo ERR >> /dev/stderr
         e.setup();
         e.set_exec_permission("run.sh").unwrap();
 
-        let output = run_as_stream(
+        let command = file_command(
             &e.path().unwrap().join(PathBuf::from("run.sh")),
             &vec![],
             &vec![],
         )
         .unwrap();
+        let output = run_as_stream(command, None, None).unwrap();
         assert_eq!(output.stdout, "TEST\n".to_string());
         assert_eq!(output.stderr, "ERR\n".to_string());
         assert_eq!(output.status, 0);
     }
 
+    #[test]
+    fn run_integration_test_stream_timeout() {
+        let mut e = IntegrationTestEnvironment::new("run_integration_test_stream_timeout");
+        e.add_file(
+            "run.sh",
+            r#"#!/bin/bash
+echo START
+sleep 10
+"#,
+        );
+        e.setup();
+        e.set_exec_permission("run.sh").unwrap();
+
+        let command = file_command(
+            &e.path().unwrap().join(PathBuf::from("run.sh")),
+            &vec![],
+            &vec![],
+        )
+        .unwrap();
+        let output = run_as_stream(command, None, Some(Duration::from_millis(500))).unwrap();
+        assert_eq!(output.stdout, "START\n".to_string());
+        assert!(output.timed_out);
+        assert_eq!(output.status, 143);
+    }
+
     #[test]
     fn run_integration_test_stream_with_args() {
         let mut e = IntegrationTestEnvironment::new("run_integration_test");
@@ -174,13 +534,38 @@ echo ARG = $1
         e.setup();
         e.set_exec_permission("run.sh").unwrap();
 
-        let output = run_as_stream(
+        let command = file_command(
             &e.path().unwrap().join(PathBuf::from("run.sh")),
             &vec![],
             &vec!["TEST_ARG".to_string()],
         )
         .unwrap();
+        let output = run_as_stream(command, None, None).unwrap();
         assert_eq!(output.stdout, "ARG = TEST_ARG\n".to_string());
         assert_eq!(output.status, 0);
     }
+
+    #[test]
+    fn run_integration_test_interactive() {
+        let mut e = IntegrationTestEnvironment::new("run_integration_test_interactive");
+        e.add_file(
+            "run.sh",
+            r#"#!/bin/bash
+echo TEST
+exit 4
+"#,
+        );
+        e.setup();
+        e.set_exec_permission("run.sh").unwrap();
+
+        let command = file_command(
+            &e.path().unwrap().join(PathBuf::from("run.sh")),
+            &vec![],
+            &vec![],
+        )
+        .unwrap();
+        let output = run_interactive(command, None).unwrap();
+        assert_eq!(output.stdout, "".to_string());
+        assert_eq!(output.status, 4);
+    }
 }
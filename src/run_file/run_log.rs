@@ -0,0 +1,145 @@
+use std::fmt::{Display, Formatter};
+use std::fs::{create_dir_all, File, OpenOptions};
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use anyhow::{Context, Result};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl Display for Stream {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let stream = match self {
+            Stream::Stdout => "stdout",
+            Stream::Stderr => "stderr",
+        };
+        write!(f, "{}", stream)
+    }
+}
+
+/// Log of a run, each line is prefixed by a timestamp and a tag : the stream or `short` for
+/// the events of the run itself.
+#[derive(Debug, Clone)]
+pub struct RunLog {
+    file: Arc<Mutex<File>>,
+}
+
+impl RunLog {
+    /// Append to `path`, create it if it does not exist.
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent).context(format!("fail to create directory {:?}", parent))?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .context(format!("fail to open log file {:?}", path))?;
+        Ok(Self {
+            file: Arc::new(Mutex::new(file)),
+        })
+    }
+
+    pub fn event(&self, message: &str) -> io::Result<()> {
+        self.write_line("short", message.as_bytes())
+    }
+
+    pub fn stream(&self, stream: Stream) -> StreamLog {
+        StreamLog {
+            log: self.clone(),
+            stream,
+            line: vec![],
+        }
+    }
+
+    fn write_line(&self, tag: &str, line: &[u8]) -> io::Result<()> {
+        let mut entry = format!(
+            "{} [{}] ",
+            humantime::format_rfc3339_millis(SystemTime::now()),
+            tag
+        )
+        .into_bytes();
+        entry.extend_from_slice(line);
+        entry.push(b'\n');
+        let mut file = self.file.lock().unwrap();
+        file.write_all(&entry)
+    }
+}
+
+/// Split the chunks of a stream into lines, a line is logged once complete.
+pub struct StreamLog {
+    log: RunLog,
+    stream: Stream,
+    line: Vec<u8>,
+}
+
+impl StreamLog {
+    pub fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        for byte in bytes {
+            if *byte == b'\n' {
+                self.log
+                    .write_line(self.stream.to_string().as_str(), &self.line)?;
+                self.line.clear();
+            } else {
+                self.line.push(*byte);
+            }
+        }
+        Ok(())
+    }
+
+    /// Log the last line, even without end of line.
+    pub fn finish(&mut self) -> io::Result<()> {
+        if !self.line.is_empty() {
+            self.log
+                .write_line(self.stream.to_string().as_str(), &self.line)?;
+            self.line.clear();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use tempdir::TempDir;
+
+    use crate::run_file::run_log::{RunLog, Stream};
+
+    #[test]
+    fn run_log_lines() {
+        let dir = TempDir::new("run_log_lines").unwrap();
+        let path = dir.path().join("run.log");
+        let log = RunLog::open(&path).unwrap();
+        log.event("start").unwrap();
+        let mut stdout = log.stream(Stream::Stdout);
+        let mut stderr = log.stream(Stream::Stderr);
+        stdout.write(b"li").unwrap();
+        stderr.write(b"error\n").unwrap();
+        stdout.write(b"ne 1\nline 2").unwrap();
+        stdout.finish().unwrap();
+        stderr.finish().unwrap();
+
+        let content = read_to_string(&path).unwrap();
+        let lines: Vec<_> = content
+            .lines()
+            .map(|line| line.splitn(2, ' ').nth(1).unwrap())
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "[short] start",
+                "[stderr] error",
+                "[stdout] line 1",
+                "[stdout] line 2"
+            ]
+        );
+    }
+}
@@ -0,0 +1,468 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::io;
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::process::{Command, ExitStatus};
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use libc::c_int;
+use log::*;
+use signal_hook::iterator::Signals;
+use signal_hook::{SIGHUP, SIGINT, SIGTERM};
+
+/// Signals received by short that are forwarded to the process group of the child.
+pub const FORWARDED_SIGNALS: [c_int; 3] = [SIGINT, SIGTERM, SIGHUP];
+
+/// Exit code used by shells when a process is killed by a signal.
+const SIGNAL_EXIT_CODE_BASE: i32 = 128;
+
+/// Time given to the child to stop after the timeout before to be killed.
+const TIMEOUT_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// Start the child in its own process group, so that signals can be forwarded
+/// to all the processes it spawns.
+pub fn set_process_group(command: &mut Command) {
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setpgid(0, 0) == 0 {
+                Ok(())
+            } else {
+                Err(io::Error::last_os_error())
+            }
+        });
+    }
+}
+
+/// Start the child in a new session, detached from the terminal of short, it is also the leader
+/// of its own process group.
+pub fn set_session(command: &mut Command) {
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setsid() != -1 {
+                Ok(())
+            } else {
+                Err(io::Error::last_os_error())
+            }
+        });
+    }
+}
+
+/// Same as `set_process_group` and also give the terminal to the child, so that it can read
+/// from it and receive the signals typed by the user (Ctrl-C, ...).
+pub fn set_foreground_process_group(command: &mut Command) {
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setpgid(0, 0) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            if libc::isatty(libc::STDIN_FILENO) == 1 {
+                // A background process group is stopped by SIGTTOU when it takes the terminal.
+                libc::signal(libc::SIGTTOU, libc::SIG_IGN);
+                let result = libc::tcsetpgrp(libc::STDIN_FILENO, libc::getpid());
+                libc::signal(libc::SIGTTOU, libc::SIG_DFL);
+                if result != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        });
+    }
+}
+
+/// Take back the terminal once the child of `set_foreground_process_group` has exited.
+pub fn restore_foreground_process_group() {
+    unsafe {
+        if libc::isatty(libc::STDIN_FILENO) == 1 {
+            let handler = libc::signal(libc::SIGTTOU, libc::SIG_IGN);
+            libc::tcsetpgrp(libc::STDIN_FILENO, libc::getpgrp());
+            libc::signal(libc::SIGTTOU, handler);
+        }
+    }
+}
+
+/// Wait the child of `set_foreground_process_group`. When it is stopped from the terminal
+/// (Ctrl-Z), short takes back the terminal and stops too, so that the shell gets it back. Once
+/// short is continued, the child gets the terminal again, if short has it, and is continued.
+pub fn wait_foreground(pid: u32) -> io::Result<ExitStatus> {
+    let job_control = unsafe { libc::isatty(libc::STDIN_FILENO) } == 1;
+    let options = if job_control { libc::WUNTRACED } else { 0 };
+    loop {
+        let mut status: c_int = 0;
+        if unsafe { libc::waitpid(pid as libc::pid_t, &mut status, options) } == -1 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(err);
+        }
+        if !libc::WIFSTOPPED(status) {
+            return Ok(ExitStatus::from_raw(status));
+        }
+
+        debug!("process group {} stopped, stop", pid);
+        restore_foreground_process_group();
+        unsafe {
+            libc::raise(libc::SIGSTOP);
+        }
+        debug!("continue process group {}", pid);
+        unsafe {
+            if libc::tcgetpgrp(libc::STDIN_FILENO) == libc::getpgrp() {
+                let handler = libc::signal(libc::SIGTTOU, libc::SIG_IGN);
+                libc::tcsetpgrp(libc::STDIN_FILENO, pid as libc::pid_t);
+                libc::signal(libc::SIGTTOU, handler);
+            }
+        }
+        kill_process_group(pid, libc::SIGCONT);
+    }
+}
+
+/// Signal from its name (`TERM`, `SIGTERM`) or its number.
+pub fn parse_signal(signal: &str) -> Result<c_int> {
+    if let Ok(number) = signal.parse::<c_int>() {
+        return Ok(number);
+    }
+    let name = signal.to_uppercase();
+    let name = name.trim_start_matches("SIG");
+    let signal = match name {
+        "HUP" => libc::SIGHUP,
+        "INT" => libc::SIGINT,
+        "QUIT" => libc::SIGQUIT,
+        "KILL" => libc::SIGKILL,
+        "USR1" => libc::SIGUSR1,
+        "USR2" => libc::SIGUSR2,
+        "TERM" => libc::SIGTERM,
+        _ => bail!("unknown signal `{}`", signal),
+    };
+    Ok(signal)
+}
+
+/// Send `signal` to all the processes of the group `pgid`.
+pub fn kill_process_group(pgid: u32, signal: c_int) {
+    unsafe {
+        libc::kill(-(pgid as libc::pid_t), signal);
+    }
+}
+
+/// True if the process `pid` exists, can be signalled by short and is not a zombie waiting to be
+/// reaped. A process of another user is not alive for short.
+pub fn is_alive(pid: u32) -> bool {
+    if unsafe { libc::kill(pid as libc::pid_t, 0) } != 0 {
+        return false;
+    }
+    match process_info(pid) {
+        Some(info) => !info.zombie,
+        None => true,
+    }
+}
+
+/// Start time of the process `pid`, in clock ticks after the boot on Linux and in microseconds
+/// since the epoch on macOS. Tells apart a process from another one that has been given the
+/// same pid. `None` when the process doesn't exist or on other platforms.
+pub fn process_start_time(pid: u32) -> Option<u64> {
+    process_info(pid).map(|info| info.start_time)
+}
+
+struct ProcessInfo {
+    zombie: bool,
+    start_time: u64,
+}
+
+/// Read from `/proc/<pid>/stat`, the fields that follow the command name start with the state.
+/// The command name is between parentheses and may contain spaces.
+#[cfg(target_os = "linux")]
+fn process_info(pid: u32) -> Option<ProcessInfo> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    let (_, fields) = stat.rsplit_once(')')?;
+    let fields: Vec<&str> = fields.split_whitespace().collect();
+    Some(ProcessInfo {
+        zombie: fields.first()?.starts_with('Z'),
+        // `starttime` is the 22nd field, the 20th after the command name.
+        start_time: fields.get(19)?.parse().ok()?,
+    })
+}
+
+#[cfg(target_os = "macos")]
+extern "C" {
+    fn proc_pidinfo(
+        pid: c_int,
+        flavor: c_int,
+        arg: u64,
+        buffer: *mut libc::c_void,
+        buffersize: c_int,
+    ) -> c_int;
+}
+
+/// Read with `proc_pidinfo` of libproc.
+#[cfg(target_os = "macos")]
+fn process_info(pid: u32) -> Option<ProcessInfo> {
+    const PROC_PIDTBSDINFO: c_int = 3;
+    const SZOMB: u32 = 5;
+    let mut info: libc::proc_bsdinfo = unsafe { std::mem::zeroed() };
+    let size = std::mem::size_of::<libc::proc_bsdinfo>() as c_int;
+    let read = unsafe {
+        proc_pidinfo(
+            pid as c_int,
+            PROC_PIDTBSDINFO,
+            0,
+            &mut info as *mut libc::proc_bsdinfo as *mut libc::c_void,
+            size,
+        )
+    };
+    if read != size {
+        return None;
+    }
+    Some(ProcessInfo {
+        zombie: info.pbi_status == SZOMB,
+        start_time: info.pbi_start_tvsec * 1_000_000 + info.pbi_start_tvusec,
+    })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn process_info(_pid: u32) -> Option<ProcessInfo> {
+    None
+}
+
+/// Exit code of the child, `128 + n` when it has been killed by the signal `n`.
+pub fn exit_code(status: &ExitStatus) -> i32 {
+    match (status.code(), status.signal()) {
+        (Some(code), _) => code,
+        (None, Some(signal)) => SIGNAL_EXIT_CODE_BASE + signal,
+        (None, None) => 1,
+    }
+}
+
+/// Child of a `SignalForwarder`, its process group is set once spawned.
+struct Forwarding {
+    pgid: Option<u32>,
+    /// Received before the child was spawned.
+    pending: Vec<c_int>,
+    received: bool,
+}
+
+/// Receivers of `FORWARDED_SIGNALS`. When there is none, short is stopped by the signal.
+struct Receivers {
+    next_id: u64,
+    forwardings: BTreeMap<u64, Forwarding>,
+    listeners: BTreeMap<u64, VecDeque<c_int>>,
+}
+
+/// `FORWARDED_SIGNALS` are registered once for the whole process : signal-hook doesn't restore
+/// the default action when they are unregistered, short would then ignore them.
+static REGISTERED: Mutex<bool> = Mutex::new(false);
+static RECEIVERS: Mutex<Receivers> = Mutex::new(Receivers {
+    next_id: 0,
+    forwardings: BTreeMap::new(),
+    listeners: BTreeMap::new(),
+});
+
+fn register_signals() -> Result<()> {
+    let mut registered = REGISTERED.lock().unwrap();
+    if *registered {
+        return Ok(());
+    }
+    let signals = Signals::new(FORWARDED_SIGNALS).context("fail to register signals")?;
+    thread::spawn(move || {
+        for signal in signals.forever() {
+            dispatch(signal);
+        }
+    });
+    *registered = true;
+    Ok(())
+}
+
+fn dispatch(signal: c_int) {
+    let mut receivers = RECEIVERS.lock().unwrap();
+    if receivers.forwardings.is_empty() && receivers.listeners.is_empty() {
+        drop(receivers);
+        debug!("no child to forward signal {}, stop", signal);
+        raise_default(signal);
+        return;
+    }
+    for forwarding in receivers.forwardings.values_mut() {
+        forwarding.received = true;
+        match forwarding.pgid {
+            Some(pgid) => {
+                debug!("forward signal {} to process group {}", signal, pgid);
+                kill_process_group(pgid, signal);
+            }
+            None => forwarding.pending.push(signal),
+        }
+    }
+    for listener in receivers.listeners.values_mut() {
+        listener.push_back(signal);
+    }
+}
+
+/// Stop short with the default action of `signal`, its exit code is `128 + signal`.
+fn raise_default(signal: c_int) {
+    unsafe {
+        libc::signal(signal, libc::SIG_DFL);
+        libc::raise(signal);
+    }
+}
+
+fn add_receiver<F>(add: F) -> Result<u64>
+where
+    F: FnOnce(&mut Receivers, u64),
+{
+    register_signals()?;
+    let mut receivers = RECEIVERS.lock().unwrap();
+    let id = receivers.next_id;
+    receivers.next_id += 1;
+    add(&mut receivers, id);
+    Ok(id)
+}
+
+/// Forward `FORWARDED_SIGNALS` received by short to the process group of a child.
+pub struct SignalForwarder {
+    id: u64,
+}
+
+impl SignalForwarder {
+    /// Receive `FORWARDED_SIGNALS`, it must be done before spawning the child to not miss any of
+    /// them.
+    pub fn new() -> Result<Self> {
+        let id = add_receiver(|receivers, id| {
+            let forwarding = Forwarding {
+                pgid: None,
+                pending: vec![],
+                received: false,
+            };
+            receivers.forwardings.insert(id, forwarding);
+        })?;
+        Ok(Self { id })
+    }
+
+    /// Forward the signals to the process group `pgid` until dropped.
+    pub fn forward_to(&mut self, pgid: u32) {
+        let mut receivers = RECEIVERS.lock().unwrap();
+        if let Some(forwarding) = receivers.forwardings.get_mut(&self.id) {
+            forwarding.pgid = Some(pgid);
+            for signal in forwarding.pending.drain(..) {
+                kill_process_group(pgid, signal);
+            }
+        }
+    }
+
+    /// True if a signal has been forwarded.
+    pub fn received(&self) -> bool {
+        let receivers = RECEIVERS.lock().unwrap();
+        receivers
+            .forwardings
+            .get(&self.id)
+            .is_some_and(|forwarding| forwarding.received)
+    }
+}
+
+impl Drop for SignalForwarder {
+    fn drop(&mut self) {
+        RECEIVERS.lock().unwrap().forwardings.remove(&self.id);
+    }
+}
+
+/// `FORWARDED_SIGNALS` received by short, polled by the caller until dropped.
+pub struct SignalListener {
+    id: u64,
+}
+
+impl SignalListener {
+    pub fn new() -> Result<Self> {
+        let id = add_receiver(|receivers, id| {
+            receivers.listeners.insert(id, VecDeque::new());
+        })?;
+        Ok(Self { id })
+    }
+
+    /// Next received signal, if any.
+    pub fn pending(&self) -> Option<c_int> {
+        let mut receivers = RECEIVERS.lock().unwrap();
+        receivers
+            .listeners
+            .get_mut(&self.id)
+            .and_then(|listener| listener.pop_front())
+    }
+}
+
+impl Drop for SignalListener {
+    fn drop(&mut self) {
+        RECEIVERS.lock().unwrap().listeners.remove(&self.id);
+    }
+}
+
+/// Stop the process group `pgid` with `SIGTERM` when the timeout elapses, then kill it
+/// if it is still alive after `TIMEOUT_GRACE_PERIOD`.
+pub struct Timeout {
+    cancel: Sender<()>,
+    thread: JoinHandle<bool>,
+}
+
+impl Timeout {
+    pub fn start(pgid: u32, timeout: Duration) -> Self {
+        let (cancel, cancelled) = channel::<()>();
+        let thread = thread::spawn(move || {
+            if cancelled.recv_timeout(timeout) != Err(RecvTimeoutError::Timeout) {
+                return false;
+            }
+            debug!("timeout, stop process group {}", pgid);
+            kill_process_group(pgid, SIGTERM);
+            if cancelled.recv_timeout(TIMEOUT_GRACE_PERIOD) == Err(RecvTimeoutError::Timeout) {
+                debug!("timeout, kill process group {}", pgid);
+                kill_process_group(pgid, libc::SIGKILL);
+            }
+            true
+        });
+        Self { cancel, thread }
+    }
+
+    /// Stop the timer once the child has exited, true if the timeout has elapsed.
+    pub fn cancel(self) -> bool {
+        let _ = self.cancel.send(());
+        self.thread.join().expect("fail to wait timeout")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+
+    use crate::run_file::signal::{exit_code, is_alive, parse_signal, process_start_time};
+
+    #[test]
+    fn exit_code_of_status() {
+        let status = Command::new("sh").args(&["-c", "exit 3"]).status().unwrap();
+        assert_eq!(exit_code(&status), 3);
+
+        let status = Command::new("sh")
+            .args(&["-c", "kill -9 $$"])
+            .status()
+            .unwrap();
+        assert_eq!(exit_code(&status), 137);
+    }
+
+    #[test]
+    fn parse_signal_name() {
+        assert_eq!(parse_signal("TERM").unwrap(), libc::SIGTERM);
+        assert_eq!(parse_signal("sigint").unwrap(), libc::SIGINT);
+        assert_eq!(parse_signal("9").unwrap(), libc::SIGKILL);
+        assert!(parse_signal("NOPE").is_err());
+    }
+
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    fn start_time_of_process() {
+        let mut child = Command::new("sleep").arg("5").spawn().unwrap();
+        let start_time = process_start_time(child.id());
+        assert!(start_time.is_some());
+        assert_eq!(process_start_time(child.id()), start_time);
+        assert!(is_alive(child.id()));
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert!(process_start_time(child.id()).is_none());
+        assert!(!is_alive(child.id()));
+    }
+}
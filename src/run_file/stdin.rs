@@ -0,0 +1,105 @@
+use std::io;
+use std::io::{Read, Write};
+use std::mem;
+use std::process::ChildStdin;
+use std::sync::{Condvar, Mutex, Once};
+use std::thread;
+
+/// Stdin of short, copied to the stdin of one streamed child at a time. A single thread reads it
+/// for all the runs of short (retries, steps, envs), so the input typed for a run is not taken by
+/// a previous one.
+struct Pump {
+    claimed: bool,
+    /// Incremented at each claim, tells apart the child of a released claim.
+    claim: u64,
+    child: Option<ChildStdin>,
+    /// Read when the previous child had exited, given to the next one.
+    pending: Vec<u8>,
+    eof: bool,
+}
+
+static PUMP: Mutex<Pump> = Mutex::new(Pump {
+    claimed: false,
+    claim: 0,
+    child: None,
+    pending: Vec::new(),
+    eof: false,
+});
+static CHILD_ATTACHED: Condvar = Condvar::new();
+static START: Once = Once::new();
+
+/// Stdin of short claimed by a run, released on drop.
+pub struct StdinClaim(());
+
+/// Claim the stdin of short, `None` when another run has it.
+pub fn claim_stdin() -> Option<StdinClaim> {
+    let mut pump = PUMP.lock().unwrap();
+    if pump.claimed {
+        return None;
+    }
+    pump.claimed = true;
+    pump.claim += 1;
+    Some(StdinClaim(()))
+}
+
+impl StdinClaim {
+    /// Copy the stdin of short to the stdin of the child until the claim is released. Once
+    /// stdin of short is closed, the stdin of the child is closed right away.
+    pub fn attach(&self, child: ChildStdin) {
+        START.call_once(|| {
+            thread::spawn(read_stdin);
+        });
+        let mut pump = PUMP.lock().unwrap();
+        if !pump.eof {
+            pump.child = Some(child);
+            CHILD_ATTACHED.notify_one();
+        }
+    }
+}
+
+impl Drop for StdinClaim {
+    fn drop(&mut self) {
+        let mut pump = PUMP.lock().unwrap();
+        pump.child = None;
+        pump.claimed = false;
+    }
+}
+
+/// Read stdin only while a child is attached, so at most one buffer is kept for the next child.
+/// The lock is not held while writing to the child, it may not read its stdin.
+fn read_stdin() {
+    let mut buffer = [0; 8192];
+    loop {
+        let (claim, mut child, pending) = {
+            let pump = PUMP.lock().unwrap();
+            let mut pump = CHILD_ATTACHED
+                .wait_while(pump, |pump| pump.child.is_none())
+                .unwrap();
+            let pending = mem::take(&mut pump.pending);
+            (pump.claim, pump.child.take().unwrap(), pending)
+        };
+
+        let bytes = if pending.is_empty() {
+            match io::stdin().read(&mut buffer) {
+                Ok(len) if len > 0 => &buffer[..len],
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => &buffer[..0],
+                // End of stdin, or unreadable : the child and the next ones get it closed.
+                _ => {
+                    PUMP.lock().unwrap().eof = true;
+                    return;
+                }
+            }
+        } else {
+            &pending[..]
+        };
+        let written = child.write_all(bytes).is_ok();
+
+        let mut pump = PUMP.lock().unwrap();
+        if !written {
+            // The child has exited, its claim is about to be released.
+            pump.pending = bytes.to_vec();
+        } else if pump.claimed && pump.claim == claim {
+            pump.child = Some(child);
+        }
+    }
+}
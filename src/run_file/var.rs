@@ -67,6 +67,8 @@ pub struct EnvVar(VarName, EnvValue);
 
 pub const ENV_ENVIRONMENT_VAR: &'static str = "short_env";
 pub const ENV_SETUP_VAR: &'static str = "short_setup";
+pub const ENV_ARGUMENT_VAR_PREFIX: &str = "short_arg_";
+pub const ENV_EXIT_STATUS_VAR: &str = "short_exit_status";
 
 impl EnvVar {
     pub fn var(&self) -> &VarName {
@@ -87,6 +89,20 @@ impl EnvVar {
         let env_var = env_file::Var::new(ENV_SETUP_VAR, setup.name()?);
         Ok(EnvVar(var, EnvValue::Var(env_var)))
     }
+
+    /// Value of a declared argument of the setup, `SHORT_ARG_<NAME>`.
+    pub fn from_argument(name: &str, value: String) -> Self {
+        let name = argument_var_name(name);
+        let env_var = env_file::Var::new(name.as_str(), value);
+        EnvVar(VarName::new(name), EnvValue::Var(env_var))
+    }
+
+    /// Exit status of the run, given to the `post_run` hook.
+    pub fn from_exit_status(status: i32) -> Self {
+        let var = VarName::new(ENV_EXIT_STATUS_VAR.to_string());
+        let env_var = env_file::Var::new(ENV_EXIT_STATUS_VAR, status.to_string());
+        EnvVar(var, EnvValue::Var(env_var))
+    }
 }
 
 impl From<(VarName, EnvValue)> for EnvVar {
@@ -95,6 +111,10 @@ impl From<(VarName, EnvValue)> for EnvVar {
     }
 }
 
+pub fn argument_var_name(name: &str) -> String {
+    format!("{}{}", ENV_ARGUMENT_VAR_PREFIX, name.to_snake_case())
+}
+
 pub fn generate_array_env_var(env: &Env, array_var: &ArrayVar) -> Result<EnvVar> {
     let re = Regex::new(array_var.pattern().as_str())?;
     let mut array_var_value: Vec<env_file::Var> = vec![];
@@ -0,0 +1,211 @@
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Parameter {
+    pub name: String,
+    pub value: String,
+    pub secure: bool,
+}
+
+#[cfg(feature = "reqwest")]
+mod http {
+    use std::env;
+    use std::time::SystemTime;
+
+    use reqwest::blocking::Client;
+    use reqwest::Url;
+    use serde_json::{json, Value};
+
+    use super::Parameter;
+    use crate::ssm::sigv4::{amz_date, authorization, Credentials, SignRequest};
+    use crate::ssm::SsmError;
+
+    const DEFAULT_REGION: &str = "us-east-1";
+    const SERVICE: &str = "ssm";
+    const CONTENT_TYPE: &str = "application/x-amz-json-1.1";
+    const TARGET_PREFIX: &str = "AmazonSSM.";
+    const STRING_TYPE: &str = "String";
+    const SECURE_STRING_TYPE: &str = "SecureString";
+
+    fn region() -> String {
+        env::var("AWS_REGION")
+            .or_else(|_| env::var("AWS_DEFAULT_REGION"))
+            .unwrap_or(DEFAULT_REGION.to_string())
+    }
+
+    /// Endpoint can be overridden with `AWS_ENDPOINT_URL_SSM` or `AWS_ENDPOINT_URL`.
+    fn endpoint(region: &str) -> String {
+        env::var("AWS_ENDPOINT_URL_SSM")
+            .or_else(|_| env::var("AWS_ENDPOINT_URL"))
+            .unwrap_or(format!("https://ssm.{}.amazonaws.com", region))
+    }
+
+    fn credentials() -> Result<Credentials, SsmError> {
+        match (
+            env::var("AWS_ACCESS_KEY_ID"),
+            env::var("AWS_SECRET_ACCESS_KEY"),
+        ) {
+            (Ok(access_key_id), Ok(secret_access_key)) => Ok(Credentials {
+                access_key_id,
+                secret_access_key,
+                session_token: env::var("AWS_SESSION_TOKEN").ok(),
+            }),
+            _ => Err(SsmError::CredentialsNotFound),
+        }
+    }
+
+    pub struct SsmClient {
+        url: Url,
+        host: String,
+        region: String,
+        credentials: Credentials,
+        client: Client,
+    }
+
+    impl SsmClient {
+        pub fn from_env() -> Result<Self, SsmError> {
+            let region = region();
+            let endpoint = endpoint(&region);
+            let url =
+                Url::parse(&endpoint).map_err(|_| SsmError::IncorrectEndpoint(endpoint.clone()))?;
+            let host = match (url.host_str(), url.port()) {
+                (Some(host), Some(port)) => format!("{}:{}", host, port),
+                (Some(host), None) => host.to_string(),
+                _ => return Err(SsmError::IncorrectEndpoint(endpoint)),
+            };
+            Ok(Self {
+                url,
+                host,
+                region,
+                credentials: credentials()?,
+                client: Client::new(),
+            })
+        }
+
+        fn call(&self, action: &str, body: Value) -> Result<Value, SsmError> {
+            let body = body.to_string();
+            let target = format!("{}{}", TARGET_PREFIX, action);
+            let amz_date = amz_date(SystemTime::now());
+            let authorization = authorization(
+                &self.credentials,
+                &SignRequest {
+                    host: &self.host,
+                    path: self.url.path(),
+                    region: &self.region,
+                    service: SERVICE,
+                    amz_date: &amz_date,
+                    headers: vec![("content-type", CONTENT_TYPE), ("x-amz-target", &target)],
+                    body: &body,
+                },
+            );
+
+            let mut request = self
+                .client
+                .post(self.url.clone())
+                .header("Content-Type", CONTENT_TYPE)
+                .header("X-Amz-Target", &target)
+                .header("X-Amz-Date", &amz_date)
+                .header("Authorization", authorization);
+            if let Some(session_token) = &self.credentials.session_token {
+                request = request.header("X-Amz-Security-Token", session_token);
+            }
+
+            let response = request.body(body).send()?;
+            let status = response.status();
+            let body = response.text()?;
+            if !status.is_success() {
+                let message = serde_json::from_str::<Value>(&body)
+                    .ok()
+                    .and_then(|value| {
+                        value["message"]
+                            .as_str()
+                            .or(value["Message"].as_str())
+                            .map(|message| message.to_string())
+                    })
+                    .unwrap_or(body);
+                return Err(SsmError::RequestFail(
+                    action.to_string(),
+                    status.as_u16(),
+                    message,
+                ));
+            }
+            serde_json::from_str(&body).map_err(|_| SsmError::MalformedResponse(action.to_string()))
+        }
+
+        /// Parameters directly under `path`, values are decrypted.
+        pub fn parameters_by_path(&self, path: &str) -> Result<Vec<Parameter>, SsmError> {
+            let action = "GetParametersByPath";
+            let mut parameters = vec![];
+            let mut next_token: Option<String> = None;
+            loop {
+                let mut body = json!({
+                    "Path": path,
+                    "Recursive": false,
+                    "WithDecryption": true,
+                });
+                if let Some(next_token) = next_token {
+                    body["NextToken"] = Value::String(next_token);
+                }
+                let value = self.call(action, body)?;
+                let page = value["Parameters"]
+                    .as_array()
+                    .ok_or(SsmError::MalformedResponse(action.to_string()))?;
+                for parameter in page {
+                    match (parameter["Name"].as_str(), parameter["Value"].as_str()) {
+                        (Some(name), Some(value)) => parameters.push(Parameter {
+                            name: name.to_string(),
+                            value: value.to_string(),
+                            secure: parameter["Type"].as_str() == Some(SECURE_STRING_TYPE),
+                        }),
+                        _ => return Err(SsmError::MalformedResponse(action.to_string())),
+                    }
+                }
+                next_token = value["NextToken"].as_str().map(|token| token.to_string());
+                if next_token.is_none() {
+                    break;
+                }
+            }
+            Ok(parameters)
+        }
+
+        pub fn put_parameter(&self, parameter: &Parameter) -> Result<(), SsmError> {
+            let kind = if parameter.secure {
+                SECURE_STRING_TYPE
+            } else {
+                STRING_TYPE
+            };
+            self.call(
+                "PutParameter",
+                json!({
+                    "Name": parameter.name,
+                    "Value": parameter.value,
+                    "Type": kind,
+                    "Overwrite": true,
+                }),
+            )?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(not(feature = "reqwest"))]
+mod http {
+    use super::Parameter;
+    use crate::ssm::SsmError;
+
+    pub struct SsmClient;
+
+    impl SsmClient {
+        pub fn from_env() -> Result<Self, SsmError> {
+            Err(SsmError::Unsupported)
+        }
+
+        pub fn parameters_by_path(&self, _path: &str) -> Result<Vec<Parameter>, SsmError> {
+            Err(SsmError::Unsupported)
+        }
+
+        pub fn put_parameter(&self, _parameter: &Parameter) -> Result<(), SsmError> {
+            Err(SsmError::Unsupported)
+        }
+    }
+}
+
+pub use http::SsmClient;
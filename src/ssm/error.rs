@@ -0,0 +1,23 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SsmError {
+    #[error("aws credentials not found, set `AWS_ACCESS_KEY_ID` and `AWS_SECRET_ACCESS_KEY`")]
+    CredentialsNotFound,
+    #[error("incorrect ssm endpoint `{0}`")]
+    IncorrectEndpoint(String),
+    #[error("ssm request `{0}` fail with status `{1}` : {2}")]
+    RequestFail(String, u16, String),
+    #[error("no parameter found under `{0}`, the env is not changed")]
+    NoParameters(String),
+    #[error("ssm response of `{0}` is malformed")]
+    MalformedResponse(String),
+    #[cfg(feature = "reqwest")]
+    #[error("ssm http error")]
+    Http {
+        #[from]
+        source: reqwest::Error,
+    },
+    #[error("ssm support require the `reqwest` feature")]
+    Unsupported,
+}
@@ -0,0 +1,90 @@
+use std::collections::BTreeMap;
+
+pub use client::{Parameter, SsmClient};
+pub use error::SsmError;
+
+use crate::env_file::Env;
+
+mod client;
+mod error;
+#[cfg(feature = "reqwest")]
+mod sigv4;
+
+/// One parameter per var, named `<path>/<var name>`.
+pub fn parameters_from_env<F>(path: &str, env: &Env, is_secret: F) -> Vec<Parameter>
+where
+    F: Fn(&str) -> bool,
+{
+    env.iter()
+        .map(|var| Parameter {
+            name: format!("{}/{}", path, var.name()),
+            value: var.value().clone(),
+            secure: is_secret(var.name()),
+        })
+        .collect()
+}
+
+/// Build the env expected after a pull, based on `env` to keep its comments and vars order.
+/// Vars missing under `path` are removed and new ones are appended.
+pub fn env_from_parameters(path: &str, env: &Env, parameters: &[Parameter]) -> Env {
+    let prefix = format!("{}/", path);
+    let vars: BTreeMap<&str, &str> = parameters
+        .iter()
+        .filter_map(|parameter| {
+            parameter
+                .name
+                .strip_prefix(&prefix)
+                .map(|name| (name, parameter.value.as_str()))
+        })
+        .collect();
+
+    let mut source_env = env.clone();
+    source_env.retain_vars(|var| vars.contains_key(var.name().as_str()));
+    for (name, value) in vars {
+        source_env.set(name, value);
+    }
+    source_env
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::env_file::Env;
+    use crate::ssm::{env_from_parameters, parameters_from_env, Parameter};
+
+    fn parameter(name: &str, value: &str, secure: bool) -> Parameter {
+        Parameter {
+            name: name.to_string(),
+            value: value.to_string(),
+            secure,
+        }
+    }
+
+    #[test]
+    fn env_to_parameters() {
+        let mut env = Env::new(".prod".into());
+        env.add("VAR1", "VALUE1");
+        env.add("SECRET", "VALUE2");
+        let parameters = parameters_from_env("/app/prod", &env, |name| name == "SECRET");
+        assert_eq!(
+            parameters,
+            vec![
+                parameter("/app/prod/VAR1", "VALUE1", false),
+                parameter("/app/prod/SECRET", "VALUE2", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn parameters_to_env() {
+        let mut env = Env::new(".prod".into());
+        env.add("VAR2", "VALUE2");
+        env.add_empty_line();
+        env.add("VAR1", "VALUE1");
+        let parameters = vec![
+            parameter("/app/prod/VAR1", "VALUE1.1", false),
+            parameter("/app/prod/VAR3", "VALUE3", true),
+        ];
+        let env = env_from_parameters("/app/prod", &env, &parameters);
+        assert_eq!(env.to_string(), "\nVAR1=VALUE1.1\nVAR3=VALUE3\n");
+    }
+}
@@ -0,0 +1,148 @@
+//! Minimal AWS signature version 4, only what is needed to sign json POST requests.
+
+use std::time::SystemTime;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::utils::hash::{hex, sha256};
+
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+
+pub fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("hmac takes a key of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().into()
+}
+
+/// Return `20200101T000000Z` formatted time.
+pub fn amz_date(time: SystemTime) -> String {
+    humantime::format_rfc3339_seconds(time)
+        .to_string()
+        .replace(&['-', ':'][..], "")
+}
+
+pub struct Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+pub struct SignRequest<'a> {
+    pub host: &'a str,
+    pub path: &'a str,
+    pub region: &'a str,
+    pub service: &'a str,
+    pub amz_date: &'a str,
+    /// Headers to sign without `host` and `x-amz-date`, names must be lower case.
+    pub headers: Vec<(&'a str, &'a str)>,
+    pub body: &'a str,
+}
+
+/// Return the `Authorization` header value.
+pub fn authorization(credentials: &Credentials, request: &SignRequest) -> String {
+    let date = &request.amz_date[..8];
+
+    let mut headers = request.headers.clone();
+    headers.push(("host", request.host));
+    headers.push(("x-amz-date", request.amz_date));
+    if let Some(session_token) = &credentials.session_token {
+        headers.push(("x-amz-security-token", session_token));
+    }
+    headers.sort();
+
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(";");
+
+    let canonical_request = format!(
+        "POST\n{}\n\n{}\n{}\n{}",
+        request.path,
+        canonical_headers,
+        signed_headers,
+        hex(&sha256(request.body.as_bytes()))
+    );
+
+    let scope = format!(
+        "{}/{}/{}/aws4_request",
+        date, request.region, request.service
+    );
+    let string_to_sign = format!(
+        "{}\n{}\n{}\n{}",
+        ALGORITHM,
+        request.amz_date,
+        scope,
+        hex(&sha256(canonical_request.as_bytes()))
+    );
+
+    let key = format!("AWS4{}", credentials.secret_access_key);
+    let key = hmac_sha256(key.as_bytes(), date.as_bytes());
+    let key = hmac_sha256(&key, request.region.as_bytes());
+    let key = hmac_sha256(&key, request.service.as_bytes());
+    let key = hmac_sha256(&key, b"aws4_request");
+    let signature = hex(&hmac_sha256(&key, string_to_sign.as_bytes()));
+
+    format!(
+        "{} Credential={}/{}, SignedHeaders={}, Signature={}",
+        ALGORITHM, credentials.access_key_id, scope, signed_headers, signature
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use crate::ssm::sigv4::{amz_date, authorization, hmac_sha256};
+    use crate::ssm::sigv4::{Credentials, SignRequest};
+    use crate::utils::hash::hex;
+
+    #[test]
+    fn hmac_sha256_digest() {
+        assert_eq!(
+            hex(&hmac_sha256(
+                b"key",
+                b"The quick brown fox jumps over the lazy dog"
+            )),
+            "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+
+    #[test]
+    fn format_amz_date() {
+        let time = UNIX_EPOCH + Duration::from_secs(1440938160);
+        assert_eq!(amz_date(time), "20150830T123600Z");
+    }
+
+    #[test]
+    fn sign_request() {
+        let credentials = Credentials {
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            session_token: None,
+        };
+        let request = SignRequest {
+            host: "ssm.us-east-1.amazonaws.com",
+            path: "/",
+            region: "us-east-1",
+            service: "ssm",
+            amz_date: "20150830T123600Z",
+            headers: vec![
+                ("content-type", "application/x-amz-json-1.1"),
+                ("x-amz-target", "AmazonSSM.GetParametersByPath"),
+            ],
+            body: "{}",
+        };
+        assert_eq!(
+            authorization(&credentials, &request),
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/ssm/aws4_request, \
+             SignedHeaders=content-type;host;x-amz-date;x-amz-target, \
+             Signature=de05a1d9677a09165a5dc1fbacbc85a4efd3ca50352fb90fe0a4bb288e8c3414"
+        );
+    }
+}
@@ -0,0 +1,64 @@
+#[derive(Debug, Eq, PartialEq)]
+pub enum LineDiff<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Line based diff (longest common subsequence).
+pub fn line_diff<'a>(old: &'a str, new: &'a str) -> Vec<LineDiff<'a>> {
+    let old: Vec<_> = old.lines().collect();
+    let new: Vec<_> = new.lines().collect();
+
+    // lcs[i][j] : length of the longest common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            diff.push(LineDiff::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            diff.push(LineDiff::Removed(old[i]));
+            i += 1;
+        } else {
+            diff.push(LineDiff::Added(new[j]));
+            j += 1;
+        }
+    }
+    diff.extend(old[i..].iter().map(|line| LineDiff::Removed(line)));
+    diff.extend(new[j..].iter().map(|line| LineDiff::Added(line)));
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::line_diff::{line_diff, LineDiff};
+
+    #[test]
+    fn diff() {
+        let old = "line1\nline2\nline3\n";
+        let new = "line1\nline2.1\nline3\nline4\n";
+        assert_eq!(
+            line_diff(old, new),
+            vec![
+                LineDiff::Same("line1"),
+                LineDiff::Removed("line2"),
+                LineDiff::Added("line2.1"),
+                LineDiff::Same("line3"),
+                LineDiff::Added("line4"),
+            ]
+        );
+    }
+}
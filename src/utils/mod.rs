@@ -2,4 +2,6 @@ pub mod check_update;
 pub mod colorize;
 pub mod file_time;
 pub mod find;
+pub mod hash;
+pub mod line_diff;
 pub mod write_all;
@@ -0,0 +1,226 @@
+use std::collections::BTreeMap;
+
+pub type Secrets = BTreeMap<String, String>;
+/// Custom metadata to merge in a secret, a `None` value removes the key.
+pub type MetadataPatch = BTreeMap<String, Option<String>>;
+
+/// Key/value pairs of the latest version of a secret, with the custom metadata of the secret.
+pub struct Secret {
+    pub data: Secrets,
+    pub custom_metadata: Secrets,
+}
+
+#[cfg(feature = "reqwest")]
+mod http {
+    use std::env;
+    use std::fs::read_to_string;
+    use std::path::PathBuf;
+
+    use dirs::home_dir;
+    use reqwest::blocking::{Client, RequestBuilder, Response};
+    use reqwest::header::CONTENT_TYPE;
+    use reqwest::StatusCode;
+    use serde_json::{json, Map, Value};
+
+    use super::{MetadataPatch, Secret, Secrets};
+    use crate::vault::VaultError;
+
+    const DEFAULT_VAULT_ADDR: &str = "http://127.0.0.1:8200";
+    const VAULT_TOKEN_FILE_NAME: &str = ".vault-token";
+    const VAULT_TOKEN_HEADER: &str = "X-Vault-Token";
+    const MERGE_PATCH_CONTENT_TYPE: &str = "application/merge-patch+json";
+
+    fn vault_addr() -> String {
+        env::var("VAULT_ADDR")
+            .unwrap_or(DEFAULT_VAULT_ADDR.to_string())
+            .trim_end_matches('/')
+            .to_string()
+    }
+
+    fn vault_token() -> Result<String, VaultError> {
+        if let Ok(token) = env::var("VAULT_TOKEN") {
+            return Ok(token);
+        }
+        let token_file = env::var("VAULT_TOKEN_FILE")
+            .map(PathBuf::from)
+            .ok()
+            .or_else(|| home_dir().map(|home| home.join(VAULT_TOKEN_FILE_NAME)));
+        token_file
+            .and_then(|token_file| read_to_string(token_file).ok())
+            .map(|token| token.trim().to_string())
+            .filter(|token| !token.is_empty())
+            .ok_or(VaultError::TokenNotFound)
+    }
+
+    pub struct VaultClient {
+        addr: String,
+        token: String,
+        client: Client,
+    }
+
+    impl VaultClient {
+        pub fn from_env() -> Result<Self, VaultError> {
+            Ok(Self {
+                addr: vault_addr(),
+                token: vault_token()?,
+                client: Client::new(),
+            })
+        }
+
+        fn url(&self, mount: &str, kind: &str, path: &str) -> String {
+            format!("{}/v1/{}/{}/{}", self.addr, mount, kind, path)
+        }
+
+        fn send(&self, request: RequestBuilder, url: &str) -> Result<Response, VaultError> {
+            let response = request.header(VAULT_TOKEN_HEADER, &self.token).send()?;
+            if response.status().is_success() || response.status() == StatusCode::NOT_FOUND {
+                Ok(response)
+            } else {
+                Err(VaultError::RequestFail(
+                    url.to_string(),
+                    response.status().as_u16(),
+                ))
+            }
+        }
+
+        fn json(response: Response, url: &str) -> Result<Value, VaultError> {
+            let body = response.text()?;
+            serde_json::from_str(&body).map_err(|_| VaultError::MalformedResponse(url.to_string()))
+        }
+
+        fn secrets(object: &Map<String, Value>) -> Secrets {
+            object
+                .iter()
+                .map(|(key, value)| {
+                    let value = match value {
+                        Value::String(value) => value.clone(),
+                        value => value.to_string(),
+                    };
+                    (key.clone(), value)
+                })
+                .collect()
+        }
+
+        /// List secret names under `path`, sub folders are ignored.
+        pub fn list(&self, mount: &str, path: &str) -> Result<Vec<String>, VaultError> {
+            let url = self.url(mount, "metadata", path);
+            let response = self.send(self.client.get(&url).query(&[("list", "true")]), &url)?;
+            if response.status() == StatusCode::NOT_FOUND {
+                return Ok(vec![]);
+            }
+            let value = Self::json(response, &url)?;
+            let keys = value["data"]["keys"]
+                .as_array()
+                .ok_or(VaultError::MalformedResponse(url.clone()))?;
+            Ok(keys
+                .iter()
+                .filter_map(|key| key.as_str())
+                .filter(|key| !key.ends_with('/'))
+                .map(|key| key.to_string())
+                .collect())
+        }
+
+        pub fn read(&self, mount: &str, path: &str) -> Result<Option<Secret>, VaultError> {
+            let url = self.url(mount, "data", path);
+            let response = self.send(self.client.get(&url), &url)?;
+            if response.status() == StatusCode::NOT_FOUND {
+                return Ok(None);
+            }
+            let value = Self::json(response, &url)?;
+            let data = value["data"]["data"]
+                .as_object()
+                .ok_or(VaultError::MalformedResponse(url.clone()))?;
+            let custom_metadata = value["data"]["metadata"]["custom_metadata"]
+                .as_object()
+                .map(Self::secrets)
+                .unwrap_or_default();
+            Ok(Some(Secret {
+                data: Self::secrets(data),
+                custom_metadata,
+            }))
+        }
+
+        pub fn write(&self, mount: &str, path: &str, secrets: &Secrets) -> Result<(), VaultError> {
+            let url = self.url(mount, "data", path);
+            let body = json!({ "data": secrets }).to_string();
+            let response = self.send(self.client.post(&url).body(body), &url)?;
+            if response.status() == StatusCode::NOT_FOUND {
+                return Err(VaultError::RequestFail(url, StatusCode::NOT_FOUND.as_u16()));
+            }
+            Ok(())
+        }
+
+        pub fn patch_metadata(
+            &self,
+            mount: &str,
+            path: &str,
+            custom_metadata: &MetadataPatch,
+        ) -> Result<(), VaultError> {
+            let url = self.url(mount, "metadata", path);
+            let body = json!({ "custom_metadata": custom_metadata }).to_string();
+            let request = self
+                .client
+                .patch(&url)
+                .header(CONTENT_TYPE, MERGE_PATCH_CONTENT_TYPE)
+                .body(body);
+            let response = self.send(request, &url)?;
+            if response.status() == StatusCode::NOT_FOUND {
+                return Err(VaultError::RequestFail(url, StatusCode::NOT_FOUND.as_u16()));
+            }
+            Ok(())
+        }
+
+        /// Soft delete of the latest version, it can be restored with `vault kv undelete`.
+        pub fn delete(&self, mount: &str, path: &str) -> Result<(), VaultError> {
+            let url = self.url(mount, "data", path);
+            self.send(self.client.delete(&url), &url)?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(not(feature = "reqwest"))]
+mod http {
+    use super::{MetadataPatch, Secret, Secrets};
+    use crate::vault::VaultError;
+
+    pub struct VaultClient;
+
+    impl VaultClient {
+        pub fn from_env() -> Result<Self, VaultError> {
+            Err(VaultError::Unsupported)
+        }
+
+        pub fn list(&self, _mount: &str, _path: &str) -> Result<Vec<String>, VaultError> {
+            Err(VaultError::Unsupported)
+        }
+
+        pub fn read(&self, _mount: &str, _path: &str) -> Result<Option<Secret>, VaultError> {
+            Err(VaultError::Unsupported)
+        }
+
+        pub fn write(
+            &self,
+            _mount: &str,
+            _path: &str,
+            _secrets: &Secrets,
+        ) -> Result<(), VaultError> {
+            Err(VaultError::Unsupported)
+        }
+
+        pub fn patch_metadata(
+            &self,
+            _mount: &str,
+            _path: &str,
+            _custom_metadata: &MetadataPatch,
+        ) -> Result<(), VaultError> {
+            Err(VaultError::Unsupported)
+        }
+
+        pub fn delete(&self, _mount: &str, _path: &str) -> Result<(), VaultError> {
+            Err(VaultError::Unsupported)
+        }
+    }
+}
+
+pub use http::VaultClient;
@@ -0,0 +1,21 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum VaultError {
+    #[error("incorrect vault source `{0}`, expected `vault://<mount>/data/<path>/{{env}}`")]
+    IncorrectSource(String),
+    #[error("vault token not found, set `VAULT_TOKEN` or `VAULT_TOKEN_FILE`")]
+    TokenNotFound,
+    #[error("vault request `{0}` fail with status `{1}`")]
+    RequestFail(String, u16),
+    #[error("vault response of `{0}` is malformed")]
+    MalformedResponse(String),
+    #[cfg(feature = "reqwest")]
+    #[error("vault http error")]
+    Http {
+        #[from]
+        source: reqwest::Error,
+    },
+    #[error("vault support require the `reqwest` feature")]
+    Unsupported,
+}
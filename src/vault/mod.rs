@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+
+pub use client::{MetadataPatch, Secret, Secrets, VaultClient};
+pub use error::VaultError;
+pub use source::VaultSource;
+
+use crate::env_file::{Env, EnvLocation};
+
+mod client;
+mod error;
+mod source;
+
+pub const VAULT_SCHEME: &str = "vault://";
+/// Custom metadata of a secret set for a protected env.
+const PROTECTED_METADATA: &str = "sht_protected";
+
+/// Env names of the sources already listed, a source is listed once per command.
+static LISTED: Mutex<BTreeMap<VaultSource, Vec<String>>> = Mutex::new(BTreeMap::new());
+
+/// Names of the envs of the source.
+pub fn env_names(source: &VaultSource) -> Result<Vec<String>, VaultError> {
+    let mut listed = LISTED.lock().unwrap();
+    if let Some(env_names) = listed.get(source) {
+        return Ok(env_names.clone());
+    }
+    let client = VaultClient::from_env()?;
+    let env_names = client.list(source.mount(), source.prefix())?;
+    listed.insert(source.clone(), env_names.clone());
+    Ok(env_names)
+}
+
+/// Read all the envs of the source.
+pub fn read_envs(source: &VaultSource) -> Vec<Result<Env>> {
+    let read_envs = || -> Result<Vec<Result<Env>>> {
+        let env_names = env_names(source)?;
+        Ok(env_names
+            .iter()
+            .map(|env_name| {
+                read_env(source, env_name)
+                    .context(format!("fail to read `{}`", source.env_url(env_name)))
+            })
+            .collect())
+    };
+    match read_envs() {
+        Ok(envs) => envs,
+        Err(err) => vec![Err(
+            err.context(format!("fail to list vault envs `{}`", source.url()))
+        )],
+    }
+}
+
+pub fn read_env(source: &VaultSource, env_name: &str) -> Result<Env, VaultError> {
+    let client = VaultClient::from_env()?;
+    let path = source.env_path(env_name);
+    let secret = client
+        .read(source.mount(), &path)?
+        .ok_or(VaultError::RequestFail(path, 404))?;
+
+    let mut env = Env::from_location(EnvLocation::Vault(source.clone(), env_name.to_string()));
+    for (name, value) in secret.data.iter() {
+        env.add(name, value);
+    }
+    if secret
+        .custom_metadata
+        .get(PROTECTED_METADATA)
+        .map(String::as_str)
+        == Some("true")
+    {
+        env.set_protected();
+    }
+    Ok(env)
+}
+
+/// Comments and empty lines are not kept, vault only store key/value pairs. The protected
+/// annotation is kept in the custom metadata of the secret.
+pub fn write_env(source: &VaultSource, env_name: &str, env: &Env) -> Result<(), VaultError> {
+    let client = VaultClient::from_env()?;
+    let path = source.env_path(env_name);
+    let secrets: Secrets = env.iter().map(|var| var.tuple()).collect();
+    client.write(source.mount(), &path, &secrets)?;
+    let mut custom_metadata = MetadataPatch::new();
+    custom_metadata.insert(
+        PROTECTED_METADATA.to_string(),
+        Some("true".to_string()).filter(|_| env.is_protected()),
+    );
+    client.patch_metadata(source.mount(), &path, &custom_metadata)?;
+    if let Some(env_names) = LISTED.lock().unwrap().get_mut(source) {
+        if !env_names.iter().any(|name| name == env_name) {
+            env_names.push(env_name.to_string());
+        }
+    }
+    Ok(())
+}
+
+pub fn remove_env(source: &VaultSource, env_name: &str) -> Result<(), VaultError> {
+    let client = VaultClient::from_env()?;
+    client.delete(source.mount(), &source.env_path(env_name))?;
+    if let Some(env_names) = LISTED.lock().unwrap().get_mut(source) {
+        env_names.retain(|name| name != env_name);
+    }
+    Ok(())
+}
+
+/// An env not found is `false`, a failing request is an error.
+pub fn env_exists(source: &VaultSource, env_name: &str) -> Result<bool, VaultError> {
+    Ok(env_names(source)?.iter().any(|name| name == env_name))
+}
@@ -0,0 +1,103 @@
+use crate::vault::{VaultError, VAULT_SCHEME};
+
+const ENV_PLACEHOLDER: &str = "{env}";
+const DATA_SEGMENT: &str = "/data/";
+
+/// Vault KV v2 location of private envs, e.g. `vault://secret/data/myapp/{env}`.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub struct VaultSource {
+    url: String,
+    mount: String,
+    prefix: String,
+}
+
+impl VaultSource {
+    pub fn parse<S: AsRef<str>>(url: S) -> Result<Self, VaultError> {
+        let url = url.as_ref().trim_end_matches('/');
+        let incorrect = || VaultError::IncorrectSource(url.to_string());
+
+        let path = url.strip_prefix(VAULT_SCHEME).ok_or_else(incorrect)?;
+        let index = path.find(DATA_SEGMENT).ok_or_else(incorrect)?;
+        let mount = &path[..index];
+        let template = &path[index + DATA_SEGMENT.len()..];
+        let prefix = template
+            .strip_suffix(ENV_PLACEHOLDER)
+            .ok_or_else(incorrect)?;
+        if mount.is_empty() || prefix.contains(ENV_PLACEHOLDER) {
+            return Err(incorrect());
+        }
+        if !prefix.is_empty() && !prefix.ends_with('/') {
+            return Err(incorrect());
+        }
+
+        Ok(Self {
+            url: url.to_string(),
+            mount: mount.to_string(),
+            prefix: prefix.to_string(),
+        })
+    }
+
+    /// A private env source is a vault when it has the vault scheme, a directory otherwise.
+    pub fn is_source<S: AsRef<str>>(source: S) -> bool {
+        source.as_ref().starts_with(VAULT_SCHEME)
+    }
+
+    pub fn url(&self) -> &String {
+        &self.url
+    }
+
+    pub fn mount(&self) -> &String {
+        &self.mount
+    }
+
+    /// Secret path relative to the mount, where are listed all envs.
+    pub fn prefix(&self) -> &String {
+        &self.prefix
+    }
+
+    /// Secret path relative to the mount of the env.
+    pub fn env_path(&self, env_name: &str) -> String {
+        format!("{}{}", self.prefix, env_name)
+    }
+
+    /// Url of the env, e.g. `vault://secret/data/myapp/dev`.
+    pub fn env_url(&self, env_name: &str) -> String {
+        format!(
+            "{}{}/data/{}",
+            VAULT_SCHEME,
+            self.mount,
+            self.env_path(env_name)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vault::VaultSource;
+
+    #[test]
+    fn parse() {
+        let source = VaultSource::parse("vault://secret/data/myapp/{env}").unwrap();
+        assert_eq!(source.mount(), "secret");
+        assert_eq!(source.prefix(), "myapp/");
+        assert_eq!(source.env_path("dev"), "myapp/dev");
+
+        let source = VaultSource::parse("vault://kv/team/data/{env}/").unwrap();
+        assert_eq!(source.mount(), "kv/team");
+        assert_eq!(source.prefix(), "");
+
+        VaultSource::parse("secret/data/myapp/{env}").unwrap_err();
+        VaultSource::parse("vault://secret/myapp/{env}").unwrap_err();
+        VaultSource::parse("vault://secret/data/myapp").unwrap_err();
+        VaultSource::parse("vault://secret/data/{env}/config").unwrap_err();
+        VaultSource::parse("vault://secret/data/myapp{env}").unwrap_err();
+    }
+
+    #[test]
+    fn env_url() {
+        let source = VaultSource::parse("vault://secret/data/myapp/{env}").unwrap();
+        assert_eq!(source.env_url("dev"), "vault://secret/data/myapp/dev");
+        assert!(VaultSource::is_source(source.url()));
+        assert!(!VaultSource::is_source("../private_env"));
+    }
+}
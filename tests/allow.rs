@@ -0,0 +1,212 @@
+use predicates::prelude::Predicate;
+use predicates::str::contains;
+
+use short::BIN_NAME;
+use test_utils::init;
+use test_utils::{HOME_CFG_FILE, PROJECT_CFG_FILE, PROJECT_ENV_EXAMPLE_1_FILE, PROJECT_RUN_FILE};
+
+mod test_utils;
+
+#[test]
+fn cmd_allow() {
+    let mut e = init("cmd_allow");
+
+    e.add_file(PROJECT_ENV_EXAMPLE_1_FILE, r#"VAR1=VALUE1"#);
+    e.add_file(
+        PROJECT_CFG_FILE,
+        r#"
+setups:
+  setup_1:
+    file: run.sh
+"#,
+    );
+    e.add_file(
+        PROJECT_RUN_FILE,
+        r#"#!/bin/bash
+echo "TEST VAR1=$VAR1"
+"#,
+    );
+    e.setup();
+    e.set_exec_permission(PROJECT_RUN_FILE).unwrap();
+
+    // Trusted on first use.
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("run")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("TEST VAR1=VALUE1").count(1).eval(&r));
+    let global_cfg = e.read_file(HOME_CFG_FILE);
+    assert!(contains("allowed_run_file").count(1).eval(&global_cfg));
+
+    e.add_file(
+        PROJECT_RUN_FILE,
+        r#"#!/bin/bash
+echo "TEST VAR1=$VAR1"
+echo "SEND VAR1=$VAR1"
+"#,
+    );
+    e.setup();
+    e.set_exec_permission(PROJECT_RUN_FILE).unwrap();
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("run")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .assert()
+        .failure()
+        .to_string();
+    assert!(contains("TEST VAR1=VALUE1").count(0).eval(&r));
+    assert!(contains("changed since it was allowed").count(1).eval(&r));
+    assert!(contains("+ echo \"SEND VAR1=$VAR1\"").count(1).eval(&r));
+    assert!(contains("is not allowed").count(1).eval(&r));
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("allow")
+        .args(&vec!["-s", "setup_1"])
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("allowed for `setup_1`").count(1).eval(&r));
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("run")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("TEST VAR1=VALUE1").count(1).eval(&r));
+}
+
+#[test]
+fn cmd_allow_settings() {
+    let mut e = init("cmd_allow_settings");
+
+    e.add_file(PROJECT_ENV_EXAMPLE_1_FILE, r#"VAR1=VALUE1"#);
+    e.add_file(
+        PROJECT_CFG_FILE,
+        r#"
+setups:
+  setup_1:
+    file: run.sh
+    args: [ --safe ]
+"#,
+    );
+    e.add_file(
+        PROJECT_RUN_FILE,
+        r#"#!/bin/bash
+echo "TEST ARGS=$@"
+"#,
+    );
+    e.setup();
+    e.set_exec_permission(PROJECT_RUN_FILE).unwrap();
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("run")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("TEST ARGS=--safe").count(1).eval(&r));
+
+    e.add_file(
+        PROJECT_CFG_FILE,
+        r#"
+setups:
+  setup_1:
+    file: run.sh
+    args: [ --safe ]
+    envs:
+      example1:
+        args: [ --unsafe ]
+"#,
+    );
+    e.setup();
+
+    // The override of the env is approved apart, not trusted on first use.
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("run")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .assert()
+        .failure()
+        .to_string();
+    assert!(contains("TEST ARGS=--unsafe").count(0).eval(&r));
+    assert!(contains(r#"+ # sht args: ["--unsafe"]"#).count(1).eval(&r));
+    assert!(contains(r#"run "sht allow -e example1""#).count(1).eval(&r));
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    command
+        .arg("allow")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .assert()
+        .success();
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("run")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("TEST ARGS=--unsafe").count(1).eval(&r));
+
+    e.add_file(
+        PROJECT_CFG_FILE,
+        r#"
+setups:
+  setup_1:
+    file: run.sh
+    args: [ --safe ]
+    envs:
+      example1:
+        args: [ --unsafe, --force ]
+"#,
+    );
+    e.setup();
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("run")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .assert()
+        .failure()
+        .to_string();
+    assert!(contains("TEST ARGS").count(0).eval(&r));
+    assert!(contains("changed since it was allowed").count(1).eval(&r));
+    assert!(contains(r#"- # sht args: ["--unsafe"]"#).count(1).eval(&r));
+    assert!(contains(r#"+ # sht args: ["--unsafe","--force"]"#)
+        .count(1)
+        .eval(&r));
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    command
+        .arg("allow")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .assert()
+        .success();
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("run")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("TEST ARGS=--unsafe --force").count(1).eval(&r));
+}
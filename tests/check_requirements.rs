@@ -0,0 +1,116 @@
+use predicates::prelude::Predicate;
+use predicates::str::contains;
+
+use short::BIN_NAME;
+use test_utils::init;
+use test_utils::{PROJECT_CFG_FILE, PROJECT_ENV_EXAMPLE_1_FILE, PROJECT_RUN_FILE};
+
+mod test_utils;
+
+const FAKE_NODE_FILE: &'static str = "project/bin/node";
+const FAKE_PATH_NODE_FILE: &'static str = "tools/node-of-short";
+
+#[test]
+fn cmd_check_requirements() {
+    let mut e = init("cmd_check_requirements");
+    e.add_file(PROJECT_ENV_EXAMPLE_1_FILE, "VAR1=VALUE1\nVAR2=");
+    e.add_file(
+        PROJECT_CFG_FILE,
+        r#"
+setups:
+  setup_1:
+    file: run.sh
+    requires:
+      - sh
+      - ./bin/node
+      - ./bin/node >=14
+      - node-of-short >=14
+      - not-a-tool-of-short
+      - var: VAR1
+      - var: VAR2
+      - file: run.sh
+      - file: .env.local"#,
+    );
+    e.add_file(
+        PROJECT_RUN_FILE,
+        r#"#!/bin/bash
+echo "RUN"
+"#,
+    );
+    e.add_file(
+        FAKE_NODE_FILE,
+        r#"#!/bin/bash
+touch node_has_run
+echo "v12.3.0"
+"#,
+    );
+    e.add_file(
+        FAKE_PATH_NODE_FILE,
+        r#"#!/bin/bash
+echo "v12.3.0"
+"#,
+    );
+    e.setup();
+    e.set_exec_permission(PROJECT_RUN_FILE).unwrap();
+    e.set_exec_permission(FAKE_NODE_FILE).unwrap();
+    e.set_exec_permission(FAKE_PATH_NODE_FILE).unwrap();
+    let path = format!(
+        "{}:{}",
+        e.path().unwrap().join("tools").to_string_lossy(),
+        std::env::var("PATH").unwrap()
+    );
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .env("PATH", &path)
+        .arg("run")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .assert()
+        .failure()
+        .to_string();
+    assert!(
+        contains("`./bin/node >=14` : the version of a tool of the project is not checked")
+            .count(1)
+            .eval(&r)
+    );
+    assert!(contains("`node-of-short >=14` : version 12.3.0 found")
+        .count(1)
+        .eval(&r));
+    assert!(!e.file_exists("project/node_has_run"));
+    assert!(
+        contains("`not-a-tool-of-short` : not found in the PATH, install it")
+            .count(1)
+            .eval(&r)
+    );
+    assert!(contains("`var VAR2` : not set in the env `example1`")
+        .count(1)
+        .eval(&r));
+    assert!(
+        contains("`file .env.local` : not found in the project directory")
+            .count(1)
+            .eval(&r)
+    );
+    assert!(contains("5 requirement(s) of `setup_1` missing")
+        .count(1)
+        .eval(&r));
+    assert!(contains("`sh`").count(0).eval(&r));
+    assert!(contains("RUN").count(0).eval(&r));
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .env("PATH", &path)
+        .arg("check-requirements")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .assert()
+        .failure()
+        .to_string();
+    assert!(contains("`sh` :").count(1).eval(&r));
+    assert!(contains("`var VAR1`").count(1).eval(&r));
+    assert!(contains("`file run.sh`").count(1).eval(&r));
+    assert!(contains("`./bin/node` :").count(1).eval(&r));
+    assert!(contains("5 requirement(s) of `setup_1` missing")
+        .count(1)
+        .eval(&r));
+}
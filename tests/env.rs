@@ -0,0 +1,130 @@
+use predicates::prelude::Predicate;
+use predicates::str::contains;
+
+use short::BIN_NAME;
+use test_utils::init_project;
+use test_utils::{
+    HOME_DIR, PROJECT_CFG_FILE, PROJECT_ENV_EXAMPLE_1_FILE, PROJECT_RUN_FILE, SETUP_1_CFG,
+};
+
+mod test_utils;
+
+const FILES: &[(&str, &str)] = &[
+    (PROJECT_ENV_EXAMPLE_1_FILE, "VAR1=it's\n"),
+    (PROJECT_CFG_FILE, SETUP_1_CFG),
+    (PROJECT_RUN_FILE, "#!/bin/bash\n"),
+];
+
+#[test]
+fn cmd_env_export() {
+    let e = init_project("cmd_env_export", FILES);
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .env("SHORT_EXPORTED", "OLD_VAR:VAR1")
+        .arg("env")
+        .arg("--export")
+        .args(&vec!["--shell", "bash"])
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("unset OLD_VAR;").count(1).eval(&r));
+    assert!(contains("unset VAR1;").count(0).eval(&r));
+    assert!(contains(r#"export VAR1='it'\''s';"#).count(1).eval(&r));
+    assert!(contains("export SHORT_SETUP='setup_1';").count(1).eval(&r));
+    assert!(contains("export SHORT_ENV='example1';").count(1).eval(&r));
+    assert!(
+        contains("export SHORT_EXPORTED='VAR1:SHORT_ENV:SHORT_SETUP';")
+            .count(1)
+            .eval(&r)
+    );
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("env")
+        .arg("--export")
+        .args(&vec!["--shell", "fish"])
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains(r#"set -gx VAR1 'it\'s';"#).count(1).eval(&r));
+}
+
+#[test]
+fn cmd_env_export_outside_project() {
+    let e = init_project("cmd_env_export_outside_project", FILES);
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .current_dir(e.path().unwrap().join(HOME_DIR))
+        .env("SHORT_EXPORTED", "VAR1:SHORT_ENV")
+        .arg("env")
+        .arg("--export")
+        .args(&vec!["--shell", "bash"])
+        .assert()
+        .failure()
+        .to_string();
+    assert!(contains("unset VAR1;").count(1).eval(&r));
+    assert!(contains("unset SHORT_ENV;").count(1).eval(&r));
+    assert!(contains("unset SHORT_EXPORTED;").count(1).eval(&r));
+}
+
+#[test]
+fn cmd_env_hook() {
+    let e = init_project("cmd_env_hook", FILES);
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("env")
+        .arg("--hook")
+        .args(&vec!["--shell", "zsh"])
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("chpwd_functions").count(4).eval(&r));
+    assert!(contains("sht env --export --shell zsh").count(1).eval(&r));
+}
+
+#[test]
+fn cmd_env_hook_sh() {
+    let e = init_project("cmd_env_hook_sh", FILES);
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("env")
+        .arg("--hook")
+        .args(&vec!["--shell", "sh"])
+        .assert()
+        .failure()
+        .to_string();
+    assert!(contains("PROMPT_COMMAND").count(0).eval(&r));
+    assert!(contains("no hook for the shell `sh`").count(1).eval(&r));
+}
+
+#[test]
+fn cmd_env_export_shell_control_vars() {
+    let mut e = init_project("cmd_env_export_shell_control_vars", FILES);
+    e.add_file(
+        PROJECT_ENV_EXAMPLE_1_FILE,
+        "VAR1=VALUE1\nPROMPT_COMMAND=touch pwned\n",
+    );
+    e.setup();
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("env")
+        .arg("--export")
+        .args(&vec!["--shell", "bash"])
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("export VAR1='VALUE1';").count(1).eval(&r));
+    assert!(contains("export PROMPT_COMMAND").count(0).eval(&r));
+    assert!(contains("# PROMPT_COMMAND not exported").count(1).eval(&r));
+}
@@ -0,0 +1,65 @@
+use predicates::prelude::Predicate;
+use predicates::str::contains;
+
+use short::BIN_NAME;
+use test_utils::init;
+use test_utils::{PROJECT_CFG_FILE, PROJECT_ENV_EXAMPLE_1_FILE, PROJECT_RUN_FILE};
+
+mod test_utils;
+
+#[test]
+fn cmd_exec() {
+    let mut e = init("cmd_exec");
+
+    e.add_file(PROJECT_ENV_EXAMPLE_1_FILE, r#"VAR1=VALUE1"#);
+    e.add_file(
+        PROJECT_CFG_FILE,
+        r#"
+setups:
+  setup_1:
+    file: run.sh
+"#,
+    );
+    e.add_file(PROJECT_RUN_FILE, "#!/bin/bash\n");
+    e.add_dir("project/sub");
+    e.setup();
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("exec")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .args(&vec![
+            "--",
+            "bash",
+            "-c",
+            "echo \"VAR1=$VAR1 $SHORT_SETUP:$SHORT_ENV\"; echo ARG=$0",
+            "-a",
+        ])
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("VAR1=VALUE1 setup_1:example1").count(1).eval(&r));
+    assert!(contains("ARG=-a").count(1).eval(&r));
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .current_dir(e.path().unwrap().join("project/sub"))
+        .arg("exec")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .args(&vec!["--", "pwd"])
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("project/sub\n").count(1).eval(&r));
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    command
+        .arg("exec")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .args(&vec!["--", "bash", "-c", "exit 7"])
+        .assert()
+        .code(7);
+}
@@ -0,0 +1,204 @@
+use predicates::prelude::Predicate;
+use predicates::str::contains;
+
+use short::BIN_NAME;
+use test_utils::init_project;
+use test_utils::{
+    HOME_CFG_FILE, PRIVATE_ENV_DEV_FILE, PRIVATE_ENV_DIR, PROJECT_CFG_FILE,
+    PROJECT_ENV_EXAMPLE_1_FILE, PROJECT_ENV_EXAMPLE_2_FILE, PROJECT_RUN_FILE, SETUP_1_CFG,
+};
+
+mod test_utils;
+
+const FILES: &[(&str, &str)] = &[
+    (PROJECT_ENV_EXAMPLE_1_FILE, "VAR1=VALUE1\n"),
+    (PROJECT_ENV_EXAMPLE_2_FILE, "VAR1=VALUE2\n"),
+    (PROJECT_CFG_FILE, SETUP_1_CFG),
+    (
+        PROJECT_RUN_FILE,
+        r#"#!/bin/bash
+echo "TEST VAR1=$VAR1 ARGS=$@"
+exit $1
+"#,
+    ),
+];
+
+#[test]
+fn cmd_history() {
+    let e = init_project("cmd_history", FILES);
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    command
+        .arg("run")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .arg("0")
+        .assert()
+        .success();
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    command
+        .arg("run")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example2"])
+        .arg("3")
+        .assert()
+        .code(3);
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    command
+        .arg("exec")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .args(&vec!["--", "true"])
+        .assert()
+        .success();
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command.arg("history").assert().success().to_string();
+    assert!(contains("setup_1:example1 run 0").count(1).eval(&r));
+    assert!(contains("setup_1:example2 run 3").count(1).eval(&r));
+    assert!(contains("setup_1:example1 exec true").count(1).eval(&r));
+    assert!(contains("status 3").count(1).eval(&r));
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("history")
+        .args(&vec!["-e", "example2"])
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("setup_1:example2").count(1).eval(&r));
+    assert!(contains("setup_1:example1").count(0).eval(&r));
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("history")
+        .args(&vec!["-n", "1"])
+        .arg("--json")
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains(r#""id": 3"#).count(1).eval(&r));
+    assert!(contains(r#""command": "exec""#).count(1).eval(&r));
+    assert!(contains(r#""command": "run""#).count(0).eval(&r));
+}
+
+#[test]
+fn cmd_history_rerun() {
+    let e = init_project("cmd_history_rerun", FILES);
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    command
+        .arg("run")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example2"])
+        .args(&vec!["--timeout", "10s"])
+        .arg("3")
+        .assert()
+        .code(3);
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("history")
+        .arg("rerun")
+        .arg("1")
+        .assert()
+        .code(3)
+        .to_string();
+    assert!(
+        contains("rerun 1 `run -s setup_1 -e example2 --timeout 10s -- 3`")
+            .count(1)
+            .eval(&r)
+    );
+    assert!(contains("TEST VAR1=VALUE2 ARGS=3").count(1).eval(&r));
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("history")
+        .arg("rerun")
+        .arg("9")
+        .assert()
+        .failure()
+        .to_string();
+    assert!(contains("history entry `9` not found").count(1).eval(&r));
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command.arg("history").assert().success().to_string();
+    assert!(contains("setup_1:example2 run --timeout 10s 3")
+        .count(2)
+        .eval(&r));
+}
+
+#[test]
+fn cmd_history_masked_args() {
+    let mut e = init_project("cmd_history_masked_args", FILES);
+    e.add_file(
+        PROJECT_CFG_FILE,
+        r#"
+setups:
+  setup_1:
+    file: run.sh
+    secrets: [ VAR2 ]
+"#,
+    );
+    e.add_file(PROJECT_ENV_EXAMPLE_1_FILE, "VAR1=VALUE1\nVAR2=SECRET2\n");
+    e.add_file(PRIVATE_ENV_DEV_FILE, "VAR1=PRIVATE1\n");
+    e.add_file(
+        HOME_CFG_FILE,
+        format!(
+            r"
+projects:
+  - file: {file}
+    setups:
+      setup_1:
+        private_env_dir: {private_env_dir}
+    ",
+            file = e.path().unwrap().join(PROJECT_CFG_FILE).to_string_lossy(),
+            private_env_dir = e.path().unwrap().join(PRIVATE_ENV_DIR).to_string_lossy()
+        ),
+    );
+    e.setup();
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    command
+        .arg("run")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "dev"])
+        .args(&vec!["--", "0", "token=PRIVATE1"])
+        .assert()
+        .success();
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    command
+        .arg("run")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .args(&vec!["0", "SECRET2", "VALUE1"])
+        .assert()
+        .success();
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command.arg("history").assert().success().to_string();
+    assert!(contains("setup_1:dev run 0 token=********")
+        .count(1)
+        .eval(&r));
+    assert!(contains("setup_1:example1 run 0 ******** VALUE1")
+        .count(1)
+        .eval(&r));
+    assert!(contains("PRIVATE1").count(0).eval(&r));
+    assert!(contains("SECRET2").count(0).eval(&r));
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("history")
+        .arg("rerun")
+        .arg("2")
+        .assert()
+        .failure()
+        .to_string();
+    assert!(contains("history entry `2` has secret values masked")
+        .count(1)
+        .eval(&r));
+    assert!(contains("VALUE1").count(0).eval(&r));
+}
@@ -0,0 +1,151 @@
+use predicates::prelude::Predicate;
+use predicates::str::contains;
+
+use short::BIN_NAME;
+use test_utils::init_project;
+use test_utils::{
+    HOME_CFG_FILE, PROJECT_CFG_FILE, PROJECT_ENV_EXAMPLE_1_FILE, PROJECT_ENV_EXAMPLE_2_FILE,
+    PROJECT_RUN_FILE,
+};
+
+mod test_utils;
+
+const FILES: &[(&str, &str)] = &[
+    (PROJECT_ENV_EXAMPLE_1_FILE, "VAR1=VALUE1\n"),
+    (PROJECT_ENV_EXAMPLE_2_FILE, "# sht:protected\nVAR1=VALUE1\n"),
+    (
+        PROJECT_CFG_FILE,
+        r#"
+setups:
+  setup_1:
+    file: run.sh
+    protected:
+      - example1
+"#,
+    ),
+    (
+        PROJECT_RUN_FILE,
+        r#"#!/bin/bash
+echo "TEST VAR1=$VAR1"
+"#,
+    ),
+];
+
+#[test]
+fn cmd_run_protected() {
+    let e = init_project("cmd_run_protected", FILES);
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .write_stdin("example1\n")
+        .arg("run")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("PROTECTED ENV `example1`").count(1).eval(&r));
+    assert!(contains("TEST VAR1=VALUE1").count(1).eval(&r));
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .write_stdin("example\n")
+        .arg("run")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .assert()
+        .failure()
+        .to_string();
+    assert!(contains("protected env `example1` not confirmed")
+        .count(1)
+        .eval(&r));
+    assert!(contains("TEST VAR1=VALUE1").count(0).eval(&r));
+}
+
+#[test]
+fn cmd_sync_protected() {
+    let mut e = init_project("cmd_sync_protected", FILES);
+    e.add_file(PROJECT_ENV_EXAMPLE_1_FILE, "VAR1=VALUE1\nVAR2=VALUE2\n");
+    e.setup();
+    e.set_update_file_time(PROJECT_ENV_EXAMPLE_1_FILE).unwrap();
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .write_stdin("\n")
+        .arg("sync")
+        .arg("--copy")
+        .args(&vec!["-s", "setup_1"])
+        .assert()
+        .failure()
+        .to_string();
+    assert!(contains("PROTECTED ENV `example2`").count(1).eval(&r));
+    assert!(contains("protected env `example2` not confirmed")
+        .count(1)
+        .eval(&r));
+    let content = e.read_file(PROJECT_ENV_EXAMPLE_2_FILE);
+    assert_eq!(content, "# sht:protected\nVAR1=VALUE1\n");
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .write_stdin("example2\n")
+        .arg("sync")
+        .arg("--copy")
+        .args(&vec!["-s", "setup_1"])
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("files synchronized").count(1).eval(&r));
+    let content = e.read_file(PROJECT_ENV_EXAMPLE_2_FILE);
+    assert_eq!(content, "# sht:protected\nVAR1=VALUE1\nVAR2=VALUE2\n");
+}
+
+#[test]
+fn cmd_sync_protected_before_prompts() {
+    let mut e = init_project("cmd_sync_protected_before_prompts", FILES);
+    e.add_file(PROJECT_ENV_EXAMPLE_1_FILE, "VAR1=VALUE1\nVAR2=VALUE2\n");
+    e.setup();
+    e.set_update_file_time(PROJECT_ENV_EXAMPLE_1_FILE).unwrap();
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .write_stdin("\n")
+        .arg("sync")
+        .args(&vec!["-s", "setup_1"])
+        .assert()
+        .failure()
+        .to_string();
+    assert!(contains("protected env `example2` not confirmed")
+        .count(1)
+        .eval(&r));
+    assert!(contains("Change value ?").count(0).eval(&r));
+    let content = e.read_file(PROJECT_ENV_EXAMPLE_2_FILE);
+    assert_eq!(content, "# sht:protected\nVAR1=VALUE1\n");
+}
+
+#[test]
+fn cmd_show_protected() {
+    let mut e = init_project("cmd_show_protected", FILES);
+    e.add_file(
+        HOME_CFG_FILE,
+        format!(
+            r#"
+projects:
+  - file: {file}
+    current:
+        setup: setup_1
+        env: example2
+    setups: {{}}"#,
+            file = e.path().unwrap().join(PROJECT_CFG_FILE).to_string_lossy()
+        ),
+    );
+    e.setup();
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("show")
+        .args(&vec!["-f", "[{setup}:{env}{protected}]"])
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("[setup_1:example2🔒]").count(1).eval(&r));
+}
@@ -0,0 +1,54 @@
+use predicates::prelude::Predicate;
+use predicates::str::contains;
+
+use short::BIN_NAME;
+use test_utils::init_project;
+use test_utils::{PROJECT_CFG_FILE, PROJECT_ENV_EXAMPLE_1_FILE, PROJECT_RUN_FILE, SETUP_1_CFG};
+
+mod test_utils;
+
+const FILES: &[(&str, &str)] = &[
+    (PROJECT_ENV_EXAMPLE_1_FILE, "VAR1=VALUE1"),
+    (PROJECT_CFG_FILE, SETUP_1_CFG),
+    (PROJECT_RUN_FILE, "#!/bin/bash\n"),
+];
+
+#[test]
+fn cmd_shell() {
+    let e = init_project("cmd_shell", FILES);
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .env("SHELL", "/bin/bash")
+        .env_remove("SHORT_SHELL")
+        .write_stdin("echo \"VAR1=$VAR1 SHELL=$SHORT_SHELL\"\nexit 4\n")
+        .arg("shell")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .assert()
+        .code(4)
+        .to_string();
+    assert!(contains("enter shell `setup_1:example1`").count(1).eval(&r));
+    assert!(contains("VAR1=VALUE1 SHELL=setup_1:example1")
+        .count(1)
+        .eval(&r));
+}
+
+#[test]
+fn cmd_shell_nested() {
+    let e = init_project("cmd_shell_nested", FILES);
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .env("SHELL", "/bin/bash")
+        .env("SHORT_SHELL", "setup_1:example1")
+        .arg("shell")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .assert()
+        .failure()
+        .to_string();
+    assert!(contains("already in the shell `setup_1:example1`")
+        .count(1)
+        .eval(&r));
+}
@@ -0,0 +1,234 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use predicates::prelude::Predicate;
+use predicates::str::contains;
+use serde_json::{json, Value};
+
+use mock_server::{MockServer, Response};
+use short::BIN_NAME;
+use test_utils::init_project;
+use test_utils::{PROJECT_CFG_FILE, PROJECT_ENV_EXAMPLE_1_FILE};
+
+mod mock_server;
+mod test_utils;
+
+/// Parameter name -> (type, value)
+type Store = Arc<Mutex<BTreeMap<String, (String, String)>>>;
+
+fn ssm_server(store: &Store) -> MockServer {
+    let store = Arc::clone(store);
+    MockServer::start(move |request| {
+        let authorization = request.headers.get("authorization").cloned();
+        if !authorization.map_or(false, |a| a.contains("Credential=AKID/")) {
+            return Response::new(403, r#"{"message":"missing authentication"}"#);
+        }
+        let body: Value = serde_json::from_str(&request.body).unwrap();
+        let mut store = store.lock().unwrap();
+        match request.headers.get("x-amz-target").map(|t| t.as_str()) {
+            Some("AmazonSSM.PutParameter") => {
+                store.insert(
+                    body["Name"].as_str().unwrap().to_string(),
+                    (
+                        body["Type"].as_str().unwrap().to_string(),
+                        body["Value"].as_str().unwrap().to_string(),
+                    ),
+                );
+                Response::new(200, r#"{"Version":1}"#)
+            }
+            Some("AmazonSSM.GetParametersByPath") => {
+                let prefix = format!("{}/", body["Path"].as_str().unwrap());
+                let parameters: Vec<_> = store
+                    .iter()
+                    .filter(|(name, _)| name.starts_with(&prefix))
+                    .map(
+                        |(name, (kind, value))| json!({"Name": name, "Type": kind, "Value": value}),
+                    )
+                    .collect();
+                Response::new(200, json!({ "Parameters": parameters }).to_string())
+            }
+            _ => Response::new(400, r#"{"message":"unknown target"}"#),
+        }
+    })
+}
+
+const FILES: &[(&str, &str)] = &[
+    (
+        PROJECT_ENV_EXAMPLE_1_FILE,
+        "# comment\nVAR1=VALUE1\nSECRET_VAR=SECRET_VALUE\n",
+    ),
+    (
+        PROJECT_CFG_FILE,
+        r#"
+setups:
+  setup_1:
+    file: run.sh
+    secrets:
+      - secret_var
+    ssm:
+      path: /myapp/{env}
+"#,
+    ),
+];
+
+#[test]
+fn cmd_ssm_push() {
+    let e = init_project("cmd_ssm_push", FILES);
+    let store = Store::default();
+    let server = ssm_server(&store);
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .env("AWS_ENDPOINT_URL_SSM", server.addr())
+        .env("AWS_ACCESS_KEY_ID", "AKID")
+        .env("AWS_SECRET_ACCESS_KEY", "SECRET")
+        .arg("ssm")
+        .arg("push")
+        .args(&["-s", "setup_1"])
+        .args(&["-e", "example1"])
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("`example1` pushed to `/myapp/example1`")
+        .count(1)
+        .eval(&r));
+
+    let store = store.lock().unwrap();
+    assert_eq!(
+        store.get("/myapp/example1/VAR1").unwrap(),
+        &("String".to_string(), "VALUE1".to_string())
+    );
+    assert_eq!(
+        store.get("/myapp/example1/SECRET_VAR").unwrap(),
+        &("SecureString".to_string(), "SECRET_VALUE".to_string())
+    );
+}
+
+#[test]
+fn cmd_ssm_pull() {
+    let e = init_project("cmd_ssm_pull", FILES);
+    let store = Store::default();
+    {
+        let mut store = store.lock().unwrap();
+        store.insert(
+            "/myapp/example1/VAR1".into(),
+            ("String".into(), "VALUE1".into()),
+        );
+        store.insert(
+            "/myapp/example1/SECRET_VAR".into(),
+            ("SecureString".into(), "NEW_SECRET_VALUE".into()),
+        );
+        store.insert(
+            "/myapp/example1/VAR2".into(),
+            ("String".into(), "VALUE2".into()),
+        );
+        store.insert(
+            "/myapp/example2/VAR3".into(),
+            ("String".into(), "VALUE3".into()),
+        );
+    }
+    let server = ssm_server(&store);
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .env("AWS_ENDPOINT_URL_SSM", server.addr())
+        .env("AWS_ACCESS_KEY_ID", "AKID")
+        .env("AWS_SECRET_ACCESS_KEY", "SECRET")
+        .arg("ssm")
+        .arg("pull")
+        .arg("--yes")
+        .args(&["-s", "setup_1"])
+        .args(&["-e", "example1"])
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("SECRET_VAR=NEW_SECRET_VALUE").count(1).eval(&r));
+    assert!(contains("VAR2=VALUE2").count(1).eval(&r));
+    assert!(contains("`example1` pulled from `/myapp/example1`")
+        .count(1)
+        .eval(&r));
+
+    let content = e.read_file(PROJECT_ENV_EXAMPLE_1_FILE);
+    assert_eq!(
+        content,
+        "# comment\nVAR1=VALUE1\nSECRET_VAR=NEW_SECRET_VALUE\nVAR2=VALUE2\n"
+    );
+}
+
+#[test]
+fn cmd_ssm_pull_protected() {
+    let mut e = init_project("cmd_ssm_pull_protected", FILES);
+    e.add_file(PROJECT_ENV_EXAMPLE_1_FILE, "# sht:protected\nVAR1=VALUE1\n");
+    e.setup();
+    let store = Store::default();
+    store.lock().unwrap().insert(
+        "/myapp/example1/VAR1".into(),
+        ("String".into(), "NEW_VALUE1".into()),
+    );
+    let server = ssm_server(&store);
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .env("AWS_ENDPOINT_URL_SSM", server.addr())
+        .env("AWS_ACCESS_KEY_ID", "AKID")
+        .env("AWS_SECRET_ACCESS_KEY", "SECRET")
+        .write_stdin("\n")
+        .arg("ssm")
+        .arg("pull")
+        .arg("--yes")
+        .args(&["-s", "setup_1"])
+        .args(&["-e", "example1"])
+        .assert()
+        .failure()
+        .to_string();
+    assert!(contains("protected env `example1` not confirmed")
+        .count(1)
+        .eval(&r));
+    let content = e.read_file(PROJECT_ENV_EXAMPLE_1_FILE);
+    assert_eq!(content, "# sht:protected\nVAR1=VALUE1\n");
+}
+
+#[test]
+fn cmd_ssm_credentials_not_found() {
+    let e = init_project("cmd_ssm_credentials_not_found", FILES);
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .env_remove("AWS_ACCESS_KEY_ID")
+        .env_remove("AWS_SECRET_ACCESS_KEY")
+        .arg("ssm")
+        .arg("push")
+        .args(&["-s", "setup_1"])
+        .args(&["-e", "example1"])
+        .assert()
+        .failure()
+        .to_string();
+    assert!(contains("aws credentials not found").count(1).eval(&r));
+}
+
+#[test]
+fn cmd_ssm_pull_no_parameters() {
+    let e = init_project("cmd_ssm_pull_no_parameters", FILES);
+    let store = Store::default();
+    let server = ssm_server(&store);
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .env("AWS_ENDPOINT_URL_SSM", server.addr())
+        .env("AWS_ACCESS_KEY_ID", "AKID")
+        .env("AWS_SECRET_ACCESS_KEY", "SECRET")
+        .arg("ssm")
+        .arg("pull")
+        .arg("--yes")
+        .args(&["-s", "setup_1"])
+        .args(&["-e", "example1"])
+        .assert()
+        .failure()
+        .to_string();
+    assert!(contains("no parameter found under `/myapp/example1`")
+        .count(1)
+        .eval(&r));
+
+    let content = e.read_file(PROJECT_ENV_EXAMPLE_1_FILE);
+    assert_eq!(content, "# comment\nVAR1=VALUE1\nSECRET_VAR=SECRET_VALUE\n");
+}
@@ -0,0 +1,240 @@
+use std::process::Command;
+use std::time::Duration;
+
+use predicates::prelude::Predicate;
+use predicates::str::contains;
+
+use short::BIN_NAME;
+use test_utils::{init, wait_until};
+use test_utils::{HOME_DIR, PROJECT_CFG_FILE, PROJECT_ENV_EXAMPLE_1_FILE, PROJECT_RUN_FILE};
+
+mod test_utils;
+
+#[test]
+fn cmd_start_ps_logs_stop() {
+    let mut e = init("cmd_start_ps_logs_stop");
+    e.add_file(PROJECT_ENV_EXAMPLE_1_FILE, r#"VAR1=VALUE1"#);
+    e.add_file(
+        PROJECT_CFG_FILE,
+        r#"
+setups:
+  setup_1:
+    file: run.sh
+    commands:
+      job:
+        command: echo "JOB VAR1=$VAR1""#,
+    );
+    e.add_file(
+        PROJECT_RUN_FILE,
+        r#"#!/bin/bash
+echo "SERVE VAR1=$VAR1 ARGS=$@"
+while true; do sleep 0.1; done
+"#,
+    );
+    e.setup();
+    e.set_exec_permission(PROJECT_RUN_FILE).unwrap();
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("start")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .arg("arg1")
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("`setup_1` started on `example1` with pid")
+        .count(1)
+        .eval(&r));
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("start")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .assert()
+        .failure()
+        .to_string();
+    assert!(contains("`setup_1` is already running with pid")
+        .count(1)
+        .eval(&r));
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command.arg("ps").assert().success().to_string();
+    assert!(contains("setup_1:example1 running").count(1).eval(&r));
+
+    let logs = || {
+        let mut command = e.command(BIN_NAME).unwrap();
+        command
+            .arg("logs")
+            .args(&vec!["-s", "setup_1"])
+            .args(&vec!["-e", "example1"])
+            .assert()
+            .success()
+            .to_string()
+    };
+    assert!(wait_until(Duration::from_secs(10), || logs()
+        .contains("SERVE VAR1=VALUE1 ARGS=arg1")));
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("stop")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("`setup_1:example1` stopped").count(1).eval(&r));
+    assert!(contains("kill it").count(0).eval(&r));
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command.arg("ps").assert().success().to_string();
+    assert!(contains("setup_1:example1").count(0).eval(&r));
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    command
+        .arg("start")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .arg("job")
+        .assert()
+        .success();
+
+    let ps = || {
+        let mut command = e.command(BIN_NAME).unwrap();
+        command.arg("ps").assert().success().to_string()
+    };
+    assert!(wait_until(Duration::from_secs(10), || ps()
+        .contains("setup_1:job:example1 stopped")));
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("ps")
+        .arg("--clean")
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("1 stopped instance(s) removed").count(1).eval(&r));
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("logs")
+        .arg("999999")
+        .assert()
+        .failure()
+        .to_string();
+    assert!(contains("instance `999999` not found").count(1).eval(&r));
+}
+
+#[test]
+fn cmd_stop_reused_pid() {
+    let mut e = init("cmd_stop_reused_pid");
+    let mut other = Command::new("sleep").arg("30").spawn().unwrap();
+    e.add_file(
+        format!("{}/.short/instances/{}.json", HOME_DIR, other.id()),
+        format!(
+            r#"{{
+  "pid": {pid},
+  "start_time": 1,
+  "started_at": "2020-01-01T00:00:00Z",
+  "project": "{project}",
+  "setup": "setup_1",
+  "env": "example1",
+  "args": [],
+  "log": "{project}/setup_1.log"
+}}"#,
+            pid = other.id(),
+            project = e.path().unwrap().join("project").to_string_lossy()
+        ),
+    );
+    e.setup();
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("stop")
+        .arg(other.id().to_string())
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("`setup_1:example1` was not running, removed")
+        .count(1)
+        .eval(&r));
+    assert!(other.try_wait().unwrap().is_none());
+    other.kill().unwrap();
+    other.wait().unwrap();
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command.arg("ps").assert().success().to_string();
+    assert!(contains("setup_1:example1").count(0).eval(&r));
+}
+
+#[test]
+fn cmd_start_hooks() {
+    let mut e = init("cmd_start_hooks");
+    e.add_file(PROJECT_ENV_EXAMPLE_1_FILE, r#"VAR1=VALUE1"#);
+    e.add_file(
+        PROJECT_CFG_FILE,
+        r#"
+setups:
+  setup_1:
+    file: run.sh
+    hooks:
+      pre_run: echo "PRE RUN VAR1=$VAR1"
+      post_run: echo "POST RUN""#,
+    );
+    e.add_file(PROJECT_RUN_FILE, "#!/bin/bash\necho \"SERVE VAR1=$VAR1\"\n");
+    e.setup();
+    e.set_exec_permission(PROJECT_RUN_FILE).unwrap();
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    command
+        .arg("allow")
+        .args(&vec!["-s", "setup_1"])
+        .assert()
+        .success();
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("start")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .assert()
+        .failure()
+        .to_string();
+    assert!(
+        contains("the `post_run` hook of `setup_1` can't run after a start in background")
+            .count(1)
+            .eval(&r)
+    );
+    assert!(contains("PRE RUN").count(0).eval(&r));
+
+    e.add_file(
+        PROJECT_CFG_FILE,
+        r#"
+setups:
+  setup_1:
+    file: run.sh
+    hooks:
+      pre_run: echo "PRE RUN VAR1=$VAR1""#,
+    );
+    e.setup();
+    let mut command = e.command(BIN_NAME).unwrap();
+    command
+        .arg("allow")
+        .args(&vec!["-s", "setup_1"])
+        .assert()
+        .success();
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("start")
+        .args(&vec!["-s", "setup_1"])
+        .args(&vec!["-e", "example1"])
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("PRE RUN VAR1=VALUE1").count(1).eval(&r));
+    assert!(contains("`setup_1` started on `example1`")
+        .count(1)
+        .eval(&r));
+}
@@ -1,4 +1,7 @@
 #![allow(dead_code)]
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
 use cli_integration_test::IntegrationTestEnvironment;
 
 pub const HOME_DIR: &'static str = "home";
@@ -12,6 +15,11 @@ pub const PROJECT_ENV_DIR: &'static str = "project/env";
 pub const PRIVATE_ENV_DIR: &'static str = "private_env";
 pub const PRIVATE_ENV_DEV_FILE: &'static str = "private_env/.dev";
 pub const TMP_DIR: &'static str = "tmp";
+pub const SETUP_1_CFG: &'static str = r#"
+setups:
+  setup_1:
+    file: run.sh
+"#;
 
 pub fn init<L: AsRef<str>>(label: L) -> IntegrationTestEnvironment {
     let mut e = IntegrationTestEnvironment::new(label);
@@ -34,3 +42,28 @@ pub fn init<L: AsRef<str>>(label: L) -> IntegrationTestEnvironment {
     });
     e
 }
+
+/// `init` with the given project files, the run file is made executable.
+pub fn init_project<L: AsRef<str>>(label: L, files: &[(&str, &str)]) -> IntegrationTestEnvironment {
+    let mut e = init(label);
+    for (file, content) in files {
+        e.add_file(file, content);
+    }
+    e.setup();
+    if files.iter().any(|(file, _)| *file == PROJECT_RUN_FILE) {
+        e.set_exec_permission(PROJECT_RUN_FILE).unwrap();
+    }
+    e
+}
+
+/// Poll `condition` until it's true, false if it's still not after `timeout`.
+pub fn wait_until<F: FnMut() -> bool>(timeout: Duration, mut condition: F) -> bool {
+    let deadline = Instant::now() + timeout;
+    while !condition() {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        sleep(Duration::from_millis(50));
+    }
+    true
+}
@@ -0,0 +1,199 @@
+use std::fs::read_to_string;
+
+use predicates::prelude::Predicate;
+use predicates::str::contains;
+
+use short::BIN_NAME;
+use test_utils::init_project;
+use test_utils::{
+    PROJECT_CFG_FILE, PROJECT_ENV_EXAMPLE_1_FILE, PROJECT_ENV_EXAMPLE_2_FILE, PROJECT_RUN_FILE,
+};
+
+mod test_utils;
+
+const ENV_EXAMPLE_1: (&str, &str) = (PROJECT_ENV_EXAMPLE_1_FILE, "VAR1=VALUE1");
+const ENV_EXAMPLE_2: (&str, &str) = (PROJECT_ENV_EXAMPLE_2_FILE, "VAR1=VALUE2");
+const RUN_FILE: (&str, &str) = (
+    PROJECT_RUN_FILE,
+    r#"#!/bin/bash
+echo "SERVE VAR1=$VAR1"
+sleep 30
+echo "NOT STOPPED"
+"#,
+);
+const SETUP_CFG: &str = r#"
+setups:
+  setup_1:
+    file: run.sh
+    commands:
+      job:
+        command: sleep 1; echo "JOB VAR1=$VAR1"; exit 3"#;
+
+#[test]
+fn cmd_up() {
+    let e = init_project(
+        "cmd_up",
+        &[
+            ENV_EXAMPLE_1,
+            ENV_EXAMPLE_2,
+            RUN_FILE,
+            (
+                PROJECT_CFG_FILE,
+                &format!(
+                    "{}{}",
+                    SETUP_CFG,
+                    r#"
+groups:
+  dev:
+    processes:
+      web:
+        run: setup_1
+        env: example2
+      job:
+        run: setup_1:job"#
+                ),
+            ),
+        ],
+    );
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("up")
+        .args(&vec!["-e", "example1"])
+        .assert()
+        .code(3)
+        .to_string();
+
+    assert!(contains("up `dev` : 2 process(es)").count(1).eval(&r));
+    assert!(contains("web | SERVE VAR1=VALUE2").count(1).eval(&r));
+    assert!(contains("job | JOB VAR1=VALUE1").count(1).eval(&r));
+    assert!(contains("job | exit with status 3").count(1).eval(&r));
+    assert!(contains("stop the other processes").count(1).eval(&r));
+    assert!(contains("NOT STOPPED").count(0).eval(&r));
+}
+
+#[test]
+fn cmd_up_keep_running() {
+    let e = init_project(
+        "cmd_up_keep_running",
+        &[
+            ENV_EXAMPLE_1,
+            ENV_EXAMPLE_2,
+            RUN_FILE,
+            (
+                PROJECT_CFG_FILE,
+                &format!(
+                    "{}{}",
+                    SETUP_CFG,
+                    r#"
+groups:
+  dev:
+    stop_on_exit: false
+    processes:
+      job:
+        run: setup_1:job
+      other:
+        run: setup_1:job
+        env: example2
+  other:
+    processes:
+      web:
+        run: setup_1"#
+                ),
+            ),
+        ],
+    );
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("up")
+        .args(&vec!["-e", "example1"])
+        .assert()
+        .failure()
+        .to_string();
+    assert!(
+        contains("several process groups found, choose one of `dev`, `other`")
+            .count(1)
+            .eval(&r)
+    );
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("up")
+        .arg("dev")
+        .args(&vec!["-e", "example1"])
+        .assert()
+        .failure()
+        .to_string();
+    assert!(contains("job   | JOB VAR1=VALUE1").count(1).eval(&r));
+    assert!(contains("other | JOB VAR1=VALUE2").count(1).eval(&r));
+    assert!(contains("exit with status 3").count(2).eval(&r));
+    assert!(contains("stop the other processes").count(0).eval(&r));
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("up")
+        .arg("unknown")
+        .assert()
+        .failure()
+        .to_string();
+    assert!(contains("process group `unknown` not found")
+        .count(1)
+        .eval(&r));
+}
+
+#[test]
+fn cmd_up_import_procfile() {
+    let mut e = init_project(
+        "cmd_up_import_procfile",
+        &[
+            ENV_EXAMPLE_1,
+            ENV_EXAMPLE_2,
+            RUN_FILE,
+            (PROJECT_CFG_FILE, SETUP_CFG),
+        ],
+    );
+    e.add_file(
+        "project/Procfile",
+        r#"
+# processes
+hello: echo "HELLO VAR1=$VAR1"
+world: echo "WORLD"
+"#,
+    );
+    e.setup();
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let procfile = e.path().unwrap().join("project/Procfile");
+    let r = command
+        .arg("up")
+        .arg("--import")
+        .arg(procfile)
+        .args(&vec!["-s", "setup_1"])
+        .assert()
+        .success()
+        .to_string();
+    assert!(
+        contains("2 process(es) imported in the group `procfile` as commands of `setup_1`")
+            .count(1)
+            .eval(&r)
+    );
+
+    let cfg = read_to_string(e.path().unwrap().join(PROJECT_CFG_FILE)).unwrap();
+    assert!(contains("command: \"echo \\\"HELLO VAR1=$VAR1\\\"\"")
+        .count(1)
+        .eval(&cfg));
+    assert!(contains("run: \"setup_1:world\"").count(1).eval(&cfg));
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("up")
+        .arg("procfile")
+        .arg("--keep-running")
+        .args(&vec!["-e", "example1"])
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("hello | HELLO VAR1=VALUE1").count(1).eval(&r));
+    assert!(contains("world | WORLD").count(1).eval(&r));
+}
@@ -5,7 +5,7 @@ use short::BIN_NAME;
 use test_utils::init;
 use test_utils::{
     HOME_CFG_FILE, PRIVATE_ENV_DEV_FILE, PRIVATE_ENV_DIR, PROJECT_CFG_FILE,
-    PROJECT_ENV_EXAMPLE_1_FILE, PROJECT_ENV_EXAMPLE_2_FILE,
+    PROJECT_ENV_EXAMPLE_1_FILE, PROJECT_ENV_EXAMPLE_2_FILE, PROJECT_RUN_FILE,
 };
 
 mod test_utils;
@@ -219,3 +219,54 @@ projects:
     let content = e.read_file(HOME_CFG_FILE);
     assert!(contains("env: example2").count(1).eval(&content));
 }
+
+#[test]
+fn cmd_use_post_use_hook() {
+    let mut e = init("cmd_use_post_use_hook");
+
+    e.add_file(PROJECT_ENV_EXAMPLE_1_FILE, r#"VAR1=VALUE1"#);
+    e.add_file(
+        PROJECT_CFG_FILE,
+        r#"
+setups:
+  setup_1:
+    file: run.sh
+    hooks:
+      post_use: echo "POST USE $SHORT_ENV VAR1=$VAR1"
+        "#,
+    );
+    e.add_file(PROJECT_RUN_FILE, "#!/bin/bash\n");
+    e.setup();
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("use")
+        .arg("setup_1")
+        .arg("example1")
+        .assert()
+        .failure()
+        .to_string();
+    assert!(contains("hooks of `setup_1` are not allowed")
+        .count(1)
+        .eval(&r));
+    assert!(contains("POST USE example1").count(0).eval(&r));
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    command
+        .arg("allow")
+        .args(&vec!["-s", "setup_1"])
+        .assert()
+        .success();
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .arg("use")
+        .arg("setup_1")
+        .arg("example1")
+        .assert()
+        .success()
+        .to_string();
+
+    assert!(contains("your current setup is `setup_1:example1`").eval(&r));
+    assert!(contains("POST USE example1 VAR1=VALUE1").count(1).eval(&r));
+}
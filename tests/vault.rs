@@ -0,0 +1,419 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use predicates::prelude::Predicate;
+use predicates::str::contains;
+use serde_json::{json, Value};
+
+use mock_server::{MockServer, Response};
+use short::BIN_NAME;
+use test_utils::init_project;
+use test_utils::{
+    HOME_CFG_FILE, PROJECT_CFG_FILE, PROJECT_ENV_EXAMPLE_1_FILE, PROJECT_RUN_FILE, SETUP_1_CFG,
+};
+
+mod mock_server;
+mod test_utils;
+
+const VAULT_TOKEN: &str = "s.token";
+
+type Store = Arc<Mutex<BTreeMap<String, BTreeMap<String, String>>>>;
+
+fn vault_server(store: &Store) -> MockServer {
+    vault_server_with_metadata(store, &Store::default())
+}
+
+/// `metadata` is the custom metadata of the secrets.
+fn vault_server_with_metadata(store: &Store, metadata: &Store) -> MockServer {
+    let store = Arc::clone(store);
+    let metadata = Arc::clone(metadata);
+    MockServer::start(move |request| {
+        if request.headers.get("x-vault-token") != Some(&VAULT_TOKEN.to_string()) {
+            return Response::new(403, r#"{"errors":["permission denied"]}"#);
+        }
+        let mut store = store.lock().unwrap();
+        let mut metadata = metadata.lock().unwrap();
+        if let Some(path) = request.path.strip_prefix("/v1/secret/metadata/") {
+            if request.method == "PATCH" {
+                let body: Value = serde_json::from_str(&request.body).unwrap();
+                let custom_metadata = metadata.entry(path.to_string()).or_default();
+                for (k, v) in body["custom_metadata"].as_object().unwrap() {
+                    match v.as_str() {
+                        Some(v) => custom_metadata.insert(k.clone(), v.to_string()),
+                        None => custom_metadata.remove(k),
+                    };
+                }
+                return Response::new(204, "");
+            }
+            let keys: Vec<_> = store
+                .keys()
+                .filter_map(|key| key.strip_prefix(path))
+                .collect();
+            if keys.is_empty() {
+                return Response::new(404, r#"{"errors":[]}"#);
+            }
+            return Response::new(200, json!({ "data": { "keys": keys } }).to_string());
+        }
+        if let Some(path) = request.path.strip_prefix("/v1/secret/data/") {
+            return match request.method.as_str() {
+                "GET" => match store.get(path) {
+                    Some(data) => Response::new(
+                        200,
+                        json!({
+                            "data": {
+                                "data": data,
+                                "metadata": { "custom_metadata": metadata.get(path) }
+                            }
+                        })
+                        .to_string(),
+                    ),
+                    None => Response::new(404, r#"{"errors":[]}"#),
+                },
+                "POST" => {
+                    let body: Value = serde_json::from_str(&request.body).unwrap();
+                    let data = body["data"]
+                        .as_object()
+                        .unwrap()
+                        .iter()
+                        .map(|(k, v)| (k.clone(), v.as_str().unwrap().to_string()))
+                        .collect();
+                    store.insert(path.to_string(), data);
+                    Response::new(200, r#"{"data":{"version":1}}"#)
+                }
+                "DELETE" => {
+                    store.remove(path);
+                    Response::new(204, "")
+                }
+                _ => Response::new(405, ""),
+            };
+        }
+        Response::new(404, r#"{"errors":[]}"#)
+    })
+}
+
+const FILES: &[(&str, &str)] = &[
+    (PROJECT_ENV_EXAMPLE_1_FILE, "VAR1=VALUE1\nVAR2=VALUE2\n"),
+    (PROJECT_CFG_FILE, SETUP_1_CFG),
+    (
+        PROJECT_RUN_FILE,
+        r#"#!/bin/bash
+echo "VAR1=$VAR1"
+echo "VAR2=$VAR2"
+"#,
+    ),
+];
+
+/// The private envs of `setup_1` are in the vault, the store starts with the env `dev`.
+fn init_vault(label: &str) -> (cli_integration_test::IntegrationTestEnvironment, Store) {
+    let mut e = init_project(label, FILES);
+    e.add_file(
+        HOME_CFG_FILE,
+        format!(
+            r"
+projects:
+  - file: {file}
+    setups:
+      setup_1:
+        private_env_source: vault://secret/data/myapp/{{env}}
+    ",
+            file = e.path().unwrap().join(PROJECT_CFG_FILE).to_string_lossy(),
+        ),
+    );
+    e.setup();
+
+    let mut dev = BTreeMap::new();
+    dev.insert("VAR1".to_string(), "VAULT_VALUE1".to_string());
+    dev.insert("VAR2".to_string(), "VAULT_VALUE2".to_string());
+    let mut store = BTreeMap::new();
+    store.insert("myapp/dev".to_string(), dev);
+    (e, Arc::new(Mutex::new(store)))
+}
+
+#[test]
+fn cmd_vault_ls_and_run() {
+    let (e, store) = init_vault("cmd_vault_ls_and_run");
+    let server = vault_server(&store);
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .env("VAULT_ADDR", server.addr())
+        .env("VAULT_TOKEN", VAULT_TOKEN)
+        .arg("ls")
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("example1 (.example1)").count(1).eval(&r));
+    assert!(contains("dev (vault://secret/data/myapp/dev)")
+        .count(1)
+        .eval(&r));
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .env("VAULT_ADDR", server.addr())
+        .env("VAULT_TOKEN", VAULT_TOKEN)
+        .arg("run")
+        .args(&["-s", "setup_1"])
+        .args(&["-e", "dev"])
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("VAR1=VAULT_VALUE1").count(1).eval(&r));
+    assert!(contains("VAR2=VAULT_VALUE2").count(1).eval(&r));
+}
+
+#[test]
+fn cmd_vault_token_file() {
+    let (mut e, store) = init_vault("cmd_vault_token_file");
+    e.add_file("home/.vault-token", format!("{}\n", VAULT_TOKEN));
+    e.setup();
+    let server = vault_server(&store);
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .env("VAULT_ADDR", server.addr())
+        .env_remove("VAULT_TOKEN")
+        .arg("run")
+        .args(&["-s", "setup_1"])
+        .args(&["-e", "dev"])
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("VAR1=VAULT_VALUE1").count(1).eval(&r));
+}
+
+#[test]
+fn cmd_vault_sync_and_edit() {
+    let (mut e, store) = init_vault("cmd_vault_sync_and_edit");
+    store
+        .lock()
+        .unwrap()
+        .get_mut("myapp/dev")
+        .unwrap()
+        .remove("VAR2");
+    e.add_file(
+        "editor.sh",
+        r#"#!/bin/bash
+echo "VAR3=VALUE3" >> $1
+"#,
+    );
+    e.setup();
+    e.set_exec_permission("editor.sh").unwrap();
+    let server = vault_server(&store);
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .env("VAULT_ADDR", server.addr())
+        .env("VAULT_TOKEN", VAULT_TOKEN)
+        .arg("sync")
+        .arg("--copy")
+        .args(&["-s", "setup_1"])
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("files synchronized").eval(&r));
+    {
+        let store = store.lock().unwrap();
+        let dev = store.get("myapp/dev").unwrap();
+        assert_eq!(dev.get("VAR1").unwrap(), "VAULT_VALUE1");
+        assert_eq!(dev.get("VAR2").unwrap(), "VALUE2");
+    }
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .env("VAULT_ADDR", server.addr())
+        .env("VAULT_TOKEN", VAULT_TOKEN)
+        .arg("edit")
+        .arg("dev")
+        .arg("--copy")
+        .args(&["-s", "setup_1"])
+        .args(&[
+            "--editor",
+            &e.path().unwrap().join("editor.sh").to_string_lossy(),
+        ])
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("`dev` edited").eval(&r));
+    {
+        let store = store.lock().unwrap();
+        let dev = store.get("myapp/dev").unwrap();
+        assert_eq!(dev.get("VAR3").unwrap(), "VALUE3");
+    }
+    assert_eq!(
+        e.read_file(PROJECT_ENV_EXAMPLE_1_FILE),
+        "VAR1=VALUE1\nVAR2=VALUE2\nVAR3=VALUE3\n"
+    );
+}
+
+#[test]
+fn cmd_vault_new_request_fail() {
+    let (e, store) = init_vault("cmd_vault_new_request_fail");
+    let server = vault_server(&store);
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .env("VAULT_ADDR", server.addr())
+        .env("VAULT_TOKEN", "s.wrong")
+        .arg("new")
+        .arg("dev")
+        .arg("--private")
+        .args(&["-s", "setup_1"])
+        .assert()
+        .failure()
+        .to_string();
+    assert!(contains("403").count(1).eval(&r));
+    assert_eq!(store.lock().unwrap().get("myapp/dev").unwrap().len(), 2);
+}
+
+#[test]
+fn cmd_vault_new_aborted() {
+    let (e, store) = init_vault("cmd_vault_new_aborted");
+    let server = vault_server(&store);
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    command
+        .env("VAULT_ADDR", server.addr())
+        .env("VAULT_TOKEN", VAULT_TOKEN)
+        .arg("new")
+        .arg("staging")
+        .arg("--private")
+        .args(&["-s", "setup_1"])
+        .assert()
+        .failure();
+    assert!(store.lock().unwrap().get("myapp/staging").is_none());
+    let deletes: Vec<_> = server
+        .requests()
+        .into_iter()
+        .filter(|request| request.method == "DELETE")
+        .map(|request| request.path)
+        .collect();
+    assert_eq!(deletes, vec!["/v1/secret/data/myapp/staging".to_string()]);
+}
+
+#[test]
+fn cmd_vault_listed_once() {
+    let (e, store) = init_vault("cmd_vault_listed_once");
+    let server = vault_server(&store);
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    command
+        .env("VAULT_ADDR", server.addr())
+        .env("VAULT_TOKEN", VAULT_TOKEN)
+        .arg("use")
+        .args(&["setup_1", "dev"])
+        .assert()
+        .success();
+    let lists = || {
+        server
+            .requests()
+            .iter()
+            .filter(|request| request.query == "list=true")
+            .count()
+    };
+    let lists_before_run = lists();
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .env("VAULT_ADDR", server.addr())
+        .env("VAULT_TOKEN", VAULT_TOKEN)
+        .arg("run")
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("VAR1=VAULT_VALUE1").count(1).eval(&r));
+    assert_eq!(lists() - lists_before_run, 1);
+}
+
+#[test]
+fn cmd_vault_unreachable_public_env() {
+    let (e, _) = init_vault("cmd_vault_unreachable_public_env");
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .env("VAULT_ADDR", "http://127.0.0.1:1")
+        .env("VAULT_TOKEN", VAULT_TOKEN)
+        .arg("run")
+        .args(&["-s", "setup_1"])
+        .args(&["-e", "example1"])
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("VAR1=VALUE1").count(1).eval(&r));
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    command
+        .env("VAULT_ADDR", "http://127.0.0.1:1")
+        .env("VAULT_TOKEN", VAULT_TOKEN)
+        .arg("run")
+        .args(&["-s", "setup_1"])
+        .args(&["-e", "dev"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn cmd_vault_protected() {
+    let (mut e, store) = init_vault("cmd_vault_protected");
+    e.add_file(
+        "editor.sh",
+        r#"#!/bin/bash
+echo "VAR3=VALUE3" >> $1
+"#,
+    );
+    e.setup();
+    e.set_exec_permission("editor.sh").unwrap();
+    let metadata = Store::default();
+    let mut dev = BTreeMap::new();
+    dev.insert("sht_protected".to_string(), "true".to_string());
+    metadata
+        .lock()
+        .unwrap()
+        .insert("myapp/dev".to_string(), dev);
+    let server = vault_server_with_metadata(&store, &metadata);
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .env("VAULT_ADDR", server.addr())
+        .env("VAULT_TOKEN", VAULT_TOKEN)
+        .write_stdin("dev\n")
+        .arg("edit")
+        .arg("dev")
+        .arg("--copy")
+        .args(&["-s", "setup_1"])
+        .args(&[
+            "--editor",
+            &e.path().unwrap().join("editor.sh").to_string_lossy(),
+        ])
+        .assert()
+        .success()
+        .to_string();
+    assert!(contains("PROTECTED ENV `dev`").count(1).eval(&r));
+    assert_eq!(
+        store.lock().unwrap().get("myapp/dev").unwrap().get("VAR3"),
+        Some(&"VALUE3".to_string())
+    );
+    assert_eq!(
+        metadata
+            .lock()
+            .unwrap()
+            .get("myapp/dev")
+            .unwrap()
+            .get("sht_protected"),
+        Some(&"true".to_string())
+    );
+
+    let mut command = e.command(BIN_NAME).unwrap();
+    let r = command
+        .env("VAULT_ADDR", server.addr())
+        .env("VAULT_TOKEN", VAULT_TOKEN)
+        .write_stdin("\n")
+        .arg("run")
+        .args(&["-s", "setup_1"])
+        .args(&["-e", "dev"])
+        .assert()
+        .failure()
+        .to_string();
+    assert!(contains("protected env `dev` not confirmed")
+        .count(1)
+        .eval(&r));
+}